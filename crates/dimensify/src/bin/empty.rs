@@ -1,4 +1,4 @@
-use bevy::prelude::*;
+use bevy::{log::LogPlugin, prelude::*};
 use dimensify::DimensifyPlugin;
 use eyre::Result;
 
@@ -7,8 +7,11 @@ use dimensify::util;
 fn main() -> Result<()> {
     util::initialise()?;
     let mut app = App::new();
-    app.add_plugins(DefaultPlugins)
-        .add_plugins(DimensifyPlugin::default());
+    app.add_plugins(DefaultPlugins.set(LogPlugin {
+        custom_layer: dimensify_ui::console::capture_log_layer,
+        ..default()
+    }))
+    .add_plugins(DimensifyPlugin::default());
 
     app.run();
 
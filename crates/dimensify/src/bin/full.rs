@@ -48,6 +48,7 @@ fn main() -> Result<()> {
             })
             .set(LogPlugin {
                 filter: filter.join(","),
+                custom_layer: dimensify_ui::console::capture_log_layer,
                 ..default()
             }),
     )
@@ -0,0 +1,85 @@
+//! Headless telemetry export.
+//!
+//! ```text
+//! dimensify-telemetry-export <input.jsonl|input.rrd> <output.csv|.parquet|.mcap>
+//!     [--timeline <name>] [--path <path>]... [--from <time>] [--to <time>]
+//! ```
+
+use anyhow::{Context, bail};
+use dimensify::telemetry::{
+    TelemetryStore,
+    export::{TelemetryExportFormat, TelemetryExportSelection, export_telemetry},
+};
+
+const USAGE: &str = "usage: dimensify-telemetry-export <input.jsonl|input.rrd> <output.csv|.parquet|.mcap> [--timeline <name>] [--path <path>]... [--from <time>] [--to <time>]";
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let mut positional = Vec::new();
+    let mut selection = TelemetryExportSelection::new("sim_time");
+    let (mut from, mut to) = (f64::NEG_INFINITY, f64::INFINITY);
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .with_context(|| format!("{} needs a value", flag))
+        };
+        match arg.as_str() {
+            "--timeline" => selection.timeline = value("--timeline")?,
+            "--path" => selection.paths.push(value("--path")?),
+            "--from" => {
+                from = value("--from")?
+                    .parse()
+                    .context("--from must be a number")?
+            }
+            "--to" => to = value("--to")?.parse().context("--to must be a number")?,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ => positional.push(arg),
+        }
+    }
+    let [input, output] = positional.as_slice() else {
+        bail!(USAGE);
+    };
+    if from.is_finite() || to.is_finite() {
+        selection.range = Some((from, to));
+    }
+
+    let format = TelemetryExportFormat::from_path(output)
+        .with_context(|| format!("cannot tell the export format of {}", output))?;
+    if !format.is_supported() {
+        bail!(
+            "{} export is not enabled in this build (see the `telemetry_{}` feature)",
+            format.extension(),
+            format.extension()
+        );
+    }
+
+    let mut store = TelemetryStore::new(usize::MAX);
+    let imported = if input.ends_with(".rrd") {
+        import_rrd(&mut store, input)?
+    } else {
+        // no logger is installed here, so report skipped lines directly
+        dimensify::telemetry::import_jsonl_with(&mut store, input, |line_no, err| {
+            eprintln!("warning: skipping line {} of {}: {}", line_no, input, err)
+        })
+        .with_context(|| format!("failed to read {}", input))?
+    };
+    let exported = export_telemetry(&store, &selection, format, output)?;
+    println!(
+        "exported {} of {} events on timeline '{}' to {}",
+        exported, imported, selection.timeline, output
+    );
+    Ok(())
+}
+
+#[cfg(feature = "telemetry_rrd")]
+fn import_rrd(store: &mut TelemetryStore, path: &str) -> anyhow::Result<usize> {
+    dimensify::telemetry::import_rrd(store, path)
+}
+
+#[cfg(not(feature = "telemetry_rrd"))]
+fn import_rrd(_store: &mut TelemetryStore, path: &str) -> anyhow::Result<usize> {
+    bail!("reading {} requires the `telemetry_rrd` feature", path)
+}
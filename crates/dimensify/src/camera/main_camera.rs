@@ -2,6 +2,7 @@ use bevy::{anti_alias::fxaa::Fxaa, prelude::*};
 
 use crate::scene::preset;
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
+use dimensify_ui::command_palette::{PaletteAction, RegisterPaletteAction};
 
 #[derive(Component)]
 pub struct MainCamera;
@@ -31,9 +32,39 @@ impl Plugin for CameraPlugin {
         if self.with_sun {
             app.add_systems(Startup, preset::add_sun);
         }
+
+        app.register_palette_provider(std::sync::Arc::new(focus_actions));
     }
 }
 
+/// A "Focus" action per named entity, centring the orbit camera on it.
+fn focus_actions(world: &mut World) -> Vec<PaletteAction> {
+    let mut named =
+        world.query_filtered::<(Entity, &Name), (With<GlobalTransform>, Without<MainCamera>)>();
+    let mut entities: Vec<(Entity, String)> = named
+        .iter(world)
+        .map(|(entity, name)| (entity, name.to_string()))
+        .collect();
+    entities.sort_by(|a, b| a.1.cmp(&b.1));
+    entities
+        .into_iter()
+        .map(|(entity, name)| {
+            PaletteAction::new(format!("Focus: {}", name), move |world| {
+                let Some(focus) = world
+                    .get::<GlobalTransform>(entity)
+                    .map(GlobalTransform::translation)
+                else {
+                    return;
+                };
+                let mut cameras = world.query_filtered::<&mut PanOrbitCamera, With<MainCamera>>();
+                for mut camera in cameras.iter_mut(world) {
+                    camera.target_focus = focus;
+                }
+            })
+        })
+        .collect()
+}
+
 // #[derive(States)]
 // #[derive(Debug, Hash, Eq, PartialEq, Clone)]
 // pub enum MainCameraState {
@@ -10,6 +10,8 @@ pub mod constants;
 pub mod coordinate_system;
 pub mod graphics;
 pub mod plugins;
+#[cfg(all(feature = "protocol", not(target_arch = "wasm32")))]
+pub mod project;
 pub mod services;
 
 #[cfg(feature = "physics")]
@@ -23,6 +25,7 @@ pub mod scene;
 pub mod sim;
 #[cfg(feature = "protocol")]
 pub mod stream;
+pub mod tasks;
 #[cfg(feature = "protocol")]
 pub mod telemetry;
 pub mod test_scene;
@@ -78,7 +81,7 @@ impl PluginGroup for DimensifyPlugin {
     fn build(self) -> PluginGroupBuilder {
         let mut group = PluginGroupBuilder::start::<Self>();
 
-        group = group.add(graphics::plugin);
+        group = group.add(graphics::plugin).add(tasks::plugin);
         group = group
         .add(camera::CameraPlugin {
             with_ambient_light: self.with_ambient_light,
@@ -88,12 +91,18 @@ impl PluginGroup for DimensifyPlugin {
         // .add(sketching::plugin)
         ;
 
+        group = group.add(scene::gltf_export::plugin);
+
         if self.infinite_grid {
             group = group.add(graphics::infinite_grid_plugin);
         }
         if self.with_ui {
             // group = group.add(ui::plugin);
-            group = group.add(dimensify_ui::setup_ui);
+            group = group.add(dimensify_ui::setup_ui).add(ui::tasks_tab::plugin);
+            #[cfg(all(feature = "protocol", not(target_arch = "wasm32")))]
+            {
+                group = group.add(ui::widget_stream::plugin);
+            }
         }
 
         #[cfg(feature = "transport")]
@@ -106,6 +115,10 @@ impl PluginGroup for DimensifyPlugin {
         #[cfg(feature = "protocol")]
         {
             group = group.add(telemetry::plugin).add(stream::plugin);
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                group = group.add(project::plugin);
+            }
         }
 
         // #[cfg(feature = "robot")]
@@ -1,8 +1,9 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
+use dimensify_ui::command_palette::{PaletteAction, RegisterPaletteAction};
 use strum::{AsRefStr, EnumIter};
 
-use crate::collision_checker;
+use crate::{collision_checker, scene::DrivenTransform};
 
 pub mod collidable;
 
@@ -28,7 +29,18 @@ pub fn plugin(app: &mut App) {
         //     PreUpdate,
         //     remove_physics_system.run_if(on_event::<StateTransitionEvent<PhysicsState>>),
         // )
-        .register_type::<collidable::IgnoredColliders>();
+        .register_type::<collidable::IgnoredColliders>()
+        .register_required_components::<RigidBody, DrivenTransform>()
+        .register_palette_action(PaletteAction::new("Toggle physics", toggle_physics));
+}
+
+/// Switch between a static scene and the dynamic simulation.
+fn toggle_physics(world: &mut World) {
+    let next = match world.resource::<State<PhysicsState>>().get() {
+        PhysicsState::None => PhysicsState::Dynamic,
+        PhysicsState::Dynamic => PhysicsState::None,
+    };
+    world.resource_mut::<NextState<PhysicsState>>().set(next);
 }
 
 // hard to sync up the physics state with the rapier physics plugin.
@@ -0,0 +1,442 @@
+//! Project files: a whole session saved as one JSON document.
+//!
+//! A project holds the UI layout, the camera poses, the loaded robots with their joint
+//! values, protocol-spawned entities, widget definitions and the telemetry source.
+//! The File menu sends a [`ProjectRequest`]; set `DIMENSIFY_PROJECT` to open a project
+//! at start-up.
+
+use std::path::{Path, PathBuf};
+
+use bevy::{ecs::system::SystemState, platform::time::Instant, prelude::*};
+use bevy_panorbit_camera::PanOrbitCamera;
+use dimensify_protocol::{WorldCommand, prelude::ProtoComponent};
+use dimensify_ui::project::{ProjectDialog, ProjectRequest};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::main_camera::MainCamera,
+    stream::CommandLog,
+    tasks::Tasks,
+    telemetry::{TelemetryPlayback, TelemetrySettings, TelemetrySourceKind, TelemetryStore},
+    ui::widgets::{WidgetCommand, WidgetCommandQueue, WidgetRegistry},
+};
+
+/// Bumped when a project can no longer be read by older versions.
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Project {
+    pub version: u32,
+    /// The UI layout as KDL, see `dimensify_ui::current_layout_kdl`.
+    #[serde(default)]
+    pub layout: Option<String>,
+    /// Poses of the main cameras, in spawn order.
+    #[serde(default)]
+    pub cameras: Vec<CameraPose>,
+    #[serde(default)]
+    pub robots: Vec<ProjectRobot>,
+    /// Components of the top-level entities spawned through the protocol, with their
+    /// current transforms and their descendants nested, see `scene_file::scene_entities`.
+    #[serde(default)]
+    pub entities: Vec<Vec<ProtoComponent>>,
+    /// Commands defining the widgets, with the values users last set.
+    #[serde(default, with = "crate::ui::widgets::tagged_widgets")]
+    pub widgets: Vec<WidgetCommand>,
+    #[serde(default)]
+    pub telemetry: Option<ProjectTelemetry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraPose {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    /// Orbit of a pan-orbit camera as focus, yaw, pitch and radius.
+    #[serde(default)]
+    pub orbit: Option<(Vec3, f32, f32, f32)>,
+}
+
+/// A robot to load again from its URDF file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectRobot {
+    pub filename: String,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    #[serde(default)]
+    pub fixed_base: bool,
+    #[serde(default)]
+    pub ignored_linkpair_collision: Vec<(String, String)>,
+    #[serde(default)]
+    pub joint_values: Vec<(String, f32)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTelemetry {
+    pub source: TelemetrySourceKind,
+    pub max_events: usize,
+    pub timeline: String,
+    pub time: f64,
+    #[serde(default)]
+    pub loop_range: Option<(f64, f64)>,
+}
+
+pub fn plugin(app: &mut App) {
+    app.add_message::<ProjectRequest>()
+        .add_systems(PostStartup, open_project_from_env)
+        .add_systems(Update, handle_project_requests);
+}
+
+fn open_project_from_env(mut requests: MessageWriter<ProjectRequest>) {
+    if let Ok(path) = std::env::var("DIMENSIFY_PROJECT") {
+        requests.write(ProjectRequest::Open(PathBuf::from(path)));
+    }
+}
+
+fn handle_project_requests(
+    world: &mut World,
+    reader: &mut SystemState<MessageReader<ProjectRequest>>,
+) {
+    let requests: Vec<ProjectRequest> = reader.get_mut(world).read().cloned().collect();
+    for request in requests {
+        let started = Instant::now();
+        let (done, path, result) = match request {
+            ProjectRequest::Open(path) => {
+                let result = open_project(world, &path);
+                ("Opened", path, result)
+            }
+            ProjectRequest::Save(path) => {
+                let result = save_project(world, &path);
+                ("Saved", path, result)
+            }
+            // see `scene::gltf_export`
+            ProjectRequest::ExportGltf { .. } => continue,
+        };
+        match &result {
+            Ok(()) => info!("{} project {}", done, path.display()),
+            Err(err) => error!("Project {}: {}", path.display(), err),
+        }
+        world.resource_mut::<Tasks>().record(
+            "Project",
+            path.display().to_string(),
+            started,
+            result.clone(),
+        );
+        if let Some(mut dialog) = world.get_resource_mut::<ProjectDialog>() {
+            let status = result.map(|()| format!("{} {}", done, path.display()));
+            dialog.report(&path, status);
+        }
+    }
+}
+
+/// Capture the current session.
+pub fn collect_project(world: &mut World) -> Project {
+    let mut project = Project {
+        version: VERSION,
+        layout: dimensify_ui::current_layout_kdl(world),
+        ..default()
+    };
+
+    let mut cameras =
+        world.query_filtered::<(&Transform, Option<&PanOrbitCamera>), With<MainCamera>>();
+    project.cameras = cameras
+        .iter(world)
+        .map(|(transform, orbit)| CameraPose {
+            translation: transform.translation,
+            rotation: transform.rotation,
+            orbit: orbit.map(|orbit| {
+                (
+                    orbit.focus,
+                    orbit.yaw.unwrap_or_default(),
+                    orbit.pitch.unwrap_or_default(),
+                    orbit.radius.unwrap_or(1.0),
+                )
+            }),
+        })
+        .collect();
+
+    #[cfg(feature = "robot")]
+    {
+        use crate::robot::{RobotRoot, RobotState, urdf_loader::UrdfSource};
+
+        let mut robots =
+            world.query_filtered::<(&UrdfSource, &Transform, &RobotState), With<RobotRoot>>();
+        project.robots = robots
+            .iter(world)
+            .map(|(source, transform, state)| ProjectRobot {
+                filename: source.filename.clone(),
+                translation: transform.translation,
+                rotation: transform.rotation,
+                fixed_base: source.fixed_base,
+                ignored_linkpair_collision: source.ignored_linkpair_collision.clone(),
+                joint_values: state
+                    .robot_chain
+                    .iter_joints()
+                    .filter_map(|joint| Some((joint.name.clone(), joint.joint_position()?)))
+                    .collect(),
+            })
+            .collect();
+    }
+
+    #[cfg(feature = "transport")]
+    {
+        project.entities = crate::services::scene_file::scene_entities(world);
+    }
+
+    if let Some(registry) = world.get_resource::<WidgetRegistry>() {
+        project.widgets = registry.definitions().to_vec();
+    }
+
+    if let (Some(settings), Some(playback)) = (
+        world.get_resource::<TelemetrySettings>(),
+        world.get_resource::<TelemetryPlayback>(),
+    ) {
+        project.telemetry = Some(ProjectTelemetry {
+            source: settings.source.clone(),
+            max_events: settings.max_events,
+            timeline: playback.timeline.clone(),
+            time: playback.time,
+            loop_range: playback.loop_range,
+        });
+    }
+    project
+}
+
+pub fn save_project(world: &mut World, path: &Path) -> Result<(), String> {
+    let project = collect_project(world);
+    let json = serde_json::to_string_pretty(&project).map_err(|err| err.to_string())?;
+    std::fs::write(path, json).map_err(|err| err.to_string())
+}
+
+pub fn open_project(world: &mut World, path: &Path) -> Result<(), String> {
+    let content = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let project: Project =
+        serde_json::from_str(&content).map_err(|err| format!("invalid project: {}", err))?;
+    if project.version > VERSION {
+        return Err(format!(
+            "project version {} is newer than the supported version {}",
+            project.version, VERSION
+        ));
+    }
+    apply_project(world, project)
+}
+
+/// Replace the current session with `project`. Robots and entities are spawned over the
+/// next frames by the usual loaders. Fails if the telemetry cannot be loaded, after the
+/// rest of the project was applied.
+pub fn apply_project(world: &mut World, project: Project) -> Result<(), String> {
+    clear_session(world);
+
+    if let Some(layout) = &project.layout
+        && let Err(err) = dimensify_ui::apply_layout_kdl(world, layout)
+    {
+        warn!("Project layout not applied: {}", err);
+    }
+
+    let mut cameras =
+        world.query_filtered::<(&mut Transform, Option<&mut PanOrbitCamera>), With<MainCamera>>();
+    for ((mut transform, orbit), pose) in cameras.iter_mut(world).zip(&project.cameras) {
+        transform.translation = pose.translation;
+        transform.rotation = pose.rotation;
+        if let (Some(mut orbit), Some((focus, yaw, pitch, radius))) = (orbit, pose.orbit) {
+            orbit.target_focus = focus;
+            orbit.target_yaw = yaw;
+            orbit.target_pitch = pitch;
+            orbit.target_radius = radius;
+            orbit.force_update = true;
+        }
+    }
+
+    #[cfg(feature = "robot")]
+    for robot in project.robots {
+        use crate::robot::urdf_loader::{UrdfLoadRequest, UrdfLoadRequestParams};
+
+        let params = UrdfLoadRequestParams {
+            transform: Transform::from_translation(robot.translation).with_rotation(robot.rotation),
+            fixed_base: robot.fixed_base,
+            ignored_linkpair_collision: robot.ignored_linkpair_collision,
+            initial_joint_values: robot.joint_values.into_iter().collect(),
+            ..default()
+        };
+        world.write_message(UrdfLoadRequest::new(robot.filename, Some(params)));
+    }
+    #[cfg(not(feature = "robot"))]
+    if !project.robots.is_empty() {
+        warn!(
+            "Skipping {} robots of the project: the `robot` feature is disabled",
+            project.robots.len()
+        );
+    }
+
+    if let Some(mut log) = world.get_resource_mut::<CommandLog>() {
+        log.commands.extend(
+            project
+                .entities
+                .into_iter()
+                .map(|components| (Entity::PLACEHOLDER, WorldCommand::Spawn { components })),
+        );
+    }
+
+    if let Some(mut queue) = world.get_resource_mut::<WidgetCommandQueue>() {
+        for command in project.widgets {
+            queue.push(command);
+        }
+    }
+
+    match project.telemetry {
+        Some(telemetry) => apply_telemetry(world, telemetry),
+        None => Ok(()),
+    }
+}
+
+/// Remove what a project brings along, so opening one does not pile onto the last.
+fn clear_session(world: &mut World) {
+    #[cfg(feature = "robot")]
+    {
+        let mut robots = world.query_filtered::<Entity, With<crate::robot::RobotRoot>>();
+        let robots: Vec<Entity> = robots.iter(world).collect();
+        for entity in robots {
+            world.despawn(entity);
+        }
+    }
+
+    #[cfg(feature = "transport")]
+    {
+        use crate::services::scene_sync::ProtocolComponents;
+
+        let mut entities = world.query_filtered::<Entity, With<ProtocolComponents>>();
+        let entities: Vec<Entity> = entities.iter(world).collect();
+        for entity in entities {
+            // children are already gone with their parent
+            if let Ok(entity) = world.get_entity_mut(entity) {
+                entity.despawn();
+            }
+        }
+    }
+
+    let ids: Vec<String> = world
+        .get_resource::<WidgetRegistry>()
+        .map(|registry| {
+            registry
+                .definitions()
+                .iter()
+                .map(|command| command.id().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(mut queue) = world.get_resource_mut::<WidgetCommandQueue>() {
+        for id in ids {
+            queue.push(WidgetCommand::Remove { id });
+        }
+    }
+}
+
+/// Switch to the project's telemetry source and reload it. The current telemetry is
+/// kept if the source cannot be read.
+fn apply_telemetry(world: &mut World, telemetry: ProjectTelemetry) -> Result<(), String> {
+    if !world.contains_resource::<TelemetrySettings>() {
+        return Ok(());
+    }
+
+    let mut store = TelemetryStore::new(telemetry.max_events);
+    let imported = match &telemetry.source {
+        // live telemetry keeps what has been recorded so far
+        TelemetrySourceKind::Local => None,
+        TelemetrySourceKind::FileReplay { path } => {
+            Some(crate::telemetry::import_jsonl(&mut store, path))
+        }
+        #[cfg(feature = "telemetry_rrd")]
+        TelemetrySourceKind::RrdReplay { path } => {
+            Some(crate::telemetry::import_rrd(&mut store, path))
+        }
+        #[cfg(not(feature = "telemetry_rrd"))]
+        TelemetrySourceKind::RrdReplay { path } => Some(Err(anyhow::anyhow!(
+            "reading {} requires the `telemetry_rrd` feature",
+            path
+        ))),
+    };
+    if let Some(imported) = imported {
+        let count = imported.map_err(|err| format!("telemetry not loaded: {:#}", err))?;
+        info!("Loaded {} telemetry events for the project", count);
+        world.insert_resource(store);
+    }
+
+    {
+        let mut settings = world.resource_mut::<TelemetrySettings>();
+        settings.source = telemetry.source;
+        settings.max_events = telemetry.max_events;
+    }
+    if let Some(mut playback) = world.get_resource_mut::<TelemetryPlayback>() {
+        playback.timeline = telemetry.timeline;
+        playback.seek(telemetry.time);
+        playback.playing = false;
+        playback.loop_range = telemetry.loop_range;
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "transport"))]
+mod tests {
+    use bevy::prelude::*;
+    use dimensify_protocol::{ProtoComponent, WorldCommand};
+
+    use super::{apply_project, collect_project};
+    use crate::{
+        services::{
+            protocol_response::controller::remove_component, scene_sync::ProtocolComponents,
+        },
+        stream::CommandLog,
+    };
+
+    #[test]
+    fn entities_round_trip_with_their_current_state() {
+        let mut world = World::new();
+        world.init_resource::<CommandLog>();
+        let arm = world
+            .spawn((
+                Name::new("arm"),
+                ProtocolComponents(vec![
+                    ProtoComponent::Name("arm".to_string()),
+                    ProtoComponent::Transform {
+                        translation: Vec3::X,
+                        rotation: Quat::IDENTITY,
+                        scale: Vec3::ONE,
+                    },
+                ]),
+                // moved in the viewer after it was spawned
+                Transform::from_xyz(3.0, 0.0, 0.0),
+                GlobalTransform::from_translation(Vec3::new(3.0, 0.0, 0.0)),
+            ))
+            .id();
+        world.spawn((
+            ProtocolComponents(vec![ProtoComponent::Name("hand".to_string())]),
+            Transform::from_xyz(0.0, 1.0, 0.0),
+            GlobalTransform::from_translation(Vec3::new(3.0, 1.0, 0.0)),
+            ChildOf(arm),
+        ));
+        let name = world.components().component_id::<Name>().unwrap();
+        remove_component(&mut world.commands().entity(arm), name.into());
+        world.flush();
+
+        let json = serde_json::to_string(&collect_project(&mut world)).unwrap();
+        apply_project(&mut world, serde_json::from_str(&json).unwrap()).unwrap();
+
+        let mut protocol_entities = world.query::<&ProtocolComponents>();
+        assert_eq!(protocol_entities.iter(&world).count(), 0);
+        let log = world.resource::<CommandLog>();
+        let [(_, WorldCommand::Spawn { components })] = log.commands.as_slice() else {
+            panic!("expected one top-level spawn, got {:?}", log.commands);
+        };
+        assert!(
+            !components
+                .iter()
+                .any(|c| matches!(c, ProtoComponent::Name(_)))
+        );
+        assert!(components.iter().any(|c| matches!(
+            c,
+            ProtoComponent::Transform { translation, .. } if *translation == Vec3::new(3.0, 0.0, 0.0)
+        )));
+        let Some(ProtoComponent::Children(children)) = components.last() else {
+            panic!("expected the hand nested in the arm, got {:?}", components);
+        };
+        assert!(matches!(&children[0][0], ProtoComponent::Name(name) if name == "hand"));
+    }
+}
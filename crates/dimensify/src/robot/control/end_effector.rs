@@ -1,12 +1,13 @@
 use std::time::Duration;
 
-use bevy::{prelude::*, time::common_conditions::on_timer};
+use bevy::{platform::time::Instant, prelude::*, time::common_conditions::on_timer};
 use k::{InverseKinematicsSolver, JacobianIkSolver};
 
 use crate::{
     camera::window_camera::{FloatingCamera, build_camera_to_egui_img_texture},
     coordinate_system::prelude::*,
     robot::{RobotLink, RobotState, control::DesireRobotState},
+    tasks::Tasks,
     util::{exponential_iterator::ExponentialIterator, math_trait_ext::BevyQuatDistanceTrait},
 };
 use bevy_egui::EguiUserTextures;
@@ -14,6 +15,10 @@ use bevy_egui::EguiUserTextures;
 #[cfg(feature = "physics")]
 use crate::robot::urdf_loader::{RobotLinkInitOption, RobotLinkInitOptions};
 
+/// IK batches taking longer than this are listed in the tasks tab, as are failed ones;
+/// batches ending alike are counted into one task.
+const SLOW_IK_BATCH: Duration = Duration::from_millis(50);
+
 pub fn plugin(app: &mut App) {
     app.register_type::<EndEffectorMode>()
         .register_type::<EndEffectorTarget>()
@@ -258,6 +263,7 @@ fn ee_target_to_target_joint_state(
     // mut q_robot_state: Query<(&mut RobotState, &mut DesireRobotState)>,
     mut ee_target: Query<&mut EndEffectorTarget, Changed<EndEffectorTarget>>,
     mut gizmos: Gizmos,
+    mut tasks: ResMut<Tasks>,
 ) {
     if ee_target.iter().count() == 0 {
         return;
@@ -273,6 +279,10 @@ fn ee_target_to_target_joint_state(
     // let show_gizmo = false;
     let show_gizmo = true;
 
+    let started = Instant::now();
+    let robots = q_robot_state.iter().len();
+    let mut solved = 0;
+
     for (entity, robot_state) in q_robot_state.iter_mut() {
         // for (mut robot_state, mut desire_robot_state) in q_robot_state.iter_mut() {
 
@@ -422,6 +432,7 @@ fn ee_target_to_target_joint_state(
                         real_serial_link.unwrap(), // unwrap is safe here to get inner value
                         Some(arm.joint_positions().to_vec()),
                     ));
+                    solved += 1;
                     break;
                     // }
                 }
@@ -431,4 +442,17 @@ fn ee_target_to_target_joint_state(
             }
         }
     }
+
+    if solved < robots || started.elapsed() >= SLOW_IK_BATCH {
+        let result = match robots - solved {
+            0 => Ok(()),
+            failed => Err(format!("no solution for {} of {} robots", failed, robots)),
+        };
+        tasks.record_repeated(
+            "IK",
+            format!("end effector target, {} robot(s)", robots),
+            started,
+            result,
+        );
+    }
 }
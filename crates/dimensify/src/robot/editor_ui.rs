@@ -41,7 +41,7 @@ use dimensify_ui::{
 
 fn setup_panel_registry(mut registry: ResMut<tabs::PanelRegistry>) {
     registry.register(PanelEntry {
-        title: "Robot State",
+        title: "Robot State".into(),
         location: PanelLocation::Left,
         default_enabled: true,
         factory: std::sync::Arc::new(|| Box::new(RobotStateEditorWindow::default())),
@@ -68,7 +68,7 @@ impl Default for RobotStateEditorWindow {
 
 impl tabs::ViewerTab for RobotStateEditorWindow {
     // type State = EditorState;
-    fn title(&self) -> &'static str {
+    fn title(&self) -> &str {
         "Robot State"
     }
 
@@ -66,7 +66,7 @@ impl Plugin for RobotPlugin {
     }
 }
 
-use crate::robot::sync_state::RemoteRobotState;
+pub use crate::robot::sync_state::RemoteRobotState;
 
 #[derive(Component, Debug, Reflect)]
 #[reflect(from_reflect = false)]
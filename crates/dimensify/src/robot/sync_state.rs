@@ -112,6 +112,26 @@ type ChangedRobotStateWithRemote<'a, 'b, 'c> = Query<
 #[derive(Component, Default, Reflect)]
 pub struct RemoteRobotState(Vec<(String, f32)>);
 
+impl RemoteRobotState {
+    /// Request new positions for the given joints, keeping any other requested joints.
+    pub fn set_joint_positions(&mut self, joints: impl IntoIterator<Item = (String, f32)>) {
+        for (name, position) in joints {
+            match self.0.iter_mut().find(|(joint, _)| *joint == name) {
+                Some((_, value)) => *value = position,
+                None => self.0.push((name, position)),
+            }
+        }
+    }
+
+    /// The requested position of a joint, if any.
+    pub fn joint_position(&self, name: &str) -> Option<f32> {
+        self.0
+            .iter()
+            .find(|(joint, _)| joint == name)
+            .map(|(_, position)| *position)
+    }
+}
+
 fn sync_robot_state_to_control_component(mut robots: ChangedRobotStateWithRemote) -> Result<()> {
     for (robot_state, mut remote_robot_state) in &mut robots {
         // we bypass the change detection here to avoid infinite recursion
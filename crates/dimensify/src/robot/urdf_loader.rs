@@ -1,13 +1,15 @@
 use crate::{
     coordinate_system::prelude::*,
     robot::{RobotLink, RobotState},
+    tasks::{TaskId, Tasks},
 };
 use bevy::{
     app::App,
-    asset::AssetLoadError,
+    asset::{AssetLoadError, LoadState, RecursiveDependencyLoadState},
     ecs::{relationship::RelatedSpawnerCommands, system::EntityCommands},
     platform::collections::HashMap,
 };
+use dimensify_ui::command_palette::{PaletteAction, RegisterPaletteAction};
 
 // use eyre::Result;
 use std::sync::{Arc, Mutex};
@@ -87,6 +89,15 @@ impl UrdfLoadRequestParams {
     }
 }
 
+/// The file a robot was loaded from and the load parameters kept after loading, so the
+/// robot can be loaded again, e.g. when a project is reopened.
+#[derive(Component, Debug, Clone)]
+pub struct UrdfSource {
+    pub filename: String,
+    pub fixed_base: bool,
+    pub ignored_linkpair_collision: Vec<(String, String)>,
+}
+
 #[derive(Message, Debug, Default)]
 pub struct UrdfLoadRequest {
     /// file to load
@@ -122,6 +133,10 @@ pub struct PendingUrdfAsset(
     )>,
 );
 
+/// Task of each pending URDF load, see [`Tasks`].
+#[derive(Debug, Default, Resource)]
+struct UrdfLoadTasks(HashMap<AssetId<UrdfAsset>, TaskId>);
+
 #[derive(Message, Debug)]
 pub struct UrdfAssetLoadedMessage(
     pub(crate)  (
@@ -136,6 +151,7 @@ pub fn plugin(app: &mut App) {
         .add_message::<UrdfLoadRequest>()
         .add_message::<UrdfAssetLoadedMessage>()
         .init_resource::<PendingUrdfAsset>()
+        .init_resource::<UrdfLoadTasks>()
         .add_plugins(assets_loader::urdf::plugin)
         // handle incoming request to load urdf
         .add_systems(
@@ -158,6 +174,15 @@ pub fn plugin(app: &mut App) {
                 // .pipe(error_to_toast)
                 .run_if(on_message::<UrdfAssetLoadedMessage>),
         );
+    app.register_palette_action(PaletteAction::with_argument(
+        "Load URDF",
+        "path/to/robot.urdf",
+        |path, world| {
+            if !path.is_empty() {
+                world.write_message(UrdfLoadRequest::new(path.to_string(), None));
+            }
+        },
+    ));
 }
 
 /// request asset server to begin the load
@@ -165,37 +190,69 @@ fn load_urdf_request_handler(
     mut reader: MessageReader<UrdfLoadRequest>,
     asset_server: Res<AssetServer>,
     mut pending_urdf_asset: ResMut<PendingUrdfAsset>,
+    mut load_tasks: ResMut<UrdfLoadTasks>,
+    mut tasks: ResMut<Tasks>,
 ) {
     for event in reader.read() {
-        pending_urdf_asset.0.push((
-            asset_server.load(event.filename.clone()),
-            event.params.clone(),
-        ));
+        let handle: Handle<UrdfAsset> = asset_server.load(event.filename.clone());
+        let task = tasks.start("URDF", event.filename.clone());
+        tasks.cancellable(task);
+        load_tasks.0.insert(handle.id(), task);
+        pending_urdf_asset.0.push((handle, event.params.clone()));
     }
 }
 
 /// This keep polling the asset server to check if the asset is loaded.
 /// If it is loaded, then it will trigger the next event.
+/// Loads whose task was cancelled are dropped.
 fn track_urdf_loading_state(
     server: Res<AssetServer>,
     mut pending_urdf_asset: ResMut<PendingUrdfAsset>,
     mut writer: MessageWriter<UrdfAssetLoadedMessage>,
+    mut load_tasks: ResMut<UrdfLoadTasks>,
+    mut tasks: ResMut<Tasks>,
 ) -> Result<()> {
     let original_length = pending_urdf_asset.0.len();
+    let mut error = None;
     {
         let pending_urdf_asset = pending_urdf_asset.bypass_change_detection();
 
         let mut tmp_vec = std::mem::take(&mut pending_urdf_asset.0);
 
         for val in &mut tmp_vec.drain(..) {
-            match server.get_load_states(val.0.id()) {
-                Some((_, _, bevy::asset::RecursiveDependencyLoadState::Loaded)) => {
+            let id = val.0.id();
+            let task = load_tasks.0.get(&id).copied();
+            if let Some(task) = task
+                && tasks.cancel_requested(task)
+            {
+                load_tasks.0.remove(&id);
+                tasks.cancelled(task);
+                continue;
+            }
+            match server.get_load_states(id) {
+                Some((_, _, RecursiveDependencyLoadState::Loaded)) => {
+                    if let Some(task) = load_tasks.0.remove(&id) {
+                        tasks.finish(task);
+                    }
                     writer.write(UrdfAssetLoadedMessage(val));
                 }
-                Some((_, _, bevy::asset::RecursiveDependencyLoadState::Failed(err))) => {
-                    return Err(UrdfAssetLoadingError::FailedToLoadUrdfAsset(err).into());
+                Some((_, _, RecursiveDependencyLoadState::Failed(err))) => {
+                    if let Some(task) = load_tasks.0.remove(&id) {
+                        tasks.fail(task, err.to_string());
+                    }
+                    // keep tracking the other loads before reporting
+                    error = Some(UrdfAssetLoadingError::FailedToLoadUrdfAsset(err));
+                }
+                state => {
+                    if let Some(task) = task {
+                        let detail = match state {
+                            Some((LoadState::Loaded, _, _)) => "loading textures",
+                            _ => "reading URDF and meshes",
+                        };
+                        tasks.set_detail(task, detail);
+                    }
+                    pending_urdf_asset.0.push(val);
                 }
-                _ => pending_urdf_asset.0.push(val),
             };
         }
     }
@@ -203,7 +260,10 @@ fn track_urdf_loading_state(
         // now triggers the changes
         pending_urdf_asset.set_changed();
     }
-    Ok(())
+    match error {
+        Some(err) => Err(err.into()),
+        None => Ok(()),
+    }
 }
 
 #[derive(Component, Debug, Default)]
@@ -402,6 +462,11 @@ fn load_urdf_meshes(
         }) = urdf_assets.remove(handle)
         {
             let mut params = params.lock().unwrap();
+            let source = handle.path().map(|path| UrdfSource {
+                filename: path.to_string(),
+                fixed_base: params.fixed_base,
+                ignored_linkpair_collision: params.ignored_linkpair_collision.clone(),
+            });
             let mut robot_state = RobotState::new(urdf_robot.clone(), [].into());
 
             // apply any user-provided configuration
@@ -567,6 +632,9 @@ fn load_urdf_meshes(
                     }
                 });
             robot_root.insert(robot_state);
+            if let Some(source) = source {
+                robot_root.insert(source);
+            }
 
             // // check if there are any unused params. If there are, then we will show a warning
             // if !params.initial_joint_values.is_empty() {
@@ -1,18 +1,26 @@
-use bevy::prelude::*;
+use bevy::{asset::RecursiveDependencyLoadState, prelude::*};
 use bevy_gaussian_splatting::{
     CloudSettings, GaussianCamera, GaussianMode, GaussianSplattingPlugin, PlanarGaussian3dHandle,
 };
 
-use crate::camera::main_camera;
+use crate::{
+    camera::main_camera,
+    tasks::{TaskId, Tasks},
+};
 
 pub fn plugin(app: &mut App) {
     app.add_plugins(GaussianSplattingPlugin)
         .add_event::<GaussianSplattingSceneLoadRequest>()
+        .init_resource::<SplatLoadTasks>()
         .add_systems(
             PreUpdate,
             load_gaussian_splatting_scene_handler
                 .run_if(on_event::<GaussianSplattingSceneLoadRequest>),
         )
+        .add_systems(
+            Update,
+            track_splat_loads.run_if(|loads: Res<SplatLoadTasks>| !loads.0.is_empty()),
+        )
         // spawn the gaussian camera
         .add_systems(
             // NOTE: this only works for the first camera
@@ -57,15 +65,23 @@ type MainCameraWithoutGaussianCameraQuery<'a, 'b> = Query<
     ),
 >;
 
+/// Clouds still loading, with their task in the tasks tab.
+#[derive(Resource, Default)]
+struct SplatLoadTasks(Vec<(Entity, TaskId)>);
+
 fn load_gaussian_splatting_scene_handler(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut reader: EventReader<GaussianSplattingSceneLoadRequest>,
     // mut gaussian_assets: ResMut<Assets<GaussianCloud>>,
     mut q_main_camera: MainCameraWithoutGaussianCameraQuery,
+    mut tasks: ResMut<Tasks>,
+    mut loads: ResMut<SplatLoadTasks>,
 ) {
     for event in reader.read() {
-        commands.spawn((
+        let task = tasks.start("Gaussian splat", event.path.clone());
+        tasks.cancellable(task);
+        let entity = commands.spawn((
             PlanarGaussian3dHandle(asset_server.load(&event.path)),
             CloudSettings {
                 gaussian_mode: GaussianMode::Gaussian3d,
@@ -74,9 +90,38 @@ fn load_gaussian_splatting_scene_handler(
             event.transform,
             Name::new("GaussianCloud"),
         ));
+        loads.0.push((entity.id(), task));
 
         // do we need the following?
         // #[cfg(feature = "gsplat")]
         // tonemapping: Tonemapping::None,
     }
 }
+
+/// End the task of each cloud once its asset loaded or failed; a cancelled load
+/// despawns the cloud.
+fn track_splat_loads(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    clouds: Query<&PlanarGaussian3dHandle>,
+    mut tasks: ResMut<Tasks>,
+    mut loads: ResMut<SplatLoadTasks>,
+) {
+    loads.0.retain(|&(entity, task)| {
+        let Ok(cloud) = clouds.get(entity) else {
+            tasks.fail(task, "the cloud was removed");
+            return false;
+        };
+        if tasks.cancel_requested(task) {
+            commands.entity(entity).despawn();
+            tasks.cancelled(task);
+            return false;
+        }
+        match asset_server.get_recursive_dependency_load_state(cloud.0.id()) {
+            Some(RecursiveDependencyLoadState::Loaded) => tasks.finish(task),
+            Some(RecursiveDependencyLoadState::Failed(err)) => tasks.fail(task, err.to_string()),
+            _ => return true,
+        }
+        false
+    });
+}
@@ -0,0 +1,533 @@
+//! Export of the visible scene to glTF 2.0.
+//!
+//! Every visible entity with a mesh is written together with its ancestors, so the
+//! hierarchy and local transforms survive. Materials keep their base color, metallic,
+//! roughness, emission, alpha mode and sidedness; textures are not exported. A `.glb`
+//! path gets a single binary file, any other path a `.gltf` document with its buffer in a
+//! `.bin` file next to it.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::AssetId,
+    ecs::system::SystemState,
+    mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+    platform::time::Instant,
+    prelude::*,
+};
+use dimensify_ui::project::{ProjectDialog, ProjectRequest};
+use serde_json::{Value, json};
+
+use crate::tasks::Tasks;
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const FLOAT: u32 = 5126;
+
+pub fn plugin(app: &mut App) {
+    app.add_message::<ProjectRequest>()
+        .add_systems(Update, handle_export_requests);
+}
+
+/// Export requests of the File menu.
+fn handle_export_requests(
+    world: &mut World,
+    reader: &mut SystemState<MessageReader<ProjectRequest>>,
+) {
+    let requests: Vec<(PathBuf, bool)> = reader
+        .get_mut(world)
+        .read()
+        .filter_map(|request| match request {
+            ProjectRequest::ExportGltf { path, joints } => Some((path.clone(), *joints)),
+            _ => None,
+        })
+        .collect();
+    for (path, joints) in requests {
+        let status = run_gltf_export(world, &path, joints)
+            .map(|meshes| format!("Exported {} meshes to {}", meshes, path.display()));
+        if let Some(mut dialog) = world.get_resource_mut::<ProjectDialog>() {
+            dialog.report(&path, status);
+        }
+    }
+}
+
+/// [`export_gltf`], logged and recorded in the tasks tab.
+pub fn run_gltf_export(world: &mut World, path: &Path, joints: bool) -> Result<usize, String> {
+    let started = Instant::now();
+    let result = export_gltf(world, path, joints).map_err(|err| format!("{:#}", err));
+    match &result {
+        Ok(meshes) => info!("Exported {} meshes to {}", meshes, path.display()),
+        Err(err) => error!("glTF export to {} failed: {}", path.display(), err),
+    }
+    if let Some(mut tasks) = world.get_resource_mut::<Tasks>() {
+        tasks.record(
+            "glTF export",
+            path.display().to_string(),
+            started,
+            result.as_ref().map(|_| ()).map_err(Clone::clone),
+        );
+    }
+    result
+}
+
+/// Write the visible scene to `path` and return the number of meshes written.
+///
+/// With `joints`, robots are exported in their current joint configuration, which is
+/// also stored in the `extras` of their root node; otherwise in their zero configuration.
+pub fn export_gltf(world: &mut World, path: &Path, joints: bool) -> anyhow::Result<usize> {
+    let is_glb = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("glb"));
+    let bin_name = path.with_extension("bin");
+    let bin_uri = bin_name
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned());
+    let (mut document, bin, meshes) = gltf_document(world, joints);
+    if is_glb {
+        std::fs::write(path, glb_bytes(&document, &bin)?)?;
+    } else {
+        document["buffers"][0]["uri"] = json!(bin_uri);
+        std::fs::write(path, serde_json::to_vec_pretty(&document)?)?;
+        std::fs::write(&bin_name, &bin)?;
+    }
+    Ok(meshes)
+}
+
+/// The glTF JSON of the visible scene, its binary buffer and the number of meshes.
+pub fn gltf_document(world: &mut World, joints: bool) -> (Value, Vec<u8>, usize) {
+    #[allow(unused_mut)]
+    let mut overrides: HashMap<Entity, Transform> = HashMap::new();
+    #[allow(unused_mut)]
+    let mut extras: HashMap<Entity, Value> = HashMap::new();
+    #[cfg(feature = "robot")]
+    robot_poses(world, joints, &mut overrides, &mut extras);
+    #[cfg(not(feature = "robot"))]
+    let _ = joints;
+
+    let mut query = world.query::<(
+        Entity,
+        Option<&Name>,
+        &Transform,
+        Option<&Mesh3d>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        Option<&Children>,
+        Option<&ChildOf>,
+        Option<&InheritedVisibility>,
+    )>();
+
+    // visible meshes and their ancestors
+    let visible_meshes: Vec<Entity> = query
+        .iter(world)
+        .filter(|item| item.3.is_some() && item.7.is_none_or(|visibility| visibility.get()))
+        .map(|item| item.0)
+        .collect();
+    let mut included = HashSet::new();
+    for entity in visible_meshes {
+        let mut current = Some(entity);
+        while let Some(entity) = current
+            && included.insert(entity)
+        {
+            current = world.get::<ChildOf>(entity).map(ChildOf::parent);
+        }
+    }
+    let mut order: Vec<Entity> = included
+        .into_iter()
+        .filter(|entity| query.get(world, *entity).is_ok())
+        .collect();
+    order.sort();
+    let exported: HashMap<Entity, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(index, entity)| (*entity, index))
+        .collect();
+
+    let meshes_assets = world.resource::<Assets<Mesh>>();
+    let materials_assets = world.get_resource::<Assets<StandardMaterial>>();
+    let mut buffer = BufferBuilder::default();
+    let mut meshes: Vec<Value> = Vec::new();
+    let mut mesh_indices: HashMap<(AssetId<Mesh>, Option<AssetId<StandardMaterial>>), usize> =
+        HashMap::new();
+    let mut materials: Vec<Value> = Vec::new();
+    let mut material_indices: HashMap<AssetId<StandardMaterial>, usize> = HashMap::new();
+    let mut nodes = Vec::with_capacity(order.len());
+    let mut scene_roots = Vec::new();
+
+    for entity in &order {
+        let Ok((_, name, transform, mesh, material, children, parent, _)) =
+            query.get(world, *entity)
+        else {
+            continue;
+        };
+        let transform = overrides.get(entity).unwrap_or(transform);
+        let mut node = json!({
+            "translation": transform.translation.to_array(),
+            "rotation": transform.rotation.to_array(),
+            "scale": transform.scale.to_array(),
+        });
+        if let Some(name) = name {
+            node["name"] = json!(name.as_str());
+        }
+        if let Some(extras) = extras.get(entity) {
+            node["extras"] = extras.clone();
+        }
+        let children: Vec<usize> = children
+            .into_iter()
+            .flatten()
+            .filter_map(|child| exported.get(child).copied())
+            .collect();
+        if !children.is_empty() {
+            node["children"] = json!(children);
+        }
+        if parent.is_none_or(|parent| !exported.contains_key(&parent.parent())) {
+            scene_roots.push(nodes.len());
+        }
+
+        if let Some(mesh) = mesh {
+            let material_id = material.map(|material| material.id());
+            let key = (mesh.id(), material_id);
+            let index = match mesh_indices.get(&key).copied() {
+                Some(index) => Some(index),
+                None => meshes_assets.get(mesh.id()).and_then(|asset| {
+                    let material = material_id.and_then(|id| {
+                        if let Some(index) = material_indices.get(&id) {
+                            return Some(*index);
+                        }
+                        let material = material_json(materials_assets?.get(id)?);
+                        materials.push(material);
+                        material_indices.insert(id, materials.len() - 1);
+                        Some(materials.len() - 1)
+                    });
+                    let primitive = primitive_json(asset, material, &mut buffer)?;
+                    meshes.push(json!({ "primitives": [primitive] }));
+                    mesh_indices.insert(key, meshes.len() - 1);
+                    Some(meshes.len() - 1)
+                }),
+            };
+            if let Some(index) = index {
+                node["mesh"] = json!(index);
+            }
+        }
+        nodes.push(node);
+    }
+
+    let mut document = json!({
+        "asset": { "version": "2.0", "generator": "dimensify" },
+        "scene": 0,
+        "scenes": [{ "nodes": scene_roots }],
+        "nodes": nodes,
+    });
+    let mesh_count = meshes.len();
+    if !meshes.is_empty() {
+        document["meshes"] = json!(meshes);
+        document["accessors"] = json!(buffer.accessors);
+        document["bufferViews"] = json!(buffer.views);
+        document["buffers"] = json!([{ "byteLength": buffer.bin.len() }]);
+    }
+    if !materials.is_empty() {
+        document["materials"] = json!(materials);
+    }
+    (document, buffer.bin, mesh_count)
+}
+
+/// Link transforms of every robot in the requested configuration, and the joint values
+/// stored on the robot roots.
+#[cfg(feature = "robot")]
+fn robot_poses(
+    world: &mut World,
+    joints: bool,
+    overrides: &mut HashMap<Entity, Transform>,
+    extras: &mut HashMap<Entity, Value>,
+) {
+    use crate::robot::{RobotRoot, RobotState};
+
+    let mut robots = world.query_filtered::<(Entity, &RobotState), With<RobotRoot>>();
+    for (root, state) in robots.iter(world) {
+        let chain = &state.robot_chain;
+        if joints {
+            let values: serde_json::Map<String, Value> = chain
+                .iter_joints()
+                .filter_map(|joint| Some((joint.name.clone(), json!(joint.joint_position()?))))
+                .collect();
+            extras.insert(root, json!({ "joint_values": values }));
+            continue;
+        }
+        let current = chain.joint_positions();
+        chain.set_joint_positions_clamped(&vec![0.0; current.len()]);
+        chain.update_transforms();
+        for link in chain.iter() {
+            let Some(entity) = state
+                .joint_link_map
+                .get(&link.joint().name)
+                .and_then(|link_name| state.link_names_to_entity.get(link_name))
+            else {
+                continue;
+            };
+            if let Some(pose) = link.world_transform() {
+                let translation = pose.translation.vector;
+                let rotation = pose.rotation;
+                overrides.insert(
+                    *entity,
+                    Transform::from_xyz(translation.x, translation.y, translation.z).with_rotation(
+                        Quat::from_xyzw(rotation.i, rotation.j, rotation.k, rotation.w),
+                    ),
+                );
+            }
+        }
+        chain.set_joint_positions_unchecked(&current);
+        chain.update_transforms();
+    }
+}
+
+fn material_json(material: &StandardMaterial) -> Value {
+    let base = material.base_color.to_linear();
+    let emissive = material.emissive;
+    let mut value = json!({
+        "pbrMetallicRoughness": {
+            "baseColorFactor": [base.red, base.green, base.blue, base.alpha],
+            "metallicFactor": material.metallic,
+            "roughnessFactor": material.perceptual_roughness,
+        },
+        "emissiveFactor": [emissive.red, emissive.green, emissive.blue],
+        "doubleSided": material.double_sided,
+    });
+    match material.alpha_mode {
+        AlphaMode::Opaque => {}
+        AlphaMode::Mask(cutoff) => {
+            value["alphaMode"] = json!("MASK");
+            value["alphaCutoff"] = json!(cutoff);
+        }
+        _ => value["alphaMode"] = json!("BLEND"),
+    }
+    value
+}
+
+/// A primitive with positions, normals, texture coordinates and indices, if the mesh
+/// has float positions.
+fn primitive_json(
+    mesh: &Mesh,
+    material: Option<usize>,
+    buffer: &mut BufferBuilder,
+) -> Option<Value> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+    let mode = match mesh.primitive_topology() {
+        PrimitiveTopology::PointList => 0,
+        PrimitiveTopology::LineList => 1,
+        PrimitiveTopology::LineStrip => 3,
+        PrimitiveTopology::TriangleList => 4,
+        PrimitiveTopology::TriangleStrip => 5,
+    };
+    let (min, max) =
+        positions
+            .iter()
+            .fold(([f32::MAX; 3], [f32::MIN; 3]), |(min, max), position| {
+                (
+                    std::array::from_fn(|i| min[i].min(position[i])),
+                    std::array::from_fn(|i| max[i].max(position[i])),
+                )
+            });
+    let mut attributes = serde_json::Map::new();
+    attributes.insert(
+        "POSITION".to_string(),
+        json!(buffer.accessor(
+            positions.as_flattened(),
+            ARRAY_BUFFER,
+            FLOAT,
+            positions.len(),
+            "VEC3",
+            Some((min.to_vec(), max.to_vec())),
+        )),
+    );
+    if let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+    {
+        attributes.insert(
+            "NORMAL".to_string(),
+            json!(buffer.accessor(
+                normals.as_flattened(),
+                ARRAY_BUFFER,
+                FLOAT,
+                normals.len(),
+                "VEC3",
+                None
+            )),
+        );
+    }
+    if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        attributes.insert(
+            "TEXCOORD_0".to_string(),
+            json!(buffer.accessor(
+                uvs.as_flattened(),
+                ARRAY_BUFFER,
+                FLOAT,
+                uvs.len(),
+                "VEC2",
+                None
+            )),
+        );
+    }
+
+    let mut primitive = json!({ "attributes": attributes, "mode": mode });
+    let indices = match mesh.indices() {
+        Some(Indices::U16(indices)) => Some(buffer.accessor(
+            indices,
+            ELEMENT_ARRAY_BUFFER,
+            UNSIGNED_SHORT,
+            indices.len(),
+            "SCALAR",
+            None,
+        )),
+        Some(Indices::U32(indices)) => Some(buffer.accessor(
+            indices,
+            ELEMENT_ARRAY_BUFFER,
+            UNSIGNED_INT,
+            indices.len(),
+            "SCALAR",
+            None,
+        )),
+        None => None,
+    };
+    if let Some(indices) = indices {
+        primitive["indices"] = json!(indices);
+    }
+    if let Some(material) = material {
+        primitive["material"] = json!(material);
+    }
+    Some(primitive)
+}
+
+/// The single binary buffer of the document with its views and accessors.
+#[derive(Default)]
+struct BufferBuilder {
+    bin: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl BufferBuilder {
+    /// Append `data` as a new view and return the index of its accessor.
+    fn accessor<T: Copy + ToLeBytes>(
+        &mut self,
+        data: &[T],
+        target: u32,
+        component_type: u32,
+        count: usize,
+        kind: &str,
+        bounds: Option<(Vec<f32>, Vec<f32>)>,
+    ) -> usize {
+        let offset = self.bin.len();
+        for &value in data {
+            value.extend_le(&mut self.bin);
+        }
+        let length = self.bin.len() - offset;
+        // views start on 4-byte boundaries
+        self.bin.resize(self.bin.len().next_multiple_of(4), 0);
+        self.views.push(json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": length,
+            "target": target,
+        }));
+        let mut accessor = json!({
+            "bufferView": self.views.len() - 1,
+            "componentType": component_type,
+            "count": count,
+            "type": kind,
+        });
+        if let Some((min, max)) = bounds {
+            accessor["min"] = json!(min);
+            accessor["max"] = json!(max);
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+}
+
+trait ToLeBytes {
+    fn extend_le(self, bytes: &mut Vec<u8>);
+}
+
+impl ToLeBytes for f32 {
+    fn extend_le(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl ToLeBytes for u16 {
+    fn extend_le(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl ToLeBytes for u32 {
+    fn extend_le(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+/// A GLB container: header, JSON chunk padded with spaces, binary chunk padded with zeros.
+fn glb_bytes(document: &Value, bin: &[u8]) -> serde_json::Result<Vec<u8>> {
+    let mut json = serde_json::to_vec(document)?;
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let bin_length = bin.len().next_multiple_of(4);
+    let mut total = 12 + 8 + json.len();
+    if !bin.is_empty() {
+        total += 8 + bin_length;
+    }
+
+    let mut bytes = Vec::with_capacity(total);
+    bytes.extend_from_slice(b"glTF");
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    bytes.extend_from_slice(&(total as u32).to_le_bytes());
+    bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(b"JSON");
+    bytes.extend_from_slice(&json);
+    if !bin.is_empty() {
+        bytes.extend_from_slice(&(bin_length as u32).to_le_bytes());
+        bytes.extend_from_slice(b"BIN\0");
+        bytes.extend_from_slice(bin);
+        bytes.resize(total, 0);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{glb_bytes, gltf_document};
+
+    #[test]
+    fn hierarchy_and_meshes_are_exported() {
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        let cube = world.resource_mut::<Assets<Mesh>>().add(Cuboid::default());
+        let parent = world
+            .spawn((Name::new("base"), Transform::from_xyz(1.0, 0.0, 0.0)))
+            .id();
+        world.spawn((Mesh3d(cube.clone()), Transform::default(), ChildOf(parent)));
+        world.spawn((Mesh3d(cube), Transform::default(), ChildOf(parent)));
+        world.spawn(Transform::default());
+
+        let (document, bin, meshes) = gltf_document(&mut world, true);
+        assert_eq!(meshes, 1);
+        assert_eq!(document["nodes"].as_array().unwrap().len(), 3);
+        assert_eq!(document["scenes"][0]["nodes"].as_array().unwrap().len(), 1);
+        assert_eq!(document["buffers"][0]["byteLength"], bin.len());
+
+        let glb = glb_bytes(&document, &bin).unwrap();
+        assert_eq!(&glb[..4], b"glTF");
+        assert_eq!(glb.len() % 4, 0);
+        assert_eq!(
+            u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize,
+            glb.len()
+        );
+    }
+}
@@ -3,9 +3,15 @@ use bevy::prelude::*;
 #[cfg(feature = "gsplat")]
 pub mod gaussian_splatting;
 
+pub mod gltf_export;
 pub mod preset;
 pub(crate) mod showcase_window;
 
+/// Marks an entity whose `Transform` the viewer sets itself, from telemetry or physics,
+/// so its changes are not taken for edits made by the user.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct DrivenTransform;
+
 #[allow(unused_variables)]
 pub fn plugin(app: &mut App) {
     #[cfg(feature = "gsplat")]
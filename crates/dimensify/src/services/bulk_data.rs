@@ -0,0 +1,199 @@
+//! Applies bulk transfers (see `dimensify_transport::bulk_chunks`) to the entity
+//! they target: meshes and point clouds become its `Mesh3d`, images the base
+//! color texture of its material.
+//!
+//! Bulk data is not part of the scene snapshot sent to mirroring viewers or replayed
+//! to viewers joining a hub late. Transfers of more than one chunk show their progress
+//! in the tasks tab.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    mesh::{Indices, PrimitiveTopology},
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use dimensify_protocol::{AccessRole, BulkData, BulkHeader, MessageChannel};
+use dimensify_transport::{
+    BulkAssembler, LinkStats, StreamBytes, TransportConfig, TransportPeer, authenticate_peers,
+};
+use lightyear::prelude::{LinkOf, MessageReceiver};
+
+use crate::tasks::{TaskId, Tasks};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Update, receive_bulk_data.after(authenticate_peers));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn receive_bulk_data(
+    mut commands: Commands,
+    config: Res<TransportConfig>,
+    mut tasks: ResMut<Tasks>,
+    mut transfer_tasks: Local<HashMap<(Entity, u64), TaskId>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    existing_materials: Query<&MeshMaterial3d<StandardMaterial>>,
+    mut links: Query<(
+        Entity,
+        &mut MessageReceiver<StreamBytes>,
+        &mut BulkAssembler,
+        &mut LinkStats,
+        Has<LinkOf>,
+        Option<&TransportPeer>,
+    )>,
+) {
+    transfer_tasks.retain(|(link, _), task| {
+        links.contains(*link) || {
+            tasks.fail(*task, "connection closed");
+            false
+        }
+    });
+    for (link, mut receiver, mut assembler, mut stats, is_link, peer) in &mut links {
+        let allowed = !is_link
+            || TransportPeer::role_of(&config, peer)
+                .is_some_and(|role| role >= AccessRole::Controller);
+        for chunk in receiver.receive() {
+            stats.received(MessageChannel::Bulk);
+            if !allowed {
+                stats.error();
+                warn!(
+                    "Rejected bulk data from {:?}: requires the Controller role",
+                    link
+                );
+                continue;
+            }
+            let key = (link, chunk.transfer);
+            if let Some(header) = &chunk.header
+                && chunk.count > 1
+            {
+                let task = tasks.start(
+                    "Bulk data",
+                    format!("{:?} for {:?}", header.kind, header.entity),
+                );
+                transfer_tasks.insert(key, task);
+            }
+            let progress = (chunk.index + 1) as f32 / chunk.count.max(1) as f32;
+            let (header, data) = match assembler.push(chunk) {
+                None => {
+                    if let Some(task) = transfer_tasks.get(&key) {
+                        tasks.set_progress(*task, progress);
+                    }
+                    continue;
+                }
+                Some(Ok(transfer)) => {
+                    if let Some(task) = transfer_tasks.remove(&key) {
+                        tasks.finish(task);
+                    }
+                    transfer
+                }
+                Some(Err(err)) => {
+                    warn!("Dropping bulk data from {:?}: {}", link, err);
+                    stats.error();
+                    if let Some(task) = transfer_tasks.remove(&key) {
+                        tasks.fail(task, err.to_string());
+                    }
+                    continue;
+                }
+            };
+            let Ok(mut entity) = commands.get_entity(header.entity) else {
+                warn!("Bulk data targets unknown entity {:?}", header.entity);
+                continue;
+            };
+            debug!(
+                "Applying {:?} bulk data to {:?}",
+                header.kind, header.entity
+            );
+            apply_bulk_data(
+                &mut entity,
+                header,
+                data,
+                &mut meshes,
+                &mut images,
+                &mut materials,
+                &existing_materials,
+            );
+        }
+    }
+}
+
+fn apply_bulk_data(
+    entity: &mut EntityCommands,
+    header: BulkHeader,
+    data: BulkData,
+    meshes: &mut Assets<Mesh>,
+    images: &mut Assets<Image>,
+    materials: &mut Assets<StandardMaterial>,
+    existing_materials: &Query<&MeshMaterial3d<StandardMaterial>>,
+) {
+    match data {
+        BulkData::Mesh {
+            positions,
+            normals,
+            indices,
+        } => {
+            let mut mesh = Mesh::new(
+                PrimitiveTopology::TriangleList,
+                RenderAssetUsages::default(),
+            )
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, to_arrays(positions));
+            if !indices.is_empty() {
+                mesh.insert_indices(Indices::U32(indices));
+            }
+            if normals.is_empty() {
+                mesh.compute_normals();
+            } else {
+                mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, to_arrays(normals));
+            }
+            entity.insert(Mesh3d(meshes.add(mesh)));
+            entity.insert_if_new(MeshMaterial3d(materials.add(StandardMaterial::default())));
+        }
+        BulkData::PointCloud { positions, colors } => {
+            let mut mesh = Mesh::new(PrimitiveTopology::PointList, RenderAssetUsages::default())
+                .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, to_arrays(positions));
+            if !colors.is_empty() {
+                mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, to_arrays(colors));
+            }
+            entity.insert(Mesh3d(meshes.add(mesh)));
+            entity.insert_if_new(MeshMaterial3d(materials.add(StandardMaterial {
+                unlit: true,
+                ..default()
+            })));
+        }
+        BulkData::Image {
+            width,
+            height,
+            rgba8,
+        } => {
+            let image = images.add(Image::new(
+                Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                rgba8,
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::default(),
+            ));
+            let existing = existing_materials
+                .get(header.entity)
+                .ok()
+                .and_then(|material| materials.get_mut(&material.0));
+            match existing {
+                Some(material) => material.base_color_texture = Some(image),
+                None => {
+                    entity.insert(MeshMaterial3d(materials.add(StandardMaterial {
+                        base_color_texture: Some(image),
+                        ..default()
+                    })));
+                }
+            }
+        }
+    }
+}
+
+fn to_arrays<T: Into<[f32; N]>, const N: usize>(values: Vec<T>) -> Vec<[f32; N]> {
+    values.into_iter().map(Into::into).collect()
+}
@@ -0,0 +1,128 @@
+//! Scene commands for the console tab: `list`, and JSON lines holding a `WorldCommand`
+//! or a widget command, applied like the requests of a controller.
+
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use dimensify_protocol::WorldCommand;
+use dimensify_ui::console::ConsoleCommands;
+
+use crate::{
+    services::scene_sync::ProtocolComponents,
+    stream::CommandLog,
+    ui::widgets::{self, WidgetCommandQueue},
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<ConsoleCommands>();
+    let mut commands = app.world_mut().resource_mut::<ConsoleCommands>();
+    commands.register("list", Arc::new(list_entities));
+    commands.register(
+        "<json WorldCommand or widget command>",
+        Arc::new(apply_json_command),
+    );
+}
+
+/// Named and protocol-spawned entities, with the ids commands refer to them by.
+fn list_entities(input: &str, world: &mut World) -> Option<Result<String, String>> {
+    if input != "list" {
+        return None;
+    }
+    let mut query = world.query_filtered::<
+        (Entity, Option<&Name>, Has<ProtocolComponents>),
+        Or<(With<Name>, With<ProtocolComponents>)>,
+    >();
+    let lines: Vec<String> = query
+        .iter(world)
+        .map(|(entity, name, spawned)| {
+            format!(
+                "{} {}{}",
+                entity.to_bits(),
+                name.map_or("-", Name::as_str),
+                if spawned { " (protocol)" } else { "" }
+            )
+        })
+        .collect();
+    Some(Ok(match lines.is_empty() {
+        true => "no entities".to_string(),
+        false => lines.join("\n"),
+    }))
+}
+
+/// Queue a JSON `WorldCommand` on the [`CommandLog`], as file replay does, or a widget
+/// command, told apart by the `type` field of widget commands. `Update` and `Clear` are
+/// refused: the viewer cannot apply them yet.
+fn apply_json_command(input: &str, world: &mut World) -> Option<Result<String, String>> {
+    if !input.starts_with('{') && !input.starts_with('"') {
+        return None;
+    }
+    let value: serde_json::Value = match serde_json::from_str(input) {
+        Ok(value) => value,
+        Err(err) => return Some(Err(format!("invalid JSON: {}", err))),
+    };
+    if value.get("type").is_none() {
+        let command = match serde_json::from_value::<WorldCommand>(value) {
+            Ok(command) => command,
+            Err(err) => return Some(Err(format!("invalid WorldCommand: {}", err))),
+        };
+        if let Err(err) = console_applicable(&command) {
+            return Some(Err(err));
+        }
+        let reply = format!("queued {:?}", command);
+        world
+            .resource_mut::<CommandLog>()
+            .commands
+            .push((Entity::PLACEHOLDER, command));
+        return Some(Ok(reply));
+    }
+    let command = match widgets::widget_from_json(value) {
+        Ok(command) => command,
+        Err(err) => return Some(Err(format!("invalid widget command: {}", err))),
+    };
+    let Some(mut queue) = world.get_resource_mut::<WidgetCommandQueue>() else {
+        return Some(Err("the widget panel is not enabled".to_string()));
+    };
+    let reply = format!("queued widget '{}'", command.id());
+    queue.push(command);
+    Some(Ok(reply))
+}
+
+/// Commands the viewer can apply; the others would stop it.
+fn console_applicable(command: &WorldCommand) -> Result<(), String> {
+    match command {
+        WorldCommand::Update { .. } => Err("Update is not supported yet; use Insert".to_string()),
+        WorldCommand::Clear => Err("Clear is not supported yet; use Despawn".to_string()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use dimensify_protocol::{ProtoComponent, WorldCommand};
+
+    use super::apply_json_command;
+    use crate::stream::CommandLog;
+
+    #[test]
+    fn unsupported_commands_are_refused() {
+        let mut world = World::new();
+        world.init_resource::<CommandLog>();
+        let update = WorldCommand::Update {
+            entity: Entity::from_raw_u32(1).unwrap(),
+            component: ProtoComponent::Name("x".to_string()),
+        };
+        for command in [update, WorldCommand::Clear] {
+            let input = serde_json::to_string(&command).unwrap();
+            assert!(matches!(
+                apply_json_command(&input, &mut world),
+                Some(Err(_))
+            ));
+        }
+        assert!(world.resource::<CommandLog>().commands.is_empty());
+
+        let spawn = r#"{"Spawn": {"components": [{"Name": "probe"}]}}"#;
+        assert!(matches!(apply_json_command(spawn, &mut world), Some(Ok(_))));
+        assert_eq!(world.resource::<CommandLog>().commands.len(), 1);
+    }
+}
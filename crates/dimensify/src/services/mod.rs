@@ -1,9 +1,25 @@
 use bevy::prelude::*;
 
+#[cfg(feature = "transport")]
+pub mod bulk_data;
+#[cfg(feature = "transport")]
+pub mod console_commands;
 #[cfg(feature = "transport")]
 pub mod protocol_response;
 #[cfg(feature = "transport")]
+pub mod remote_widgets;
+#[cfg(feature = "transport")]
+pub mod scene_file;
+#[cfg(feature = "transport")]
+pub mod scene_sync;
+#[cfg(feature = "transport")]
+pub mod stream_diagnostics;
+#[cfg(feature = "transport")]
 pub mod transport_bridge;
+#[cfg(feature = "transport")]
+pub mod transport_diagnostics;
+#[cfg(feature = "transport")]
+pub mod viewer_events;
 
 #[allow(unused)]
 pub fn plugin(app: &mut App) {
@@ -11,6 +27,20 @@ pub fn plugin(app: &mut App) {
     {
         app.add_plugins(transport_bridge::plugin);
         app.add_plugins(protocol_response::plugin);
-        app.add_plugins(dimensify_transport::TransportRuntimePlugin::default());
+        app.add_plugins(scene_sync::plugin);
+        app.add_plugins(viewer_events::plugin);
+        app.add_plugins(remote_widgets::plugin);
+        app.add_plugins(bulk_data::plugin);
+        app.add_plugins(transport_diagnostics::plugin);
+        app.add_plugins(stream_diagnostics::plugin);
+        app.add_plugins(console_commands::plugin);
+        app.add_plugins(scene_file::plugin);
+        // embedders may configure the transport by inserting a config first
+        let config = app
+            .world()
+            .get_resource::<dimensify_transport::TransportConfig>()
+            .cloned()
+            .unwrap_or_else(dimensify_transport::TransportConfig::from_env);
+        app.add_plugins(dimensify_transport::TransportRuntimePlugin { config });
     }
 }
@@ -1,9 +1,16 @@
-use super::{draw::DrawCommand, pending_response::PendingApplyCommand};
-use crate::stream::CommandLog;
+use super::{
+    draw::DrawCommand,
+    pending_response::{Awaiting, PendingResponses},
+};
+use crate::{
+    services::scene_sync::{AppliedCommands, ProtocolComponents},
+    stream::CommandLog,
+};
 use anyhow::Context;
 use dimensify_transport::ProtoResponse;
 
 use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
     pbr::{MeshMaterial3d, StandardMaterial},
     prelude::*,
 };
@@ -12,45 +19,22 @@ use dimensify_protocol::prelude::{
     WorldCommand,
 };
 
-use lightyear::prelude::MessageSender;
+/// Commands applied by [`apply_new_commands`] per second.
+pub const COMMANDS_APPLIED: DiagnosticPath =
+    DiagnosticPath::const_new("dimensify/commands_applied");
 
 pub fn plugin(app: &mut App) {
-    app.init_resource::<PendingMeshInsertion>()
+    app.register_diagnostic(Diagnostic::new(COMMANDS_APPLIED).with_suffix(" /s"))
+        .init_resource::<PendingMeshInsertion>()
         .init_resource::<PendingMaterialInsertion>()
         .add_systems(Update, apply_new_commands)
         // always insert meshes and materials after new commands are applied (and hence they would create new pending insertion)
         .add_systems(
             Update,
             (insert_meshes, insert_materials).after(apply_new_commands),
-        )
-        .add_systems(
-            Update,
-            handle_apply_command_response.after(apply_new_commands),
         );
 }
 
-/// outgoing responses to the client
-///
-/// This is a system that is responsible for sending responses to the client,
-/// after the command is applied.
-fn handle_apply_command_response(
-    mut commands: Commands,
-    mut receivers: Populated<
-        (Entity, &mut MessageSender<ProtoResponse>, &ProtoResponse),
-        With<PendingApplyCommand>,
-    >,
-) {
-    for (sender_entity, mut sender, response) in &mut receivers {
-        info!("Sending response to client: {:?}", sender_entity);
-        let _ = sender.send::<dimensify_transport::StreamReliable>(response.clone());
-        // clean up the components
-        commands
-            .entity(sender_entity)
-            .remove::<PendingApplyCommand>()
-            .remove::<ProtoResponse>();
-    }
-}
-
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ViewerMode {
     TwoD,
@@ -130,14 +114,24 @@ fn insert_materials(
 pub fn apply_new_commands(
     command_log: Res<CommandLog>,
     mut cursor: ResMut<CommandCursor>,
+    mut applied_commands: ResMut<AppliedCommands>,
     // mut meshes: ResMut<Assets<Mesh>>,
     // mut materials: ResMut<Assets<StandardMaterial>>,
     mut pending_mesh_insertion: ResMut<PendingMeshInsertion>,
     mut pending_material_insertion: ResMut<PendingMaterialInsertion>,
     mut commands: Commands,
+    mut senders: Query<&mut PendingResponses>,
     draw_commands: Query<Entity, With<DrawCommand>>,
+    time: Res<Time>,
+    mut diagnostics: Diagnostics,
 ) {
     let total = command_log.commands.len();
+    let delta = time.delta_secs_f64();
+    if delta > 0.0 {
+        diagnostics.add_measurement(&COMMANDS_APPLIED, || {
+            total.saturating_sub(cursor.index) as f64 / delta
+        });
+    }
     if cursor.index >= total {
         return;
     }
@@ -150,7 +144,7 @@ pub fn apply_new_commands(
 
         let command_result = match command {
             WorldCommand::Spawn { components } => {
-                let mut entity = commands.spawn_empty();
+                let mut entity = commands.spawn(ProtocolComponents::new(components));
                 apply_components(
                     &mut entity,
                     components,
@@ -168,6 +162,7 @@ pub fn apply_new_commands(
                         &mut pending_mesh_insertion,
                         &mut pending_material_insertion,
                     );
+                    track_components(&mut target, components);
                     Ok(target.id())
                 })
                 .context(format!("Insert refers to unknown entity '{}'", entity)),
@@ -218,25 +213,26 @@ pub fn apply_new_commands(
                 // }
             }
         };
-        match command_result {
+        let response = match command_result {
             Ok(entity) => {
-                commands
-                    .entity(*sender_entity)
-                    .insert(ProtoResponse::CommandResponseEntity(entity));
+                applied_commands.items.push((command.clone(), entity));
+                ProtoResponse::CommandResponseEntity(entity)
             }
             Err(e) => {
                 bevy::log::warn!("Failed to apply command: {:?}", e);
-                commands
-                    .entity(*sender_entity)
-                    .insert(ProtoResponse::Error {
-                        message: e.to_string(),
-                    });
+                ProtoResponse::Error {
+                    message: e.to_string(),
+                }
             }
+        };
+        // replayed commands have no sender to answer
+        if let Ok(mut pending) = senders.get_mut(*sender_entity) {
+            pending.fill_first(Awaiting::ApplyCommand, response);
         }
     }
 }
 
-fn apply_components(
+pub(crate) fn apply_components(
     entity_cmd: &mut EntityCommands<'_>,
     components: &[ProtoComponent],
     pending_mesh_insertion: &mut PendingMeshInsertion,
@@ -253,17 +249,47 @@ fn apply_components(
             InsertionResult::RequireResMaterial(material) => pending_material_insertion
                 .items
                 .push((entity_cmd.id(), material)),
+            InsertionResult::SpawnChildren(children) => {
+                let parent = entity_cmd.id();
+                let mut commands = entity_cmd.commands();
+                for components in children {
+                    let mut child =
+                        commands.spawn((ProtocolComponents::new(&components), ChildOf(parent)));
+                    apply_components(
+                        &mut child,
+                        &components,
+                        pending_mesh_insertion,
+                        pending_material_insertion,
+                    );
+                }
+            }
         }
     }
 }
 
-/// Remove a component from an entity.
+/// Record protocol components on an entity, so it can be part of a scene snapshot.
+pub(crate) fn track_components(entity_cmd: &mut EntityCommands<'_>, components: &[ProtoComponent]) {
+    let merged = components.to_vec();
+    let inserted = ProtocolComponents::new(components);
+    entity_cmd
+        .entry::<ProtocolComponents>()
+        .and_modify(move |mut tracked| tracked.merge(&merged))
+        .or_insert(inserted);
+}
+
+/// Remove a component from an entity, and from its tracked protocol components.
 ///
 /// It will panic if the component id is not found.
-fn remove_component(
+pub(crate) fn remove_component(
     entity_cmd: &mut EntityCommands<'_>,
     component: dimensify_protocol::ComponentId,
 ) {
     let id = bevy::ecs::component::ComponentId::from(component);
-    entity_cmd.remove_by_id(id);
+    entity_cmd.queue(move |mut entity: EntityWorldMut| {
+        if let Some(mut tracked) = entity.take::<ProtocolComponents>() {
+            tracked.remove_by_id(id, entity.world().components());
+            entity.insert(tracked);
+        }
+        entity.remove_by_id(id);
+    });
 }
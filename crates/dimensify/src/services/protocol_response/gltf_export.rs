@@ -0,0 +1,109 @@
+use std::path::{Component, Path, PathBuf};
+
+use bevy::prelude::*;
+use dimensify_transport::ProtoResponse;
+
+use super::pending_response::{Awaiting, PendingResponses};
+use crate::scene::gltf_export::run_gltf_export;
+
+/// `ExportGltf` requests by link, answered once the export has run.
+#[derive(Resource)]
+pub(crate) struct PendingGltfExports {
+    pub(crate) items: Vec<(Entity, String, bool)>,
+    /// Directory remote exports are written to (`DIMENSIFY_EXPORT_DIR`).
+    pub(crate) dir: PathBuf,
+}
+
+impl Default for PendingGltfExports {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            dir: std::env::var("DIMENSIFY_EXPORT_DIR")
+                .unwrap_or_else(|_| "exports".to_string())
+                .into(),
+        }
+    }
+}
+
+/// Run queued exports with the whole world and answer the links that asked for them.
+pub(crate) fn handle_pending_gltf_exports(world: &mut World) {
+    let (items, dir) = {
+        let mut exports = world.resource_mut::<PendingGltfExports>();
+        (std::mem::take(&mut exports.items), exports.dir.clone())
+    };
+    for (link, path, joints) in items {
+        let response =
+            match export_path(&dir, &path).and_then(|path| run_gltf_export(world, &path, joints)) {
+                Ok(_) => ProtoResponse::Ack,
+                Err(message) => ProtoResponse::Error { message },
+            };
+        if let Some(mut pending) = world.get_mut::<PendingResponses>(link) {
+            pending.fill_first(Awaiting::ExportGltf, response);
+        }
+    }
+}
+
+/// Resolve a path sent by a peer inside the export directory, creating the directories
+/// it names. Only relative `.glb`/`.gltf` paths without `..` are accepted, so an export
+/// (and the `.bin` next to a `.gltf`) cannot land outside the directory.
+fn export_path(dir: &Path, requested: &str) -> Result<PathBuf, String> {
+    let requested = Path::new(requested);
+    if !requested
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "export path '{}' must be relative to the export directory, without '..'",
+            requested.display()
+        ));
+    }
+    if !matches!(
+        requested.extension().and_then(|ext| ext.to_str()),
+        Some("glb" | "gltf")
+    ) {
+        return Err(format!(
+            "export path '{}' must end in .glb or .gltf",
+            requested.display()
+        ));
+    }
+    let path = dir.join(requested);
+    let parent = path.parent().unwrap_or(dir);
+    std::fs::create_dir_all(parent)
+        .map_err(|err| format!("cannot create {}: {}", parent.display(), err))?;
+    // symlinks inside the export directory must not lead out of it
+    let canonical_dir = dir.canonicalize().map_err(|err| err.to_string())?;
+    let canonical_parent = parent.canonicalize().map_err(|err| err.to_string())?;
+    if !canonical_parent.starts_with(&canonical_dir) {
+        return Err(format!(
+            "export path '{}' leaves the export directory",
+            requested.display()
+        ));
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::export_path;
+
+    #[test]
+    fn remote_exports_stay_in_the_export_directory() {
+        let dir = std::env::temp_dir().join(format!("dimensify_exports_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            export_path(&dir, "robots/pose.glb").unwrap(),
+            dir.join("robots/pose.glb")
+        );
+        assert!(dir.join("robots").is_dir());
+        for rejected in [
+            "/etc/passwd.glb",
+            "../outside.glb",
+            "a/../../b.gltf",
+            "notes.txt",
+        ] {
+            assert!(export_path(&dir, rejected).is_err(), "{}", rejected);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,7 +3,7 @@ use dimensify_protocol::ComponentInfo;
 use dimensify_transport::{EntityInfo, ProtoResponse};
 use lightyear::prelude::MessageSender;
 
-use super::pending_response::{PendingRequestList, WithPendingResponse};
+use super::pending_response::{Awaiting, PendingResponses};
 use bevy::{
     camera::Camera,
     window::{Monitor, Window},
@@ -26,39 +26,42 @@ type DefaultEntityFilter = (
     Without<Camera>,
 );
 
-/// These are all queued list requests that need to be processed.
+/// Answer all queued list requests.
 pub(crate) fn handle_pending_request_list(
-    mut commands: Commands,
     q_entities: Query<(Entity, EntityRef, Option<&Name>), DefaultEntityFilter>,
-    mut senders_with_pending_reqs: WithPendingResponse<With<PendingRequestList>>,
+    mut senders: Query<&mut PendingResponses, With<MessageSender<ProtoResponse>>>,
     components: &Components,
 ) {
-    for (entity, mut sender) in &mut senders_with_pending_reqs {
-        commands.entity(entity).remove::<PendingRequestList>();
-        // Send the list of entities to the client.
+    if !senders
+        .iter()
+        .any(|pending| pending.is_awaiting(Awaiting::List))
+    {
+        return;
+    }
 
-        let mut entities_out = Vec::new();
-        for (entity, entity_ref, name) in &q_entities {
-            let components = entity_ref
-                .archetype()
-                .components()
-                .iter()
-                .filter_map(|id| {
-                    components.get_info(*id).map(|info| ComponentInfo {
-                        id: info.id().index(),
-                        name: info.name().to_string(),
-                    })
+    let mut entities_out = Vec::new();
+    for (entity, entity_ref, name) in &q_entities {
+        let components = entity_ref
+            .archetype()
+            .components()
+            .iter()
+            .filter_map(|id| {
+                components.get_info(*id).map(|info| ComponentInfo {
+                    id: info.id().index(),
+                    name: info.name().to_string(),
                 })
-                .collect::<Vec<ComponentInfo>>();
+            })
+            .collect::<Vec<ComponentInfo>>();
 
-            entities_out.push(EntityInfo {
-                id: entity.to_bits(),
-                name: name.map(|s| s.to_string()),
-                components,
-            });
-        }
-        let _ = sender.send::<dimensify_transport::StreamReliable>(ProtoResponse::Entities {
-            entities: entities_out,
+        entities_out.push(EntityInfo {
+            id: entity.to_bits(),
+            name: name.map(|s| s.to_string()),
+            components,
+        });
+    }
+    for mut pending in &mut senders {
+        pending.fill_all(Awaiting::List, || ProtoResponse::Entities {
+            entities: entities_out.clone(),
         });
     }
 }
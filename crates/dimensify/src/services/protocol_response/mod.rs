@@ -2,15 +2,18 @@ use bevy::prelude::*;
 
 pub(super) mod controller;
 pub(super) mod draw;
+pub(super) mod gltf_export;
 pub(super) mod list;
 pub(super) mod pending_response;
+pub(super) mod stats;
 
-pub use controller::apply_new_commands;
+pub use controller::{COMMANDS_APPLIED, apply_new_commands};
 
 pub fn plugin(app: &mut App) {
     app.init_resource::<controller::ViewerSettings>()
         .init_resource::<controller::ViewerState>()
         .init_resource::<controller::CommandCursor>()
+        .init_resource::<gltf_export::PendingGltfExports>()
         .add_plugins(controller::plugin)
         .add_plugins(draw::GizmoDrawPlugin);
 }
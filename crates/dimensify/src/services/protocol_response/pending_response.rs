@@ -1,15 +1,130 @@
+use std::collections::VecDeque;
+
 use bevy::prelude::*;
-use dimensify_transport::ProtoResponse;
+use dimensify_protocol::MessageChannel;
+use dimensify_transport::{LinkStats, ProtoResponse};
 use lightyear::prelude::MessageSender;
 
-/// A populated type for entities that are waiting for a response.
-pub type WithPendingResponse<'a, 'b, 'c, F> =
-    Populated<'a, 'b, (Entity, &'c mut MessageSender<ProtoResponse>), F>;
+/// What a reserved response is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Awaiting {
+    ApplyCommand,
+    List,
+    Stats,
+    ExportGltf,
+}
+
+#[derive(Debug)]
+enum Slot {
+    Awaiting(Awaiting),
+    Ready(ProtoResponse),
+}
+
+/// Responses owed to a link, in the order its requests arrived.
+///
+/// Controllers match responses to requests by position, so a response is only
+/// sent once every earlier one is ready.
+#[derive(Component, Debug, Default)]
+pub(crate) struct PendingResponses {
+    slots: VecDeque<Slot>,
+}
+
+impl PendingResponses {
+    pub(crate) fn push_ready(&mut self, response: ProtoResponse) {
+        self.slots.push_back(Slot::Ready(response));
+    }
+
+    pub(crate) fn push_awaiting(&mut self, awaiting: Awaiting) {
+        self.slots.push_back(Slot::Awaiting(awaiting));
+    }
+
+    pub(crate) fn is_awaiting(&self, awaiting: Awaiting) -> bool {
+        self.slots
+            .iter()
+            .any(|slot| matches!(slot, Slot::Awaiting(kind) if *kind == awaiting))
+    }
+
+    /// Answer the oldest response waiting for `awaiting`.
+    pub(crate) fn fill_first(&mut self, awaiting: Awaiting, response: ProtoResponse) {
+        if let Some(slot) = self
+            .slots
+            .iter_mut()
+            .find(|slot| matches!(slot, Slot::Awaiting(kind) if *kind == awaiting))
+        {
+            *slot = Slot::Ready(response);
+        }
+    }
+
+    /// Answer every response waiting for `awaiting`.
+    pub(crate) fn fill_all(&mut self, awaiting: Awaiting, response: impl Fn() -> ProtoResponse) {
+        for slot in &mut self.slots {
+            if matches!(slot, Slot::Awaiting(kind) if *kind == awaiting) {
+                *slot = Slot::Ready(response());
+            }
+        }
+    }
+
+    fn pop_ready(&mut self) -> Option<ProtoResponse> {
+        match self.slots.front() {
+            Some(Slot::Ready(_)) => match self.slots.pop_front() {
+                Some(Slot::Ready(response)) => Some(response),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Send the responses that are ready, stopping at the first one still waiting.
+pub(crate) fn send_pending_responses(
+    mut links: Query<(
+        &mut PendingResponses,
+        &mut MessageSender<ProtoResponse>,
+        &mut LinkStats,
+    )>,
+) {
+    for (mut pending, mut sender, mut stats) in &mut links {
+        while let Some(response) = pending.pop_ready() {
+            if let ProtoResponse::Error { .. } = response {
+                stats.error();
+            }
+            stats.sent(MessageChannel::Responses);
+            let _ = sender.send::<dimensify_transport::StreamReliable>(response);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Awaiting, PendingResponses};
+    use dimensify_transport::ProtoResponse;
 
-/// A marker component for sender that are waiting for a list request.
-#[derive(Component)]
-pub(crate) struct PendingRequestList;
+    #[test]
+    fn responses_leave_in_request_order() {
+        let mut pending = PendingResponses::default();
+        pending.push_awaiting(Awaiting::ApplyCommand);
+        pending.push_ready(ProtoResponse::Ack);
+        pending.push_awaiting(Awaiting::List);
+        assert!(pending.pop_ready().is_none());
 
-/// A marker component for sender that are waiting for a response from an entity command.
-#[derive(Component)]
-pub(crate) struct PendingApplyCommand;
+        pending.fill_all(Awaiting::List, || ProtoResponse::Entities {
+            entities: Vec::new(),
+        });
+        pending.fill_first(
+            Awaiting::ApplyCommand,
+            ProtoResponse::Error {
+                message: "unknown entity".to_string(),
+            },
+        );
+        assert!(matches!(
+            pending.pop_ready(),
+            Some(ProtoResponse::Error { .. })
+        ));
+        assert!(matches!(pending.pop_ready(), Some(ProtoResponse::Ack)));
+        assert!(matches!(
+            pending.pop_ready(),
+            Some(ProtoResponse::Entities { .. })
+        ));
+        assert!(!pending.is_awaiting(Awaiting::List));
+    }
+}
@@ -0,0 +1,24 @@
+use bevy::prelude::*;
+use dimensify_protocol::PeerStats;
+use dimensify_transport::{PeerStatsData, ProtoResponse, peer_stats};
+
+use super::pending_response::{Awaiting, PendingResponses};
+
+/// Answer queued stats requests with the statistics of every link.
+pub(crate) fn handle_pending_request_stats(
+    links: Query<PeerStatsData>,
+    mut senders: Query<&mut PendingResponses>,
+) {
+    if !senders
+        .iter()
+        .any(|pending| pending.is_awaiting(Awaiting::Stats))
+    {
+        return;
+    }
+    let peers: Vec<PeerStats> = links.iter().map(peer_stats).collect();
+    for mut pending in &mut senders {
+        pending.fill_all(Awaiting::Stats, || ProtoResponse::Stats {
+            peers: peers.clone(),
+        });
+    }
+}
@@ -0,0 +1,62 @@
+//! Widgets created by controllers with `ProtoRequest::Widget`.
+//!
+//! The link that created a widget owns it: interactions with the widget are pushed
+//! to that link only, and its widgets are removed once it disconnects.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use dimensify_protocol::{ViewerEvent, WidgetCommand};
+use lightyear::prelude::MessageSender;
+
+use crate::ui::widgets::{self, WidgetCommandQueue, WidgetRegistry};
+
+pub fn plugin(app: &mut App) {
+    if !app.world().contains_resource::<WidgetRegistry>() {
+        app.add_plugins(widgets::plugin);
+    }
+    app.init_resource::<RemoteWidgets>()
+        .add_systems(Update, remove_orphaned_widgets);
+}
+
+/// Owning link of each widget created over the transport.
+#[derive(Resource, Default, Debug)]
+pub struct RemoteWidgets {
+    owners: HashMap<String, Entity>,
+}
+
+impl RemoteWidgets {
+    /// Queue `command` for the widget panel, recording `link` as the widget's owner.
+    pub fn apply(&mut self, link: Entity, command: WidgetCommand, queue: &mut WidgetCommandQueue) {
+        match &command {
+            WidgetCommand::Remove { id } => {
+                self.owners.remove(id);
+            }
+            command => {
+                self.owners.insert(command.id().to_string(), link);
+            }
+        }
+        queue.push(command);
+    }
+
+    pub fn owner(&self, id: &str) -> Option<Entity> {
+        self.owners.get(id).copied()
+    }
+}
+
+fn remove_orphaned_widgets(
+    mut widgets: ResMut<RemoteWidgets>,
+    mut queue: ResMut<WidgetCommandQueue>,
+    links: Query<(), With<MessageSender<ViewerEvent>>>,
+) {
+    if widgets.owners.values().all(|owner| links.contains(*owner)) {
+        return;
+    }
+    widgets.owners.retain(|id, owner| {
+        let connected = links.contains(*owner);
+        if !connected {
+            queue.push(WidgetCommand::Remove { id: id.clone() });
+        }
+        connected
+    });
+}
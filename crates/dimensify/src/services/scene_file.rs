@@ -0,0 +1,229 @@
+//! Scene files: the protocol-spawned entities as JSONL `WorldCommand`s.
+//!
+//! [`export_scene`] writes one `Spawn` per top-level entity, with its descendants nested
+//! as `ProtoComponent::Children`, which file replay (`DIMENSIFY_DATA_SOURCE=file`) loads
+//! like any recorded stream. [`import_scene`] queues such a file on the [`CommandLog`]
+//! under a new root entity, so it is applied and mirrored like commands from a
+//! controller. Top-level entities are written with their world transform, children with
+//! the transform relative to their parent.
+
+use std::{path::Path, sync::Arc};
+
+use bevy::prelude::*;
+use dimensify_protocol::{ProtoComponent, WorldCommand};
+use dimensify_ui::console::ConsoleCommands;
+
+use super::scene_sync::{ProtocolComponents, SceneNode, nested_scene};
+use crate::stream::CommandLog;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<ConsoleCommands>();
+    let mut commands = app.world_mut().resource_mut::<ConsoleCommands>();
+    commands.register("scene export <path>", Arc::new(export_command));
+    commands.register("scene import <path>", Arc::new(import_command));
+}
+
+/// A `Spawn` for every top-level protocol entity, with its current transform.
+pub fn scene_commands(world: &mut World) -> Vec<WorldCommand> {
+    scene_entities(world)
+        .into_iter()
+        .map(|components| WorldCommand::Spawn { components })
+        .collect()
+}
+
+/// The components of every top-level protocol entity, with its current transform and
+/// its descendants nested as `ProtoComponent::Children`.
+pub fn scene_entities(world: &mut World) -> Vec<Vec<ProtoComponent>> {
+    let mut query = world.query::<(
+        Entity,
+        &ProtocolComponents,
+        Option<&Transform>,
+        Option<&GlobalTransform>,
+        Option<&ChildOf>,
+        Option<&Children>,
+    )>();
+    let scene = query.query(world);
+    let node = |entity| {
+        let (_, components, transform, global, parent, children) = scene.get(entity).ok()?;
+        let parent = parent.map(ChildOf::parent);
+        let top_level = parent.is_none_or(|parent| !scene.contains(parent));
+        let transform = match top_level {
+            true => global.map(GlobalTransform::compute_transform),
+            false => transform.copied(),
+        };
+        let mut components = components.0.clone();
+        if let Some(transform) = transform {
+            components.retain(|c| !matches!(c, ProtoComponent::Transform { .. }));
+            components.push(ProtoComponent::Transform {
+                translation: transform.translation,
+                rotation: transform.rotation,
+                scale: transform.scale,
+            });
+        }
+        Some(SceneNode {
+            components,
+            parent,
+            children: children
+                .map(|children| children.to_vec())
+                .unwrap_or_default(),
+        })
+    };
+    nested_scene(scene.iter().map(|(entity, ..)| entity), &node)
+        .into_iter()
+        .map(|(_, components)| components)
+        .collect()
+}
+
+/// Write the scene as JSONL and return the number of top-level entities written.
+pub fn export_scene(world: &mut World, path: &Path) -> anyhow::Result<usize> {
+    let commands = scene_commands(world);
+    let mut content = String::new();
+    for command in &commands {
+        content.push_str(&serde_json::to_string(command)?);
+        content.push('\n');
+    }
+    std::fs::write(path, content)?;
+    Ok(commands.len())
+}
+
+/// Queue a `Spawn` of a root entity named after the file, with the entities of the file
+/// as its children, and return their number. Commands other than `Spawn` refer to
+/// entities of another session and are skipped.
+pub fn import_scene(world: &mut World, path: &Path) -> anyhow::Result<usize> {
+    let content = std::fs::read_to_string(path)?;
+    let mut spawns = Vec::new();
+    for (line_no, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<WorldCommand>(line) {
+            Ok(WorldCommand::Spawn { components }) => spawns.push(components),
+            Ok(command) => warn!(
+                "Skipping line {} of {}: only Spawn can be imported, got {:?}",
+                line_no + 1,
+                path.display(),
+                command
+            ),
+            Err(err) => warn!("Failed to parse command at line {}: {}", line_no + 1, err),
+        }
+    }
+
+    let name = path
+        .file_stem()
+        .map_or("scene".into(), |stem| stem.to_string_lossy());
+    let count = spawns.len();
+    let root = WorldCommand::Spawn {
+        components: vec![
+            ProtoComponent::Name(name.into_owned()),
+            ProtoComponent::Transform {
+                translation: Vec3::ZERO,
+                rotation: Quat::IDENTITY,
+                scale: Vec3::ONE,
+            },
+            ProtoComponent::Children(spawns),
+        ],
+    };
+    world
+        .resource_mut::<CommandLog>()
+        .commands
+        .push((Entity::PLACEHOLDER, root));
+    Ok(count)
+}
+
+fn export_command(input: &str, world: &mut World) -> Option<Result<String, String>> {
+    let path = input.strip_prefix("scene export ")?.trim();
+    Some(
+        export_scene(world, Path::new(path))
+            .map(|count| format!("exported {} entities to {}", count, path))
+            .map_err(|err| format!("export failed: {:#}", err)),
+    )
+}
+
+fn import_command(input: &str, world: &mut World) -> Option<Result<String, String>> {
+    let path = input.strip_prefix("scene import ")?.trim();
+    Some(
+        import_scene(world, Path::new(path))
+            .map(|count| format!("queued {} entities from {}", count, path))
+            .map_err(|err| format!("import failed: {:#}", err)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use dimensify_protocol::{ProtoComponent, WorldCommand};
+
+    use super::{ProtocolComponents, import_scene, scene_commands};
+    use crate::stream::CommandLog;
+
+    #[test]
+    fn exported_spawns_carry_world_transforms() {
+        let mut world = World::new();
+        world.spawn((
+            ProtocolComponents(vec![
+                ProtoComponent::Name("probe".to_string()),
+                ProtoComponent::Transform {
+                    translation: Vec3::X,
+                    rotation: Quat::IDENTITY,
+                    scale: Vec3::ONE,
+                },
+            ]),
+            GlobalTransform::from_translation(Vec3::new(1.0, 2.0, 0.0)),
+        ));
+
+        let commands = scene_commands(&mut world);
+        let [WorldCommand::Spawn { components }] = commands.as_slice() else {
+            panic!("expected one spawn, got {:?}", commands);
+        };
+        assert_eq!(components.len(), 2);
+        assert!(matches!(
+            &components[1],
+            ProtoComponent::Transform { translation, .. } if *translation == Vec3::new(1.0, 2.0, 0.0)
+        ));
+    }
+
+    #[test]
+    fn children_are_nested_and_imported_through_the_command_log() {
+        let mut world = World::new();
+        let parent = world
+            .spawn((
+                ProtocolComponents(vec![ProtoComponent::Name("arm".to_string())]),
+                Transform::from_xyz(1.0, 0.0, 0.0),
+                GlobalTransform::from_translation(Vec3::X),
+            ))
+            .id();
+        world.spawn((
+            ProtocolComponents(vec![ProtoComponent::Name("hand".to_string())]),
+            Transform::from_xyz(0.0, 2.0, 0.0),
+            GlobalTransform::from_translation(Vec3::new(1.0, 2.0, 0.0)),
+            ChildOf(parent),
+        ));
+
+        let commands = scene_commands(&mut world);
+        let [WorldCommand::Spawn { components }] = commands.as_slice() else {
+            panic!("expected one top-level spawn, got {:?}", commands);
+        };
+        let Some(ProtoComponent::Children(children)) = components.last() else {
+            panic!("expected nested children, got {:?}", components);
+        };
+        assert!(matches!(
+            children[0].last(),
+            Some(ProtoComponent::Transform { translation, .. }) if *translation == Vec3::new(0.0, 2.0, 0.0)
+        ));
+
+        let path =
+            std::env::temp_dir().join(format!("dimensify_scene_{}.jsonl", std::process::id()));
+        let line = serde_json::to_string(&commands[0]).unwrap();
+        std::fs::write(&path, format!("{}\n", line)).unwrap();
+        world.init_resource::<CommandLog>();
+        assert_eq!(import_scene(&mut world, &path).unwrap(), 1);
+        std::fs::remove_file(&path).unwrap();
+        let log = world.resource::<CommandLog>();
+        let [(_, WorldCommand::Spawn { components })] = log.commands.as_slice() else {
+            panic!("expected the import to queue one spawn");
+        };
+        assert!(
+            matches!(components.last(), Some(ProtoComponent::Children(children)) if children.len() == 1)
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! Scene replication for late-joining viewers.
+//!
+//! A viewer running as server records the protocol components of every entity it
+//! created from a `WorldCommand`. When another viewer links and announces itself
+//! (see [`TransportPeer`]), it receives a [`SceneSync::Snapshot`] of that state,
+//! followed by every command applied afterwards. A viewer running as client
+//! mirrors what it receives, mapping the source entity ids onto its own.
+//!
+//! The snapshot nests entities spawned as `ProtoComponent::Children` in their parent,
+//! so only the top-level ones are mapped on the mirror.
+//!
+//! Components removed with `WorldCommand::Remove` are dropped from the snapshot as
+//! well. `WorldCommand::Update` is sent as the `Insert` of its component, which a
+//! mirror applies and tracks like any other insert.
+
+use std::collections::HashMap;
+
+use bevy::{
+    ecs::component::{ComponentId, Components},
+    prelude::*,
+};
+use dimensify_protocol::{MessageChannel, ProtoComponent, SceneSync, WorldCommand};
+use dimensify_transport::{
+    LinkStats, StreamReliable, TransportEndpoint, TransportPeer, authenticate_peers,
+};
+use lightyear::prelude::{LinkOf, MessageReceiver, MessageSender};
+
+use super::protocol_response::{
+    apply_new_commands,
+    controller::{
+        PendingMaterialInsertion, PendingMeshInsertion, apply_components, remove_component,
+        track_components,
+    },
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<AppliedCommands>()
+        .init_resource::<MirroredEntities>()
+        .add_systems(
+            Update,
+            (
+                accept_scene_mirrors.after(authenticate_peers),
+                send_scene_sync.after(apply_new_commands),
+                // before the pending meshes and materials are inserted
+                receive_scene_sync.before(apply_new_commands),
+            ),
+        );
+}
+
+/// Protocol components an entity was spawned or extended with. `Children` are not
+/// kept: the entities they spawned track their own components.
+#[derive(Component, Clone, Debug, Default)]
+pub struct ProtocolComponents(pub Vec<ProtoComponent>);
+
+impl ProtocolComponents {
+    pub fn new(components: &[ProtoComponent]) -> Self {
+        let mut tracked = Self::default();
+        tracked.merge(components);
+        tracked
+    }
+
+    /// Merge components, replacing any earlier component of the same kind.
+    pub fn merge(&mut self, components: &[ProtoComponent]) {
+        for component in components {
+            if matches!(component, ProtoComponent::Children(_)) {
+                continue;
+            }
+            let kind = std::mem::discriminant(component);
+            self.0.retain(|c| std::mem::discriminant(c) != kind);
+            self.0.push(component.clone());
+        }
+    }
+
+    /// Forget the component stored as the Bevy component `id`, after it was removed.
+    pub fn remove_by_id(&mut self, id: ComponentId, components: &Components) {
+        self.0
+            .retain(|component| bevy_component_id(component, components) != Some(id));
+    }
+}
+
+/// The Bevy component a protocol component is inserted as.
+fn bevy_component_id(component: &ProtoComponent, components: &Components) -> Option<ComponentId> {
+    match component {
+        ProtoComponent::Name(_) => components.component_id::<Name>(),
+        ProtoComponent::Transform { .. } => components.component_id::<Transform>(),
+        ProtoComponent::Mesh3d(_) => components.component_id::<Mesh3d>(),
+        ProtoComponent::MeshMaterial3d(_) => {
+            components.component_id::<MeshMaterial3d<StandardMaterial>>()
+        }
+        ProtoComponent::Children(_) => None,
+    }
+}
+
+/// `Update` as the `Insert` of its component, so mirrors never have to apply updates.
+fn update_as_insert(command: &WorldCommand) -> WorldCommand {
+    match command {
+        WorldCommand::Update { entity, component } => WorldCommand::Insert {
+            entity: *entity,
+            components: vec![component.clone()],
+        },
+        command => command.clone(),
+    }
+}
+
+/// An entity as seen by [`nested_scene`].
+pub(crate) struct SceneNode {
+    pub(crate) components: Vec<ProtoComponent>,
+    pub(crate) parent: Option<Entity>,
+    pub(crate) children: Vec<Entity>,
+}
+
+/// The protocol entities among `entities` that have no protocol-spawned parent, with
+/// their protocol-spawned descendants nested as `ProtoComponent::Children`. `node` gives
+/// an entity's components and relatives, or `None` if it was not spawned by a command.
+pub(crate) fn nested_scene(
+    entities: impl IntoIterator<Item = Entity>,
+    node: &impl Fn(Entity) -> Option<SceneNode>,
+) -> Vec<(Entity, Vec<ProtoComponent>)> {
+    entities
+        .into_iter()
+        .filter(|entity| {
+            node(*entity).is_some_and(|n| n.parent.is_none_or(|parent| node(parent).is_none()))
+        })
+        .filter_map(|entity| Some((entity, nested_components(entity, node)?)))
+        .collect()
+}
+
+fn nested_components(
+    entity: Entity,
+    node: &impl Fn(Entity) -> Option<SceneNode>,
+) -> Option<Vec<ProtoComponent>> {
+    let SceneNode {
+        mut components,
+        children,
+        ..
+    } = node(entity)?;
+    let children: Vec<_> = children
+        .into_iter()
+        .filter_map(|child| nested_components(child, node))
+        .collect();
+    if !children.is_empty() {
+        components.push(ProtoComponent::Children(children));
+    }
+    Some(components)
+}
+
+/// Commands applied since the last sync, with the entity each resolved to.
+#[derive(Resource, Default)]
+pub(crate) struct AppliedCommands {
+    pub(crate) items: Vec<(WorldCommand, Entity)>,
+}
+
+/// Source entity id to local entity, on a viewer mirroring another one.
+#[derive(Resource, Default, Debug)]
+pub struct MirroredEntities {
+    map: HashMap<Entity, Entity>,
+}
+
+impl MirroredEntities {
+    /// Local entity mirroring the given source entity.
+    pub fn get(&self, source: Entity) -> Option<Entity> {
+        self.map.get(&source).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+/// A linked viewer that mirrors this one. `synced` once the snapshot was sent.
+#[derive(Component)]
+struct SceneMirror {
+    synced: bool,
+}
+
+fn accept_scene_mirrors(
+    mut commands: Commands,
+    links: Query<(Entity, &TransportPeer), (With<LinkOf>, Added<TransportPeer>)>,
+) {
+    for (entity, peer) in &links {
+        // mirrors only read the scene, so any role will do
+        if peer.endpoint == TransportEndpoint::Viewer && peer.role.is_some() {
+            info!("Viewer {:?} linked, sending scene snapshot", entity);
+            commands
+                .entity(entity)
+                .insert(SceneMirror { synced: false });
+        }
+    }
+}
+
+fn send_scene_sync(
+    mut applied: ResMut<AppliedCommands>,
+    scene: Query<(
+        Entity,
+        &ProtocolComponents,
+        Option<&ChildOf>,
+        Option<&Children>,
+    )>,
+    mut mirrors: Query<(
+        &mut SceneMirror,
+        &mut MessageSender<SceneSync>,
+        &mut LinkStats,
+    )>,
+) {
+    let applied = std::mem::take(&mut applied.items);
+    for (mut mirror, mut sender, mut stats) in &mut mirrors {
+        if !mirror.synced {
+            // commands applied this frame are already flushed into the snapshot
+            let entities = nested_scene(scene.iter().map(|(entity, ..)| entity), &|entity| {
+                let (_, components, parent, children) = scene.get(entity).ok()?;
+                Some(SceneNode {
+                    components: components.0.clone(),
+                    parent: parent.map(ChildOf::parent),
+                    children: children
+                        .map(|children| children.to_vec())
+                        .unwrap_or_default(),
+                })
+            });
+            sender.send::<StreamReliable>(SceneSync::Snapshot { entities });
+            stats.sent(MessageChannel::SceneSync);
+            mirror.synced = true;
+            continue;
+        }
+        for (command, entity) in &applied {
+            sender.send::<StreamReliable>(SceneSync::Command {
+                command: update_as_insert(command),
+                entity: *entity,
+            });
+            stats.sent(MessageChannel::SceneSync);
+        }
+    }
+}
+
+fn receive_scene_sync(
+    mut commands: Commands,
+    mut mirrored: ResMut<MirroredEntities>,
+    mut pending_mesh_insertion: ResMut<PendingMeshInsertion>,
+    mut pending_material_insertion: ResMut<PendingMaterialInsertion>,
+    mut receivers: Query<(&mut MessageReceiver<SceneSync>, &mut LinkStats), Without<LinkOf>>,
+) {
+    for (mut receiver, mut stats) in &mut receivers {
+        for sync in receiver.receive() {
+            stats.received(MessageChannel::SceneSync);
+            match sync {
+                SceneSync::Snapshot { entities } => {
+                    info!("Mirroring scene snapshot with {} entities", entities.len());
+                    for (_, local) in mirrored.map.drain() {
+                        commands.entity(local).try_despawn();
+                    }
+                    for (source, components) in entities {
+                        let mut entity = commands.spawn(ProtocolComponents::new(&components));
+                        apply_components(
+                            &mut entity,
+                            &components,
+                            &mut pending_mesh_insertion,
+                            &mut pending_material_insertion,
+                        );
+                        mirrored.map.insert(source, entity.id());
+                    }
+                }
+                SceneSync::Command { command, entity } => match command {
+                    WorldCommand::Spawn { components } => {
+                        let mut local = commands.spawn(ProtocolComponents::new(&components));
+                        apply_components(
+                            &mut local,
+                            &components,
+                            &mut pending_mesh_insertion,
+                            &mut pending_material_insertion,
+                        );
+                        mirrored.map.insert(entity, local.id());
+                    }
+                    WorldCommand::Insert { components, .. } => {
+                        let Some(local) = mirrored.get(entity) else {
+                            warn!("Scene sync refers to unknown entity {:?}", entity);
+                            continue;
+                        };
+                        let mut local = commands.entity(local);
+                        apply_components(
+                            &mut local,
+                            &components,
+                            &mut pending_mesh_insertion,
+                            &mut pending_material_insertion,
+                        );
+                        track_components(&mut local, &components);
+                    }
+                    WorldCommand::Update { .. } => {
+                        // sources send updates as inserts, see `update_as_insert`
+                        warn!("Dropping scene sync update of {:?}", entity);
+                    }
+                    WorldCommand::Remove { component, .. } => {
+                        if let Some(local) = mirrored.get(entity) {
+                            remove_component(&mut commands.entity(local), component);
+                        }
+                    }
+                    WorldCommand::Despawn { .. } => {
+                        if let Some(local) = mirrored.map.remove(&entity) {
+                            commands.entity(local).try_despawn();
+                        }
+                    }
+                    WorldCommand::Clear => {
+                        for (_, local) in mirrored.map.drain() {
+                            commands.entity(local).try_despawn();
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProtocolComponents, update_as_insert};
+    use crate::services::protocol_response::controller::remove_component;
+    use bevy::prelude::*;
+    use dimensify_protocol::{WorldCommand, prelude::Component as ProtoComponent};
+
+    fn transform() -> ProtoComponent {
+        ProtoComponent::Transform {
+            translation: Default::default(),
+            rotation: Default::default(),
+            scale: Default::default(),
+        }
+    }
+
+    #[test]
+    fn merge_replaces_components_of_the_same_kind() {
+        let mut tracked = ProtocolComponents(vec![ProtoComponent::Name("a".to_string())]);
+        tracked.merge(&[ProtoComponent::Name("b".to_string()), transform()]);
+
+        assert_eq!(tracked.0.len(), 2);
+        assert!(matches!(&tracked.0[0], ProtoComponent::Name(name) if name == "b"));
+    }
+
+    #[test]
+    fn removed_components_are_no_longer_tracked() {
+        let mut world = World::new();
+        let entity = world
+            .spawn((
+                Name::new("a"),
+                Transform::default(),
+                ProtocolComponents(vec![ProtoComponent::Name("a".to_string()), transform()]),
+            ))
+            .id();
+        let name = world.components().component_id::<Name>().unwrap();
+
+        remove_component(&mut world.commands().entity(entity), name.into());
+        world.flush();
+
+        assert!(world.get::<Name>(entity).is_none());
+        let tracked = world.get::<ProtocolComponents>(entity).unwrap();
+        assert_eq!(tracked.0.len(), 1);
+        assert!(matches!(tracked.0[0], ProtoComponent::Transform { .. }));
+    }
+
+    #[test]
+    fn updates_are_synced_as_inserts() {
+        let entity = Entity::from_raw_u32(7).unwrap();
+        let command = update_as_insert(&WorldCommand::Update {
+            entity,
+            component: transform(),
+        });
+
+        assert!(matches!(
+            command,
+            WorldCommand::Insert { entity: e, components } if e == entity && components.len() == 1
+        ));
+    }
+}
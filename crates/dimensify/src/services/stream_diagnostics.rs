@@ -0,0 +1,36 @@
+//! "Stream" section of the diagnostics tab: the size of the command log, how fast its
+//! commands are applied, and how full the telemetry store is.
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use dimensify_ui::{diagnostics::diagnostic_plot, tabs::DiagnosticsSections};
+
+use crate::{
+    services::protocol_response::COMMANDS_APPLIED, stream::CommandLog, telemetry::TelemetryStore,
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<DiagnosticsSections>();
+    app.world_mut()
+        .resource_mut::<DiagnosticsSections>()
+        .register("Stream", std::sync::Arc::new(stream_section));
+}
+
+fn stream_section(ui: &mut egui::Ui, world: &mut World) {
+    egui::Grid::new("diagnostics_stream").show(ui, |ui| {
+        ui.label("Logged commands");
+        ui.label(
+            world
+                .get_resource::<CommandLog>()
+                .map_or(0, |log| log.commands.len())
+                .to_string(),
+        );
+        ui.end_row();
+        if let Some(store) = world.get_resource::<TelemetryStore>() {
+            ui.label("Telemetry events");
+            ui.label(format!("{} / {}", store.len(), store.capacity()));
+            ui.end_row();
+        }
+    });
+    diagnostic_plot(ui, world, &COMMANDS_APPLIED, "Applied commands");
+}
@@ -1,26 +1,48 @@
 use bevy::prelude::*;
-use dimensify_protocol::{ProtoRequest, ProtoResponse};
+use dimensify_protocol::{AccessRole, MessageChannel, ProtoRequest, ProtoResponse};
+use dimensify_transport::{LinkStats, TransportConfig, TransportPeer, authenticate_peers};
 
 #[cfg(feature = "transport")]
-use lightyear::prelude::{MessageReceiver, MessageSender};
+use lightyear::prelude::{LinkOf, MessageReceiver};
 
-use crate::services::protocol_response::{
-    draw::DrawCommand, pending_response::PendingApplyCommand,
-};
-use dimensify_protocol::WorldCommand;
+use crate::services::protocol_response::draw::DrawCommand;
+use dimensify_protocol::{WidgetCommand, WorldCommand};
+use dimensify_ui::tabs::PanelRegistry;
+
+#[cfg(feature = "robot")]
+use crate::robot::urdf_loader::{UrdfLoadRequest, UrdfLoadRequestParams};
 
 use crate::{
-    services::protocol_response::{
-        list::handle_pending_request_list, pending_response::PendingRequestList,
+    services::{
+        protocol_response::{
+            apply_new_commands,
+            gltf_export::{PendingGltfExports, handle_pending_gltf_exports},
+            list::handle_pending_request_list,
+            pending_response::{Awaiting, PendingResponses, send_pending_responses},
+            stats::handle_pending_request_stats,
+        },
+        remote_widgets::RemoteWidgets,
+        viewer_events::EventSubscription,
     },
     stream::CommandLog,
+    telemetry::bindings::TelemetryBindings,
+    ui::widgets::{self, WidgetCommandQueue, WidgetRegistry},
 };
 
 pub fn plugin(app: &mut App) {
-    app.add_systems(Update, handle_pending_request_list)
+    app.register_required_components::<MessageReceiver<ProtoRequest>, PendingResponses>()
         .add_systems(
             Update,
-            handle_transport_requests.before(handle_pending_request_list),
+            (
+                handle_transport_requests
+                    .after(authenticate_peers)
+                    .before(apply_new_commands),
+                (handle_pending_request_list, handle_pending_request_stats),
+                handle_pending_gltf_exports
+                    .run_if(|exports: Res<PendingGltfExports>| !exports.items.is_empty()),
+                send_pending_responses.after(apply_new_commands),
+            )
+                .chain(),
         );
 }
 
@@ -30,20 +52,52 @@ pub fn plugin(app: &mut App) {
 struct ClientAdded(u64);
 
 /// Incoming requests from the transport layer.
+///
+/// Requests from linked peers need the role their token grants (see [`TransportPeer`]);
+/// requests from the server this viewer connected to are trusted. Every request gets
+/// exactly one response, sent in request order by [`send_pending_responses`].
+#[allow(clippy::too_many_arguments)]
 fn handle_transport_requests(
     mut commands: Commands,
+    config: Res<TransportConfig>,
     mut command_log: ResMut<CommandLog>,
+    mut telemetry_bindings: Option<ResMut<TelemetryBindings>>,
+    mut remote_widgets: ResMut<RemoteWidgets>,
+    mut widget_queue: ResMut<WidgetCommandQueue>,
+    widget_registry: Res<WidgetRegistry>,
+    panels: Option<Res<PanelRegistry>>,
+    mut gltf_exports: ResMut<PendingGltfExports>,
+    #[cfg(feature = "robot")] mut urdf_requests: Option<ResMut<Messages<UrdfLoadRequest>>>,
     // draw_commands: Query<Entity, With<DrawCommand>>,
     // mesh_entities: Query<Entity, With<Mesh3d>>,
     mut receivers: Populated<(
         Entity,
         &mut MessageReceiver<ProtoRequest>,
-        &mut MessageSender<ProtoResponse>,
+        &mut PendingResponses,
+        &mut LinkStats,
+        Has<LinkOf>,
+        Option<&TransportPeer>,
     )>,
 ) {
-    for (entity, mut receiver, mut sender) in &mut receivers {
+    for (entity, mut receiver, mut pending, mut stats, is_link, peer) in &mut receivers {
+        let role = match is_link {
+            true => TransportPeer::role_of(&config, peer),
+            false => Some(AccessRole::Admin),
+        };
         for request in receiver.receive() {
             info!("Received request: {:?}", request);
+            stats.received(MessageChannel::Requests);
+
+            if !role.is_some_and(|role| role.allows(&request)) {
+                warn!("Rejected request from {:?} with role {:?}", entity, role);
+                pending.push_ready(ProtoResponse::Error {
+                    message: format!(
+                        "permission denied: requires the {:?} role",
+                        AccessRole::required_for(&request)
+                    ),
+                });
+                continue;
+            }
 
             match request {
                 ProtoRequest::ApplyCommand(command) => {
@@ -51,16 +105,169 @@ fn handle_transport_requests(
                     info!("Applying command: {:?}", command);
 
                     command_log.commands.push((entity, command));
-                    // indicate that we need to send a response to the client after the command is applied
-                    commands.entity(entity).insert(PendingApplyCommand);
-
-                    // let _ = sender.send::<dimensify_transport::StreamReliable>(ProtoResponse::Ack);
+                    // answered once the command is applied
+                    pending.push_awaiting(Awaiting::ApplyCommand);
+                }
+                ProtoRequest::List => pending.push_awaiting(Awaiting::List),
+                ProtoRequest::Stats => pending.push_awaiting(Awaiting::Stats),
+                ProtoRequest::SetTelemetryBindings(bindings) => {
+                    pending.push_ready(match telemetry_bindings.as_mut() {
+                        Some(telemetry_bindings) => {
+                            telemetry_bindings.set(bindings);
+                            ProtoResponse::Ack
+                        }
+                        None => ProtoResponse::Error {
+                            message: "telemetry is not enabled in this viewer".to_string(),
+                        },
+                    });
                 }
-                ProtoRequest::List => {
-                    // We need to wait for the response to be sent before we can remove the component.
-                    commands.entity(entity).insert(PendingRequestList);
+                ProtoRequest::Subscribe(kinds) => {
+                    commands
+                        .entity(entity)
+                        .insert(EventSubscription(kinds.into_iter().collect()));
+                    pending.push_ready(ProtoResponse::Ack);
                 }
+                ProtoRequest::Widget(command) => {
+                    pending.push_ready(match &command {
+                        WidgetCommand::Panel { id, .. }
+                            if panels.as_ref().is_some_and(|panels| {
+                                widgets::is_builtin_panel(id, &widget_registry, panels)
+                            }) =>
+                        {
+                            ProtoResponse::Error {
+                                message: format!("'{}' is the title of a built-in panel", id),
+                            }
+                        }
+                        _ => {
+                            remote_widgets.apply(entity, command, &mut widget_queue);
+                            ProtoResponse::Ack
+                        }
+                    });
+                }
+                ProtoRequest::ExportGltf { path, joints } => {
+                    gltf_exports.items.push((entity, path, joints));
+                    pending.push_awaiting(Awaiting::ExportGltf);
+                }
+                #[cfg(feature = "robot")]
+                ProtoRequest::LoadUrdf { path, fixed_base } => {
+                    pending.push_ready(match urdf_requests.as_mut() {
+                        Some(urdf_requests) => {
+                            let params = UrdfLoadRequestParams {
+                                fixed_base,
+                                ..default()
+                            };
+                            urdf_requests.write(UrdfLoadRequest::new(path, Some(params)));
+                            ProtoResponse::Ack
+                        }
+                        None => ProtoResponse::Error {
+                            message: "robot loading is not enabled in this viewer".to_string(),
+                        },
+                    });
+                }
+                #[cfg(not(feature = "robot"))]
+                ProtoRequest::LoadUrdf { .. } => {
+                    pending.push_ready(ProtoResponse::Error {
+                        message: "this viewer is built without the `robot` feature".to_string(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "transport_loopback"))]
+mod tests {
+    use std::{
+        net::UdpSocket,
+        time::{Duration, Instant},
+    };
+
+    use bevy::{asset::AssetPlugin, prelude::*};
+    use dimensify_protocol::{ProtoRequest, ProtoResponse, WorldCommand, prelude::ProtoComponent};
+    use dimensify_transport::{
+        TransportConfig, TransportConnection, TransportController, TransportEndpoint,
+        TransportMode, TransportRuntimePlugin,
+    };
+
+    use crate::{
+        services::{
+            protocol_response::{
+                controller::{self, CommandCursor},
+                gltf_export::PendingGltfExports,
+            },
+            remote_widgets,
+            scene_sync::AppliedCommands,
+        },
+        stream::CommandLog,
+    };
+
+    /// Run `viewer`, which lives on this thread, until `controller` gets a response.
+    fn next_response(viewer: &mut App, controller: &TransportController) -> ProtoResponse {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            viewer.update();
+            if let Some(response) = controller.try_recv() {
+                return response;
             }
+            assert!(Instant::now() < deadline, "no response from the viewer");
+            std::thread::sleep(Duration::from_millis(5));
         }
     }
+
+    #[test]
+    fn controller_reaches_viewer_in_process() {
+        // loopback addresses are only keys; an ephemeral port keeps parallel tests apart
+        let addr = UdpSocket::bind("127.0.0.1:0")
+            .and_then(|socket| socket.local_addr())
+            .unwrap();
+        let mut config = TransportConfig {
+            mode: TransportMode::Loopback,
+            server_addr: addr,
+            ..Default::default()
+        };
+        // the request pipeline of the viewer, without rendering
+        let mut viewer = App::new();
+        viewer
+            .add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_resource::<CommandLog>()
+            .init_resource::<CommandCursor>()
+            .init_resource::<AppliedCommands>()
+            .init_resource::<PendingGltfExports>()
+            .add_plugins((super::plugin, controller::plugin, remote_widgets::plugin))
+            .add_plugins(TransportRuntimePlugin {
+                config: config.clone(),
+            });
+        viewer.finish();
+        viewer.cleanup();
+
+        config.connection = TransportConnection::Client;
+        config.endpoint = TransportEndpoint::Controller;
+        let controller = TransportController::start(config);
+        controller
+            .send(ProtoRequest::ApplyCommand(WorldCommand::Spawn {
+                components: vec![ProtoComponent::Name("box".to_string())],
+            }))
+            .unwrap();
+        let spawned = match next_response(&mut viewer, &controller) {
+            ProtoResponse::CommandResponseEntity(entity) => entity,
+            response => panic!("unexpected response {:?}", response),
+        };
+        assert_eq!(
+            viewer.world().get::<Name>(spawned).map(Name::as_str),
+            Some("box")
+        );
+
+        controller.send(ProtoRequest::List).unwrap();
+        let ProtoResponse::Entities { entities } = next_response(&mut viewer, &controller) else {
+            panic!("List is answered with Entities");
+        };
+        assert!(
+            entities
+                .iter()
+                .any(|entity| entity.id == spawned.to_bits()
+                    && entity.name.as_deref() == Some("box"))
+        );
+    }
 }
@@ -0,0 +1,79 @@
+//! "Transport" section of the diagnostics tab: every link with its address, role,
+//! round-trip time, message rates per channel, pending requests and errors.
+//!
+//! Shows the same numbers a controller gets from `ProtoRequest::Stats`.
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use dimensify_protocol::PeerStats;
+use dimensify_transport::{PeerStatsData, TransportStatus, peer_stats};
+use dimensify_ui::tabs::DiagnosticsSections;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<DiagnosticsSections>();
+    app.world_mut()
+        .resource_mut::<DiagnosticsSections>()
+        .register("Transport", std::sync::Arc::new(transport_section));
+}
+
+fn transport_section(ui: &mut egui::Ui, world: &mut World) {
+    if let Some(status) = world.get_resource::<TransportStatus>() {
+        ui.label(format!("State: {}", status.get()));
+    }
+    let peers: Vec<PeerStats> = world
+        .query::<PeerStatsData>()
+        .iter(world)
+        .map(peer_stats)
+        .collect();
+    if peers.is_empty() {
+        ui.label("No connected peers.");
+        return;
+    }
+    for peer in &peers {
+        ui.separator();
+        peer_ui(ui, peer);
+    }
+}
+
+fn peer_ui(ui: &mut egui::Ui, peer: &PeerStats) {
+    let addr = peer
+        .addr
+        .map_or_else(|| "-".to_string(), |addr| addr.to_string());
+    ui.strong(format!(
+        "{:?} {} {}",
+        peer.entity,
+        peer.endpoint.as_deref().unwrap_or("server"),
+        addr
+    ));
+    ui.horizontal(|ui| {
+        ui.label(match peer.role {
+            Some(role) => format!("role: {:?}", role),
+            None => "role: -".to_string(),
+        });
+        ui.label(match peer.rtt_ms {
+            Some(rtt) => format!("rtt: {:.1} ms", rtt),
+            None => "rtt: -".to_string(),
+        });
+        ui.label(format!("pending: {}", peer.pending_requests));
+        ui.label(format!("errors: {}", peer.errors));
+    });
+
+    egui::Grid::new(("transport_peer", peer.entity.to_bits()))
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("channel");
+            ui.label("in");
+            ui.label("in/s");
+            ui.label("out");
+            ui.label("out/s");
+            ui.end_row();
+            for channel in &peer.channels {
+                ui.label(format!("{:?}", channel.channel));
+                ui.label(channel.received.to_string());
+                ui.label(format!("{:.1}", channel.received_per_sec));
+                ui.label(channel.sent.to_string());
+                ui.label(format!("{:.1}", channel.sent_per_sec));
+                ui.end_row();
+            }
+        });
+}
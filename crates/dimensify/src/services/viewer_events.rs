@@ -0,0 +1,259 @@
+//! Events pushed to controllers that sent `ProtoRequest::Subscribe`.
+//!
+//! Sources: mesh picking (any click), selection (primary click, cleared with
+//! Escape), transform edits of protocol-spawned entities that telemetry or physics do
+//! not drive (see [`DrivenTransform`]), widget interactions and,
+//! with the `robot` feature, robot link collisions. Interactions with a widget a
+//! controller created go to that controller alone (see [`RemoteWidgets`]).
+
+use std::collections::HashSet;
+
+use bevy::{
+    picking::{
+        events::{Click, Pointer},
+        mesh_picking::MeshPickingPlugin,
+        pointer::PointerButton,
+    },
+    prelude::*,
+};
+use dimensify_protocol::{
+    MessageChannel, PickButton, ProtoComponent, ViewerEvent, ViewerEventKind,
+};
+use dimensify_transport::{LinkStats, StreamReliable};
+use lightyear::prelude::MessageSender;
+
+use super::{remote_widgets::RemoteWidgets, scene_sync::ProtocolComponents};
+#[cfg(feature = "robot")]
+use crate::robot::RobotLinkIsColliding;
+use crate::{
+    scene::DrivenTransform,
+    telemetry::{apply_telemetry_to_ecs, bindings::apply_transform_bindings},
+    ui::widgets::WidgetInteraction,
+};
+
+pub fn plugin(app: &mut App) {
+    if !app.is_plugin_added::<MeshPickingPlugin>() {
+        app.add_plugins(MeshPickingPlugin);
+    }
+    app.init_resource::<PendingViewerEvents>()
+        .init_resource::<ViewerSelection>()
+        .add_message::<WidgetInteraction>()
+        .add_systems(
+            Update,
+            (
+                (
+                    collect_picking_events,
+                    // once telemetry has marked the entities it drives
+                    collect_transform_edits
+                        .after(apply_telemetry_to_ecs)
+                        .after(apply_transform_bindings),
+                    collect_widget_events,
+                ),
+                send_viewer_events,
+            )
+                .chain(),
+        );
+
+    #[cfg(feature = "robot")]
+    app.add_systems(Update, collect_collision_events.before(send_viewer_events));
+}
+
+/// Event kinds a link subscribed to.
+#[derive(Component, Debug, Default)]
+pub struct EventSubscription(pub HashSet<ViewerEventKind>);
+
+/// Events collected this frame, waiting to be sent.
+#[derive(Resource, Default)]
+pub struct PendingViewerEvents {
+    /// `None` goes to every subscriber of the event's kind.
+    events: Vec<(Option<Entity>, ViewerEvent)>,
+}
+
+impl PendingViewerEvents {
+    pub fn push(&mut self, event: ViewerEvent) {
+        self.events.push((None, event));
+    }
+
+    /// Send `event` to `link` only, whether or not it subscribed.
+    pub fn push_to(&mut self, link: Entity, event: ViewerEvent) {
+        self.events.push((Some(link), event));
+    }
+}
+
+/// The entity selected in the viewer.
+#[derive(Resource, Default, Debug)]
+pub struct ViewerSelection {
+    pub entity: Option<Entity>,
+}
+
+fn collect_picking_events(
+    mut clicks: MessageReader<Pointer<Click>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut selection: ResMut<ViewerSelection>,
+    mut pending: ResMut<PendingViewerEvents>,
+) {
+    for click in clicks.read() {
+        let button = match click.button {
+            PointerButton::Primary => PickButton::Primary,
+            PointerButton::Secondary => PickButton::Secondary,
+            PointerButton::Middle => PickButton::Middle,
+        };
+        pending.push(ViewerEvent::Picked {
+            entity: click.entity,
+            position: click.hit.position,
+            button,
+        });
+        if button == PickButton::Primary && selection.entity != Some(click.entity) {
+            selection.entity = Some(click.entity);
+            pending.push(ViewerEvent::Selected {
+                entity: selection.entity,
+            });
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Escape) && selection.entity.is_some() {
+        selection.entity = None;
+        pending.push(ViewerEvent::Selected { entity: None });
+    }
+}
+
+/// Report transform changes that did not come from a `WorldCommand`, telemetry or
+/// physics, and track the new transform so scene snapshots stay current.
+fn collect_transform_edits(
+    mut entities: Query<
+        (Entity, &Transform, &mut ProtocolComponents),
+        (Changed<Transform>, Without<DrivenTransform>),
+    >,
+    mut pending: ResMut<PendingViewerEvents>,
+) {
+    for (entity, transform, mut tracked) in &mut entities {
+        let known = tracked.0.iter().find_map(|component| match component {
+            ProtoComponent::Transform {
+                translation,
+                rotation,
+                scale,
+            } => Some(Transform {
+                translation: *translation,
+                rotation: *rotation,
+                scale: *scale,
+            }),
+            _ => None,
+        });
+        if known.unwrap_or_default() == *transform {
+            continue;
+        }
+        tracked.merge(&[ProtoComponent::Transform {
+            translation: transform.translation,
+            rotation: transform.rotation,
+            scale: transform.scale,
+        }]);
+        pending.push(ViewerEvent::TransformEdited {
+            entity,
+            translation: transform.translation,
+            rotation: transform.rotation,
+            scale: transform.scale,
+        });
+    }
+}
+
+fn collect_widget_events(
+    mut interactions: MessageReader<WidgetInteraction>,
+    widgets: Res<RemoteWidgets>,
+    mut pending: ResMut<PendingViewerEvents>,
+) {
+    for interaction in interactions.read() {
+        let event = match interaction.clone() {
+            WidgetInteraction::Clicked { id } => ViewerEvent::WidgetClicked { id },
+            WidgetInteraction::Toggled { id, checked } => {
+                ViewerEvent::WidgetToggled { id, checked }
+            }
+            WidgetInteraction::Changed { id, value } => ViewerEvent::WidgetChanged { id, value },
+        };
+        match event.widget_id().and_then(|id| widgets.owner(id)) {
+            Some(owner) => pending.push_to(owner, event),
+            None => pending.push(event),
+        }
+    }
+}
+
+#[cfg(feature = "robot")]
+fn collect_collision_events(
+    changed: Query<(Entity, &RobotLinkIsColliding), Changed<RobotLinkIsColliding>>,
+    mut removed: RemovedComponents<RobotLinkIsColliding>,
+    mut pending: ResMut<PendingViewerEvents>,
+) {
+    for (entity, colliding) in &changed {
+        pending.push(ViewerEvent::CollisionChanged {
+            entity,
+            colliding_with: colliding.entities.iter().copied().collect(),
+        });
+    }
+    for entity in removed.read() {
+        pending.push(ViewerEvent::CollisionChanged {
+            entity,
+            colliding_with: Vec::new(),
+        });
+    }
+}
+
+fn send_viewer_events(
+    mut pending: ResMut<PendingViewerEvents>,
+    mut links: Query<(
+        Option<&EventSubscription>,
+        &mut MessageSender<ViewerEvent>,
+        &mut LinkStats,
+    )>,
+) {
+    for (target, event) in pending.events.drain(..) {
+        if let Some(link) = target {
+            if let Ok((_, mut sender, mut stats)) = links.get_mut(link) {
+                sender.send::<StreamReliable>(event);
+                stats.sent(MessageChannel::Events);
+            }
+            continue;
+        }
+        let kind = event.kind();
+        for (subscription, mut sender, mut stats) in &mut links {
+            if subscription.is_some_and(|subscription| subscription.0.contains(&kind)) {
+                sender.send::<StreamReliable>(event.clone());
+                stats.sent(MessageChannel::Events);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use dimensify_protocol::ViewerEvent;
+
+    use super::{PendingViewerEvents, collect_transform_edits};
+    use crate::{scene::DrivenTransform, services::scene_sync::ProtocolComponents};
+
+    #[test]
+    fn driven_transforms_are_not_reported_as_edits() {
+        let mut app = App::new();
+        app.init_resource::<PendingViewerEvents>()
+            .add_systems(Update, collect_transform_edits);
+        let edited = app
+            .world_mut()
+            .spawn((
+                ProtocolComponents::default(),
+                Transform::from_xyz(1.0, 0.0, 0.0),
+            ))
+            .id();
+        app.world_mut().spawn((
+            ProtocolComponents::default(),
+            Transform::from_xyz(2.0, 0.0, 0.0),
+            DrivenTransform,
+        ));
+        app.update();
+
+        let events = &app.world().resource::<PendingViewerEvents>().events;
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0],
+            (None, ViewerEvent::TransformEdited { entity, .. }) if entity == edited
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+//! Long-running jobs, shown in the tasks tab of the UI.
+//!
+//! Subsystems [`Tasks::start`] a task when a job begins, update its progress while it
+//! runs and end it with [`Tasks::finish`] or [`Tasks::fail`]. Jobs that can be stopped
+//! mark their task [`Tasks::cancellable`], check [`Tasks::cancel_requested`] and confirm
+//! with [`Tasks::cancelled`]. Short jobs that run often, like IK batches, are recorded
+//! with [`Tasks::record_repeated`] so they do not crowd out the others.
+
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::{platform::time::Instant, prelude::*};
+
+/// Ended tasks kept for the tab; the oldest are dropped first.
+const FINISHED_CAPACITY: usize = 200;
+
+/// Added once by `DimensifyPlugin`; the plugins that report jobs rely on it.
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Tasks>();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskId(u64);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskStatus {
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub id: TaskId,
+    /// What kind of job this is, e.g. "URDF" or "Telemetry export".
+    pub kind: &'static str,
+    pub label: String,
+    /// Fraction done in 0..1, if the job can tell.
+    pub progress: Option<f32>,
+    /// What the job is doing right now.
+    pub detail: Option<String>,
+    pub status: TaskStatus,
+    pub cancellable: bool,
+    pub cancel_requested: bool,
+    /// How often the job ran, see [`Tasks::record_repeated`].
+    pub count: u32,
+    started: Instant,
+    ended: Option<Instant>,
+}
+
+impl Task {
+    pub fn elapsed(&self) -> Duration {
+        self.ended.unwrap_or_else(Instant::now) - self.started
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == TaskStatus::Running
+    }
+}
+
+/// Running and recently ended tasks, oldest first.
+#[derive(Resource, Default)]
+pub struct Tasks {
+    tasks: VecDeque<Task>,
+    next_id: u64,
+}
+
+impl Tasks {
+    pub fn start(&mut self, kind: &'static str, label: impl Into<String>) -> TaskId {
+        let id = TaskId(self.next_id);
+        self.next_id += 1;
+        self.tasks.push_back(Task {
+            id,
+            kind,
+            label: label.into(),
+            progress: None,
+            detail: None,
+            status: TaskStatus::Running,
+            cancellable: false,
+            cancel_requested: false,
+            count: 1,
+            started: Instant::now(),
+            ended: None,
+        });
+        id
+    }
+
+    /// Add a job that already ended, e.g. one that ran within a single frame.
+    pub fn record(
+        &mut self,
+        kind: &'static str,
+        label: impl Into<String>,
+        started: Instant,
+        result: Result<(), String>,
+    ) {
+        let id = self.start(kind, label);
+        if let Some(task) = self.get_mut(id) {
+            task.started = started;
+        }
+        match result {
+            Ok(()) => self.finish(id),
+            Err(reason) => self.fail(id, reason),
+        }
+    }
+
+    /// Like [`Tasks::record`], but a job that ends like the newest ended task of the same
+    /// kind and label is counted into that task instead of being listed again.
+    pub fn record_repeated(
+        &mut self,
+        kind: &'static str,
+        label: impl Into<String>,
+        started: Instant,
+        result: Result<(), String>,
+    ) {
+        let label = label.into();
+        let status = match &result {
+            Ok(()) => TaskStatus::Done,
+            Err(reason) => TaskStatus::Failed(reason.clone()),
+        };
+        let previous = self
+            .tasks
+            .iter()
+            .rposition(|task| !task.is_running() && task.kind == kind && task.label == label);
+        match previous.filter(|index| self.tasks[*index].status == status) {
+            Some(index) => {
+                let mut task = self.tasks.remove(index).expect("index of a task");
+                task.count += 1;
+                task.started = started;
+                task.ended = Some(Instant::now());
+                self.tasks.push_back(task);
+            }
+            None => self.record(kind, label, started, result),
+        }
+    }
+
+    pub fn get(&self, id: TaskId) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    fn get_mut(&mut self, id: TaskId) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|task| task.id == id)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Task> {
+        self.tasks.iter()
+    }
+
+    pub fn running(&self) -> usize {
+        self.tasks.iter().filter(|task| task.is_running()).count()
+    }
+
+    /// Let the tab offer to cancel the task.
+    pub fn cancellable(&mut self, id: TaskId) {
+        if let Some(task) = self.get_mut(id) {
+            task.cancellable = true;
+        }
+    }
+
+    pub fn set_progress(&mut self, id: TaskId, progress: f32) {
+        if let Some(task) = self.get_mut(id) {
+            task.progress = Some(progress.clamp(0.0, 1.0));
+        }
+    }
+
+    pub fn set_detail(&mut self, id: TaskId, detail: impl Into<String>) {
+        if let Some(task) = self.get_mut(id) {
+            task.detail = Some(detail.into());
+        }
+    }
+
+    /// Ask the job to stop; it confirms with [`Tasks::cancelled`].
+    pub fn request_cancel(&mut self, id: TaskId) {
+        if let Some(task) = self.get_mut(id)
+            && task.cancellable
+            && task.is_running()
+        {
+            task.cancel_requested = true;
+        }
+    }
+
+    pub fn cancel_requested(&self, id: TaskId) -> bool {
+        self.get(id).is_some_and(|task| task.cancel_requested)
+    }
+
+    pub fn finish(&mut self, id: TaskId) {
+        self.end(id, TaskStatus::Done);
+    }
+
+    pub fn fail(&mut self, id: TaskId, reason: impl Into<String>) {
+        self.end(id, TaskStatus::Failed(reason.into()));
+    }
+
+    pub fn cancelled(&mut self, id: TaskId) {
+        self.end(id, TaskStatus::Cancelled);
+    }
+
+    /// Drop all tasks that ended.
+    pub fn clear_finished(&mut self) {
+        self.tasks.retain(Task::is_running);
+    }
+
+    fn end(&mut self, id: TaskId, status: TaskStatus) {
+        let Some(task) = self.get_mut(id).filter(|task| task.is_running()) else {
+            return;
+        };
+        if status == TaskStatus::Done {
+            task.progress = Some(1.0);
+        }
+        task.status = status;
+        task.ended = Some(Instant::now());
+
+        let ended = self.tasks.len() - self.running();
+        if ended > FINISHED_CAPACITY
+            && let Some(oldest) = self.tasks.iter().position(|task| !task.is_running())
+        {
+            self.tasks.remove(oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::platform::time::Instant;
+
+    use super::{FINISHED_CAPACITY, TaskStatus, Tasks};
+
+    #[test]
+    fn tasks_end_once_and_cancel_only_when_allowed() {
+        let mut tasks = Tasks::default();
+        let load = tasks.start("URDF", "robot.urdf");
+        let export = tasks.start("Export", "scene.glb");
+
+        tasks.request_cancel(load);
+        assert!(!tasks.cancel_requested(load));
+        tasks.cancellable(load);
+        tasks.request_cancel(load);
+        assert!(tasks.cancel_requested(load));
+        tasks.cancelled(load);
+
+        tasks.fail(export, "disk full");
+        tasks.finish(export);
+        assert_eq!(
+            tasks.get(export).unwrap().status,
+            TaskStatus::Failed("disk full".to_string())
+        );
+        assert_eq!(tasks.running(), 0);
+
+        tasks.start("IK", "batch");
+        tasks.clear_finished();
+        assert_eq!(tasks.iter().count(), 1);
+    }
+
+    #[test]
+    fn recorded_tasks_are_ended_and_the_oldest_ended_are_evicted() {
+        let mut tasks = Tasks::default();
+        let running = tasks.start("URDF", "robot.urdf");
+        tasks.record("Project", "saved", Instant::now(), Ok(()));
+        tasks.record(
+            "Project",
+            "opened",
+            Instant::now(),
+            Err("missing".to_string()),
+        );
+
+        let statuses: Vec<_> = tasks.iter().map(|task| task.status.clone()).collect();
+        assert_eq!(
+            statuses,
+            [
+                TaskStatus::Running,
+                TaskStatus::Done,
+                TaskStatus::Failed("missing".to_string())
+            ]
+        );
+        assert_eq!(tasks.iter().nth(1).unwrap().progress, Some(1.0));
+
+        for index in 0..FINISHED_CAPACITY {
+            tasks.record("Export", format!("scene {}", index), Instant::now(), Ok(()));
+        }
+        assert_eq!(tasks.iter().count(), FINISHED_CAPACITY + 1);
+        assert!(tasks.get(running).unwrap().is_running());
+        assert!(tasks.iter().all(|task| task.kind != "Project"));
+    }
+
+    #[test]
+    fn repeated_jobs_are_counted_into_one_task() {
+        let mut tasks = Tasks::default();
+        for _ in 0..3 {
+            tasks.record_repeated("IK", "end effector", Instant::now(), Ok(()));
+        }
+        tasks.record_repeated(
+            "IK",
+            "end effector",
+            Instant::now(),
+            Err("no solution".into()),
+        );
+        tasks.record_repeated("IK", "end effector", Instant::now(), Ok(()));
+        tasks.record_repeated("IK", "end effector", Instant::now(), Ok(()));
+
+        let counts: Vec<_> = tasks.iter().map(|task| task.count).collect();
+        assert_eq!(counts, [3, 1, 2]);
+        assert_eq!(tasks.iter().last().unwrap().status, TaskStatus::Done);
+    }
+}
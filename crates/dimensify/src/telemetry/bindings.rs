@@ -0,0 +1,488 @@
+//! Declarative telemetry bindings.
+//!
+//! A [`TelemetryBinding`] maps a telemetry path onto a target of a named entity
+//! (transform, robot joints, material colour, visibility or label text). Bindings
+//! are loaded from a JSONL file at startup or replaced at runtime through
+//! `ProtoRequest::SetTelemetryBindings`.
+
+use bevy::prelude::*;
+use dimensify_protocol::{TelemetryBinding, TelemetryBindingTarget, TelemetryPayload};
+
+use super::{TelemetryEcsSync, TelemetryState};
+use crate::scene::DrivenTransform;
+
+/// Active telemetry bindings.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct TelemetryBindings {
+    bindings: Vec<TelemetryBinding>,
+}
+
+impl TelemetryBindings {
+    /// Replace all bindings.
+    pub fn set(&mut self, bindings: Vec<TelemetryBinding>) {
+        self.bindings = bindings;
+    }
+
+    /// Add a binding, replacing any binding with the same entity and target.
+    pub fn add(&mut self, binding: TelemetryBinding) {
+        self.bindings
+            .retain(|b| b.entity != binding.entity || b.target != binding.target);
+        self.bindings.push(binding);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TelemetryBinding> {
+        self.bindings.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+}
+
+/// Where to load bindings from at startup.
+#[derive(Resource, Clone, Debug)]
+pub struct TelemetryBindingSettings {
+    /// JSONL file with one [`TelemetryBinding`] per line.
+    pub file: Option<String>,
+}
+
+impl Default for TelemetryBindingSettings {
+    fn default() -> Self {
+        Self {
+            file: std::env::var("DIMENSIFY_TELEMETRY_BINDINGS").ok(),
+        }
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<TelemetryBindings>()
+        .init_resource::<TelemetryBindingSettings>()
+        .add_systems(
+            Update,
+            (apply_transform_bindings, apply_appearance_bindings)
+                .after(super::refresh_telemetry_state)
+                .after(super::apply_telemetry_to_ecs),
+        );
+
+    #[cfg(feature = "robot")]
+    app.add_systems(
+        Update,
+        apply_joint_bindings.after(super::refresh_telemetry_state),
+    );
+
+    #[cfg(not(target_arch = "wasm32"))]
+    app.add_systems(Startup, load_telemetry_bindings);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_telemetry_bindings(
+    settings: Res<TelemetryBindingSettings>,
+    mut bindings: ResMut<TelemetryBindings>,
+) {
+    let Some(path) = &settings.file else {
+        return;
+    };
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => {
+            bevy::log::error!("Failed to read telemetry bindings {}: {}", path, err);
+            return;
+        }
+    };
+
+    for (line_no, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<TelemetryBinding>(line) {
+            Ok(binding) => bindings.add(binding),
+            Err(err) => {
+                bevy::log::warn!(
+                    "Failed to parse telemetry binding at line {}: {}",
+                    line_no + 1,
+                    err
+                );
+            }
+        }
+    }
+    bevy::log::info!("Loaded {} telemetry bindings", bindings.len());
+}
+
+/// Resolve the bindings whose path has a value at the current playback time.
+fn active_bindings<'a>(
+    bindings: &'a TelemetryBindings,
+    state: &'a TelemetryState,
+) -> impl Iterator<Item = (&'a TelemetryBinding, &'a TelemetryPayload)> {
+    bindings.iter().filter_map(|binding| {
+        state
+            .latest
+            .get(&binding.path)
+            .map(|event| (binding, &event.payload))
+    })
+}
+
+fn should_apply(
+    sync: &TelemetryEcsSync,
+    bindings: &Res<TelemetryBindings>,
+    state: &Res<TelemetryState>,
+) -> bool {
+    sync.enabled && !bindings.is_empty() && (bindings.is_changed() || state.is_changed())
+}
+
+pub(crate) fn apply_transform_bindings(
+    mut commands: Commands,
+    sync: Res<TelemetryEcsSync>,
+    bindings: Res<TelemetryBindings>,
+    state: Res<TelemetryState>,
+    mut query: Query<(Entity, &Name, &mut Transform, Has<DrivenTransform>)>,
+) {
+    if !should_apply(&sync, &bindings, &state) {
+        return;
+    }
+    for (binding, payload) in active_bindings(&bindings, &state) {
+        for (entity, name, mut transform, driven) in &mut query {
+            if name.as_str() != binding.entity {
+                continue;
+            }
+            let applied = match (&binding.target, payload) {
+                (
+                    TelemetryBindingTarget::Transform,
+                    TelemetryPayload::Transform {
+                        translation,
+                        rotation,
+                        scale,
+                    },
+                ) => {
+                    *transform = Transform {
+                        translation: *translation,
+                        rotation: *rotation,
+                        scale: *scale,
+                    };
+                    true
+                }
+                (TelemetryBindingTarget::Translation, TelemetryPayload::Vec3 { value }) => {
+                    transform.translation = *value;
+                    true
+                }
+                (TelemetryBindingTarget::Rotation, TelemetryPayload::Vec4 { value }) => {
+                    transform.rotation = Quat::from_vec4(*value);
+                    true
+                }
+                (TelemetryBindingTarget::Scale, TelemetryPayload::Vec3 { value }) => {
+                    transform.scale = *value;
+                    true
+                }
+                _ => false,
+            };
+            if applied && !driven {
+                commands.entity(entity).insert(DrivenTransform);
+            }
+        }
+    }
+}
+
+fn apply_appearance_bindings(
+    sync: Res<TelemetryEcsSync>,
+    bindings: Res<TelemetryBindings>,
+    state: Res<TelemetryState>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut query: Query<(
+        &Name,
+        Option<&mut Visibility>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        Option<&mut Text2d>,
+        Option<&mut Text>,
+    )>,
+) {
+    if !should_apply(&sync, &bindings, &state) {
+        return;
+    }
+
+    for (binding, payload) in active_bindings(&bindings, &state) {
+        for (name, visibility, material, text_2d, text) in &mut query {
+            if name.as_str() != binding.entity {
+                continue;
+            }
+            match &binding.target {
+                TelemetryBindingTarget::Visibility => {
+                    let (Some(mut visibility), TelemetryPayload::Scalar { value }) =
+                        (visibility, payload)
+                    else {
+                        continue;
+                    };
+                    *visibility = if *value != 0.0 {
+                        Visibility::Inherited
+                    } else {
+                        Visibility::Hidden
+                    };
+                }
+                TelemetryBindingTarget::MaterialColor => {
+                    let color = match payload {
+                        TelemetryPayload::Vec4 { value } => {
+                            Color::srgba(value.x, value.y, value.z, value.w)
+                        }
+                        TelemetryPayload::Vec3 { value } => Color::srgb(value.x, value.y, value.z),
+                        _ => continue,
+                    };
+                    // materials may be shared, in which case every user changes colour
+                    if let Some(material) = material.and_then(|m| materials.get_mut(m.id())) {
+                        material.base_color = color;
+                    }
+                }
+                TelemetryBindingTarget::LabelText => {
+                    let label = label_text(payload);
+                    if let Some(mut text) = text_2d {
+                        text.0 = label.clone();
+                    }
+                    if let Some(mut text) = text {
+                        text.0 = label;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn label_text(payload: &TelemetryPayload) -> String {
+    match payload {
+        TelemetryPayload::Text { value } => value.clone(),
+        TelemetryPayload::Scalar { value } => format!("{:.3}", value),
+        TelemetryPayload::Vec2 { value } => format!("{:.3}", value),
+        TelemetryPayload::Vec3 { value } => format!("{:.3}", value),
+        TelemetryPayload::Vec4 { value } => format!("{:.3}", value),
+        TelemetryPayload::JointState { names, positions } => names
+            .iter()
+            .zip(positions)
+            .map(|(name, position)| format!("{}: {:.3}", name, position))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(feature = "robot")]
+fn apply_joint_bindings(
+    sync: Res<TelemetryEcsSync>,
+    bindings: Res<TelemetryBindings>,
+    state: Res<TelemetryState>,
+    mut robots: Query<(&Name, &mut crate::robot::RemoteRobotState)>,
+) {
+    if !should_apply(&sync, &bindings, &state) {
+        return;
+    }
+
+    for (binding, payload) in active_bindings(&bindings, &state) {
+        let joints: Vec<(String, f32)> = match (&binding.target, payload) {
+            (
+                TelemetryBindingTarget::JointState,
+                TelemetryPayload::JointState { names, positions },
+            ) => names
+                .iter()
+                .cloned()
+                .zip(positions.iter().map(|p| *p as f32))
+                .collect(),
+            (TelemetryBindingTarget::Joint { name }, TelemetryPayload::Scalar { value }) => {
+                vec![(name.clone(), *value as f32)]
+            }
+            _ => continue,
+        };
+        for (name, mut remote) in &mut robots {
+            if name.as_str() == binding.entity {
+                remote.set_joint_positions(joints.iter().cloned());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use dimensify_protocol::{
+        TelemetryBinding, TelemetryBindingTarget, TelemetryEvent, TelemetryPayload, TelemetryTime,
+    };
+
+    use super::{TelemetryBindings, apply_appearance_bindings, apply_transform_bindings};
+    use crate::{
+        scene::DrivenTransform,
+        telemetry::{TelemetryEcsSync, TelemetryState},
+    };
+
+    fn binding(path: &str, target: TelemetryBindingTarget) -> TelemetryBinding {
+        TelemetryBinding {
+            path: path.to_string(),
+            entity: "arm".to_string(),
+            target,
+        }
+    }
+
+    /// An app running `system` with `bindings`, and `values` as the latest telemetry.
+    fn app_with<M>(
+        system: impl IntoScheduleConfigs<bevy::ecs::system::ScheduleSystem, M>,
+        bindings: Vec<TelemetryBinding>,
+        values: Vec<(&str, TelemetryPayload)>,
+    ) -> App {
+        let mut state = TelemetryState::default();
+        for (path, payload) in values {
+            let event = TelemetryEvent {
+                path: path.to_string(),
+                time: TelemetryTime {
+                    timeline: "sim_time".to_string(),
+                    value: 0.0,
+                },
+                payload,
+                metadata: None,
+            };
+            state.latest.insert(path.to_string(), event);
+        }
+        let mut app = App::new();
+        app.insert_resource(TelemetryEcsSync { enabled: true })
+            .insert_resource(state)
+            .init_resource::<TelemetryBindings>()
+            .add_systems(Update, system);
+        app.world_mut()
+            .resource_mut::<TelemetryBindings>()
+            .set(bindings);
+        app
+    }
+
+    #[test]
+    fn transform_bindings_drive_the_named_entity() {
+        let mut app = app_with(
+            apply_transform_bindings,
+            vec![
+                binding("arm/position", TelemetryBindingTarget::Translation),
+                binding("arm/scale", TelemetryBindingTarget::Scale),
+            ],
+            vec![
+                (
+                    "arm/position",
+                    TelemetryPayload::Vec3 {
+                        value: Vec3::new(1.0, 2.0, 3.0),
+                    },
+                ),
+                ("arm/scale", TelemetryPayload::Scalar { value: 2.0 }),
+            ],
+        );
+        let arm = app
+            .world_mut()
+            .spawn((Name::new("arm"), Transform::default()))
+            .id();
+        let other = app
+            .world_mut()
+            .spawn((Name::new("other"), Transform::default()))
+            .id();
+        app.update();
+
+        let transform = app.world().get::<Transform>(arm).unwrap();
+        assert_eq!(transform.translation, Vec3::new(1.0, 2.0, 3.0));
+        // a scalar is not a valid scale and leaves it alone
+        assert_eq!(transform.scale, Vec3::ONE);
+        assert!(app.world().get::<DrivenTransform>(arm).is_some());
+        assert_eq!(
+            *app.world().get::<Transform>(other).unwrap(),
+            Transform::default()
+        );
+        assert!(app.world().get::<DrivenTransform>(other).is_none());
+    }
+
+    #[test]
+    fn appearance_bindings_set_visibility_colour_and_label() {
+        let mut app = app_with(
+            apply_appearance_bindings,
+            vec![
+                binding("arm/visible", TelemetryBindingTarget::Visibility),
+                binding("arm/colour", TelemetryBindingTarget::MaterialColor),
+                binding("arm/load", TelemetryBindingTarget::LabelText),
+            ],
+            vec![
+                ("arm/visible", TelemetryPayload::Scalar { value: 0.0 }),
+                (
+                    "arm/colour",
+                    TelemetryPayload::Vec3 {
+                        value: Vec3::new(1.0, 0.0, 0.0),
+                    },
+                ),
+                ("arm/load", TelemetryPayload::Scalar { value: 0.5 }),
+            ],
+        );
+        app.init_resource::<Assets<StandardMaterial>>();
+        let material = app
+            .world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::default());
+        let arm = app
+            .world_mut()
+            .spawn((
+                Name::new("arm"),
+                Visibility::Visible,
+                MeshMaterial3d(material.clone()),
+                Text2d::new("load"),
+            ))
+            .id();
+        app.update();
+
+        assert_eq!(
+            *app.world().get::<Visibility>(arm).unwrap(),
+            Visibility::Hidden
+        );
+        let materials = app.world().resource::<Assets<StandardMaterial>>();
+        assert_eq!(
+            materials.get(&material).unwrap().base_color,
+            Color::srgb(1.0, 0.0, 0.0)
+        );
+        assert_eq!(app.world().get::<Text2d>(arm).unwrap().0, "0.500");
+    }
+
+    #[cfg(feature = "robot")]
+    #[test]
+    fn joint_bindings_request_joint_positions() {
+        use crate::robot::RemoteRobotState;
+
+        let mut app = app_with(
+            super::apply_joint_bindings,
+            vec![binding(
+                "arm/q1",
+                TelemetryBindingTarget::Joint {
+                    name: "joint1".to_string(),
+                },
+            )],
+            vec![("arm/q1", TelemetryPayload::Scalar { value: 0.25 })],
+        );
+        let arm = app
+            .world_mut()
+            .spawn((Name::new("arm"), RemoteRobotState::default()))
+            .id();
+        app.update();
+
+        let remote = app.world().get::<RemoteRobotState>(arm).unwrap();
+        assert_eq!(remote.joint_position("joint1"), Some(0.25));
+    }
+
+    #[test]
+    fn add_replaces_binding_for_same_target() {
+        let mut bindings = TelemetryBindings::default();
+        bindings.add(binding("a", TelemetryBindingTarget::Visibility));
+        bindings.add(binding("b", TelemetryBindingTarget::LabelText));
+        bindings.add(binding("c", TelemetryBindingTarget::Visibility));
+
+        let paths: Vec<&str> = bindings.iter().map(|b| b.path.as_str()).collect();
+        assert_eq!(paths, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn bindings_parse_from_jsonl() {
+        let line = r#"{"path":"robot/q1","entity":"arm","target":{"Joint":{"name":"joint1"}}}"#;
+        let parsed: TelemetryBinding = serde_json::from_str(line).unwrap();
+        assert_eq!(
+            parsed.target,
+            TelemetryBindingTarget::Joint {
+                name: "joint1".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! Export telemetry from the store to CSV, Parquet and MCAP.
+//!
+//! CSV and Parquet are written as a wide table: one row per distinct timestamp
+//! and one column per scalar component (`<path>.x`, `<path>.<joint>`, ...).
+//! Cells are empty where a path has no sample at that exact time. Payloads
+//! without a scalar form (images, point clouds, ...) are skipped.
+//!
+//! MCAP keeps every event as a JSON message on a `/<path>` topic, for ROS and
+//! Foxglove tooling.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::Context;
+use dimensify_protocol::{TelemetryEvent, TelemetryPayload};
+
+use super::TelemetryStore;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TelemetryExportFormat {
+    Csv,
+    /// Requires the `telemetry_parquet` feature.
+    Parquet,
+    /// Requires the `telemetry_mcap` feature.
+    Mcap,
+}
+
+impl TelemetryExportFormat {
+    pub const ALL: [Self; 3] = [Self::Csv, Self::Parquet, Self::Mcap];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+            Self::Mcap => "mcap",
+        }
+    }
+
+    /// Guess the format from a file extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+    }
+
+    /// Whether this build can write the format.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Self::Csv => true,
+            Self::Parquet => cfg!(feature = "telemetry_parquet"),
+            Self::Mcap => cfg!(feature = "telemetry_mcap"),
+        }
+    }
+}
+
+/// Which telemetry to export.
+#[derive(Clone, Debug)]
+pub struct TelemetryExportSelection {
+    pub timeline: String,
+    /// Paths to export; a path also selects everything below it. Empty selects all.
+    pub paths: Vec<String>,
+    /// Inclusive `(start, end)` time range. `None` exports the whole timeline.
+    pub range: Option<(f64, f64)>,
+}
+
+impl TelemetryExportSelection {
+    pub fn new(timeline: impl Into<String>) -> Self {
+        Self {
+            timeline: timeline.into(),
+            paths: Vec::new(),
+            range: None,
+        }
+    }
+
+    pub fn matches(&self, event: &TelemetryEvent) -> bool {
+        if event.time.timeline != self.timeline {
+            return false;
+        }
+        if let Some((start, end)) = self.range
+            && (event.time.value < start || event.time.value > end)
+        {
+            return false;
+        }
+        self.paths.is_empty()
+            || self.paths.iter().any(|path| {
+                event.path == *path
+                    || event
+                        .path
+                        .strip_prefix(path.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+    }
+}
+
+/// Export the selected telemetry to `output`, returning the number of events written.
+pub fn export_telemetry(
+    store: &TelemetryStore,
+    selection: &TelemetryExportSelection,
+    format: TelemetryExportFormat,
+    output: impl AsRef<Path>,
+) -> anyhow::Result<usize> {
+    let events: Vec<&TelemetryEvent> = store.iter().filter(|e| selection.matches(e)).collect();
+    export_events(&events, &selection.timeline, format, output)
+}
+
+/// Export events already selected from `timeline`, returning the number written.
+pub fn export_events(
+    events: &[&TelemetryEvent],
+    timeline: &str,
+    format: TelemetryExportFormat,
+    output: impl AsRef<Path>,
+) -> anyhow::Result<usize> {
+    let output = output.as_ref();
+    match format {
+        TelemetryExportFormat::Csv => write_csv(&TelemetryTable::build(events), output)?,
+        TelemetryExportFormat::Parquet => {
+            write_parquet(&TelemetryTable::build(events), timeline, output)?
+        }
+        TelemetryExportFormat::Mcap => write_mcap(events, output)?,
+    }
+    Ok(events.len())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ColumnValues {
+    Float(Vec<Option<f64>>),
+    Text(Vec<Option<String>>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct TelemetryColumn {
+    name: String,
+    values: ColumnValues,
+}
+
+/// Wide table with one row per distinct timestamp.
+#[derive(Debug, Default)]
+struct TelemetryTable {
+    times: Vec<f64>,
+    columns: Vec<TelemetryColumn>,
+}
+
+enum Cell {
+    Float(f64),
+    Text(String),
+}
+
+impl TelemetryTable {
+    fn build(events: &[&TelemetryEvent]) -> Self {
+        let mut times: Vec<f64> = events.iter().map(|e| e.time.value).collect();
+        times.sort_by(f64::total_cmp);
+        times.dedup();
+
+        let rows = times.len();
+        let mut columns: Vec<TelemetryColumn> = Vec::new();
+        let mut by_name: HashMap<String, usize> = HashMap::new();
+        for event in events {
+            let Ok(row) = times.binary_search_by(|t| t.total_cmp(&event.time.value)) else {
+                continue;
+            };
+            for (suffix, cell) in cells(&event.payload) {
+                let name = match suffix {
+                    Some(suffix) => format!("{}.{}", event.path, suffix),
+                    None => event.path.clone(),
+                };
+                let index = *by_name.entry(name.clone()).or_insert_with(|| {
+                    let values = match cell {
+                        Cell::Float(_) => ColumnValues::Float(vec![None; rows]),
+                        Cell::Text(_) => ColumnValues::Text(vec![None; rows]),
+                    };
+                    columns.push(TelemetryColumn { name, values });
+                    columns.len() - 1
+                });
+                // a path that changes payload kind keeps the kind it started with
+                match (&mut columns[index].values, cell) {
+                    (ColumnValues::Float(values), Cell::Float(value)) => values[row] = Some(value),
+                    (ColumnValues::Text(values), Cell::Text(value)) => values[row] = Some(value),
+                    _ => {}
+                }
+            }
+        }
+        columns.sort_by(|a, b| a.name.cmp(&b.name));
+        Self { times, columns }
+    }
+}
+
+fn components(values: &[f32], prefix: Option<&str>) -> Vec<(Option<String>, Cell)> {
+    const XYZW: [&str; 4] = ["x", "y", "z", "w"];
+    XYZW.iter()
+        .zip(values)
+        .map(|(name, value)| {
+            let suffix = match prefix {
+                Some(prefix) => format!("{}.{}", prefix, name),
+                None => name.to_string(),
+            };
+            (Some(suffix), Cell::Float(*value as f64))
+        })
+        .collect()
+}
+
+/// Scalar components of a payload, as `(column suffix, value)`.
+fn cells(payload: &TelemetryPayload) -> Vec<(Option<String>, Cell)> {
+    match payload {
+        TelemetryPayload::Scalar { value } => vec![(None, Cell::Float(*value))],
+        TelemetryPayload::Vec2 { value } => components(&value.to_array(), None),
+        TelemetryPayload::Vec3 { value } => components(&value.to_array(), None),
+        TelemetryPayload::Vec4 { value } => components(&value.to_array(), None),
+        TelemetryPayload::Text { value } => vec![(None, Cell::Text(value.clone()))],
+        TelemetryPayload::Transform {
+            translation,
+            rotation,
+            scale,
+        } => {
+            let mut cells = components(&translation.to_array(), Some("translation"));
+            cells.extend(components(&rotation.to_array(), Some("rotation")));
+            cells.extend(components(&scale.to_array(), Some("scale")));
+            cells
+        }
+        TelemetryPayload::JointState { names, positions } => names
+            .iter()
+            .zip(positions)
+            .map(|(name, position)| (Some(name.clone()), Cell::Float(*position)))
+            .collect(),
+        TelemetryPayload::Blob { .. }
+        | TelemetryPayload::Points3d { .. }
+        | TelemetryPayload::LineStrip3d { .. }
+        | TelemetryPayload::Image { .. } => Vec::new(),
+    }
+}
+
+fn write_csv(table: &TelemetryTable, output: &Path) -> anyhow::Result<()> {
+    let file = File::create(output).with_context(|| format!("failed to create {:?}", output))?;
+    let mut writer = BufWriter::new(file);
+
+    let header: Vec<String> = std::iter::once("time".to_string())
+        .chain(table.columns.iter().map(|c| csv_field(&c.name)))
+        .collect();
+    writeln!(writer, "{}", header.join(","))?;
+
+    for (row, time) in table.times.iter().enumerate() {
+        let mut fields = vec![time.to_string()];
+        for column in &table.columns {
+            fields.push(
+                match &column.values {
+                    ColumnValues::Float(values) => values[row].map(|v| v.to_string()),
+                    ColumnValues::Text(values) => values[row].as_deref().map(csv_field),
+                }
+                .unwrap_or_default(),
+            );
+        }
+        writeln!(writer, "{}", fields.join(","))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(feature = "telemetry_parquet")]
+fn write_parquet(table: &TelemetryTable, timeline: &str, output: &Path) -> anyhow::Result<()> {
+    use std::sync::Arc;
+
+    use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+
+    let mut fields = vec![Field::new("time", DataType::Float64, false)];
+    let mut arrays: Vec<ArrayRef> = vec![Arc::new(Float64Array::from(table.times.clone()))];
+    for column in &table.columns {
+        match &column.values {
+            ColumnValues::Float(values) => {
+                fields.push(Field::new(&column.name, DataType::Float64, true));
+                arrays.push(Arc::new(Float64Array::from(values.clone())));
+            }
+            ColumnValues::Text(values) => {
+                fields.push(Field::new(&column.name, DataType::Utf8, true));
+                arrays.push(Arc::new(StringArray::from(values.clone())));
+            }
+        }
+    }
+    let schema = Arc::new(Schema::new(fields).with_metadata(HashMap::from([(
+        "dimensify.timeline".to_string(),
+        timeline.to_string(),
+    )])));
+    let batch = RecordBatch::try_new(schema.clone(), arrays)?;
+
+    let file = File::create(output).with_context(|| format!("failed to create {:?}", output))?;
+    let mut writer = ArrowWriter::try_new(file, schema, None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(not(feature = "telemetry_parquet"))]
+fn write_parquet(_table: &TelemetryTable, _timeline: &str, _output: &Path) -> anyhow::Result<()> {
+    anyhow::bail!("Parquet export requires the `telemetry_parquet` feature")
+}
+
+/// Minimal JSON schema attached to the MCAP channels.
+#[cfg(feature = "telemetry_mcap")]
+const MCAP_EVENT_SCHEMA: &str = r#"{"type":"object","properties":{"path":{"type":"string"},"time":{"type":"object"},"payload":{"type":"object"},"metadata":{}}}"#;
+
+#[cfg(feature = "telemetry_mcap")]
+fn write_mcap(events: &[&TelemetryEvent], output: &Path) -> anyhow::Result<()> {
+    use std::collections::BTreeMap;
+
+    let file = File::create(output).with_context(|| format!("failed to create {:?}", output))?;
+    let mut writer = mcap::Writer::new(BufWriter::new(file))?;
+    let schema_id = writer.add_schema(
+        "dimensify.TelemetryEvent",
+        "jsonschema",
+        MCAP_EVENT_SCHEMA.as_bytes(),
+    )?;
+
+    let mut channels: HashMap<&str, u16> = HashMap::new();
+    for (sequence, event) in events.iter().enumerate() {
+        let channel_id = match channels.get(event.path.as_str()) {
+            Some(id) => *id,
+            None => {
+                let topic = format!("/{}", event.path.trim_start_matches('/'));
+                let id = writer.add_channel(schema_id, &topic, "json", &BTreeMap::new())?;
+                channels.insert(event.path.as_str(), id);
+                id
+            }
+        };
+        // MCAP timestamps are nanoseconds; sequence timelines are written as if in seconds
+        let log_time = (event.time.value.max(0.0) * 1e9) as u64;
+        let data = serde_json::to_vec(event)?;
+        writer.write_to_known_channel(
+            &mcap::records::MessageHeader {
+                channel_id,
+                sequence: sequence as u32,
+                log_time,
+                publish_time: log_time,
+            },
+            &data,
+        )?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(not(feature = "telemetry_mcap"))]
+fn write_mcap(_events: &[&TelemetryEvent], _output: &Path) -> anyhow::Result<()> {
+    anyhow::bail!("MCAP export requires the `telemetry_mcap` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColumnValues, TelemetryExportSelection, TelemetryTable, csv_field};
+    use dimensify_protocol::{TelemetryEvent, TelemetryPayload, TelemetryTime};
+
+    fn event(path: &str, time: f64, payload: TelemetryPayload) -> TelemetryEvent {
+        TelemetryEvent {
+            path: path.to_string(),
+            time: TelemetryTime {
+                timeline: "sim_time".to_string(),
+                value: time,
+            },
+            payload,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn table_has_one_column_per_component() {
+        let a = event("a", 0.0, TelemetryPayload::Scalar { value: 1.0 });
+        let b = event(
+            "b",
+            1.0,
+            TelemetryPayload::Vec2 {
+                value: [2.0, 3.0].into(),
+            },
+        );
+        let table = TelemetryTable::build(&[&a, &b]);
+
+        assert_eq!(table.times, vec![0.0, 1.0]);
+        let names: Vec<&str> = table.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b.x", "b.y"]);
+        assert_eq!(
+            table.columns[0].values,
+            ColumnValues::Float(vec![Some(1.0), None])
+        );
+    }
+
+    #[test]
+    fn selection_matches_sub_paths_and_range() {
+        let mut selection = TelemetryExportSelection::new("sim_time");
+        selection.paths = vec!["robot".to_string()];
+        selection.range = Some((0.0, 1.0));
+
+        let scalar = TelemetryPayload::Scalar { value: 0.0 };
+        assert!(selection.matches(&event("robot/q1", 0.5, scalar.clone())));
+        assert!(!selection.matches(&event("robots/q1", 0.5, scalar.clone())));
+        assert!(!selection.matches(&event("robot/q1", 2.0, scalar)));
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+}
@@ -2,6 +2,16 @@ use std::collections::HashMap;
 
 use bevy::prelude::*;
 use dimensify_protocol::{TelemetryEvent, TelemetryPayload};
+use serde::{Deserialize, Serialize};
+
+use crate::{scene::DrivenTransform, tasks::Tasks};
+
+pub mod bindings;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod export;
+pub mod render;
+#[cfg(all(feature = "telemetry_rrd", not(target_arch = "wasm32")))]
+pub mod rrd_reader;
 
 /// Telemetry storage is separate from ECS. ECS renders the current time window.
 /// Intended for Rerun/Arrow-backed telemetry sources.
@@ -47,6 +57,11 @@ impl TelemetryStore {
         self.events.len()
     }
 
+    /// Number of events kept before the oldest are dropped.
+    pub fn capacity(&self) -> usize {
+        self.max_events
+    }
+
     pub fn events(&self) -> &[TelemetryEvent] {
         &self.events
     }
@@ -91,6 +106,41 @@ impl TelemetryStore {
         }
     }
 
+    /// List known timeline names, sorted alphabetically.
+    pub fn timelines(&self) -> Vec<String> {
+        let mut timelines: Vec<String> = self.index.timelines.keys().cloned().collect();
+        timelines.sort();
+        timelines
+    }
+
+    /// Return the closest event time strictly after (or before) `time` on a timeline.
+    ///
+    /// Used to step playback one event at a time.
+    pub fn step_time(&self, timeline: &str, time: f64, forward: bool) -> Option<f64> {
+        let paths = self.index.timelines.get(timeline)?;
+        let mut best: Option<f64> = None;
+        for indices in paths.values() {
+            let pos = indices.partition_point(|idx| {
+                let value = self.events[*idx].time.value;
+                if forward { value <= time } else { value < time }
+            });
+            let candidate = if forward {
+                indices.get(pos)
+            } else {
+                pos.checked_sub(1).and_then(|i| indices.get(i))
+            };
+            let Some(candidate) = candidate.map(|idx| self.events[*idx].time.value) else {
+                continue;
+            };
+            best = Some(match best {
+                Some(v) if forward => v.min(candidate),
+                Some(v) => v.max(candidate),
+                None => candidate,
+            });
+        }
+        best
+    }
+
     /// List known paths for a timeline.
     pub fn paths(&self, timeline: &str) -> Vec<String> {
         self.index
@@ -149,6 +199,28 @@ pub struct TelemetryPlayback {
     pub timeline: String,
     pub time: f64,
     pub mode: TelemetryPlaybackMode,
+    /// Whether `Fixed` playback advances on its own.
+    pub playing: bool,
+    /// Playback speed multiplier applied while `playing`.
+    pub speed: f64,
+    /// Optional `(start, end)` range that playback wraps around in.
+    pub loop_range: Option<(f64, f64)>,
+}
+
+impl TelemetryPlayback {
+    /// Jump to a fixed time, pausing live following.
+    pub fn seek(&mut self, time: f64) {
+        self.time = time;
+        self.mode = TelemetryPlaybackMode::Fixed;
+    }
+
+    /// Step to the previous or next event on the current timeline.
+    pub fn step(&mut self, store: &TelemetryStore, forward: bool) {
+        if let Some(time) = store.step_time(&self.timeline, self.time, forward) {
+            self.playing = false;
+            self.seek(time);
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -173,10 +245,17 @@ impl Default for TelemetryPlayback {
             .ok()
             .and_then(|value| value.parse::<f64>().ok())
             .unwrap_or(0.0);
+        let speed = std::env::var("DIMENSIFY_TELEMETRY_SPEED")
+            .ok()
+            .and_then(|value| value.parse::<f64>().ok())
+            .unwrap_or(1.0);
         Self {
             timeline,
             time,
             mode,
+            playing: false,
+            speed,
+            loop_range: None,
         }
     }
 }
@@ -231,10 +310,16 @@ struct TelemetryEcsSyncState {
     last_timeline: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum TelemetrySourceKind {
     Local,
-    FileReplay { path: String },
+    FileReplay {
+        path: String,
+    },
+    /// Rerun recording, decoded with the `telemetry_rrd` feature.
+    RrdReplay {
+        path: String,
+    },
 }
 
 #[derive(Resource, Clone, Debug)]
@@ -251,7 +336,17 @@ impl Default for TelemetrySettings {
         {
             "file" => std::env::var("DIMENSIFY_TELEMETRY_FILE")
                 .ok()
-                .map(|path| TelemetrySourceKind::FileReplay { path })
+                .map(|path| {
+                    if path.ends_with(".rrd") {
+                        TelemetrySourceKind::RrdReplay { path }
+                    } else {
+                        TelemetrySourceKind::FileReplay { path }
+                    }
+                })
+                .unwrap_or(TelemetrySourceKind::Local),
+            "rrd" => std::env::var("DIMENSIFY_TELEMETRY_FILE")
+                .ok()
+                .map(|path| TelemetrySourceKind::RrdReplay { path })
                 .unwrap_or(TelemetrySourceKind::Local),
             _ => TelemetrySourceKind::Local,
         };
@@ -275,44 +370,133 @@ pub fn plugin(app: &mut App) {
         .add_systems(
             Update,
             apply_telemetry_to_ecs.after(refresh_telemetry_state),
-        );
+        )
+        .add_plugins((bindings::plugin, render::plugin));
+
+    #[cfg(feature = "robot")]
+    app.add_systems(
+        Update,
+        apply_telemetry_joint_states.after(refresh_telemetry_state),
+    );
 
     #[cfg(not(target_arch = "wasm32"))]
     app.add_systems(Startup, load_file_replay);
 
     #[cfg(feature = "telemetry_rrd")]
     {
+        use dimensify_ui::command_palette::{PaletteAction, RegisterPaletteAction};
+
         app.init_resource::<TelemetryRrdRecorder>()
-            .add_systems(Update, record_telemetry_to_rrd);
+            .add_systems(Update, record_telemetry_to_rrd)
+            .register_palette_action(PaletteAction::new("Start/stop RRD recording", |world| {
+                let mut recording = world.resource_mut::<TelemetryRecordingState>();
+                recording.enabled = !recording.enabled;
+            }));
     }
+
+    #[cfg(all(feature = "telemetry_rrd", not(target_arch = "wasm32")))]
+    app.add_systems(Startup, load_rrd_replay);
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn load_file_replay(settings: Res<TelemetrySettings>, mut store: ResMut<TelemetryStore>) {
+fn load_file_replay(
+    settings: Res<TelemetrySettings>,
+    mut store: ResMut<TelemetryStore>,
+    mut tasks: ResMut<Tasks>,
+) {
+    if let TelemetrySourceKind::RrdReplay { path } = &settings.source
+        && !cfg!(feature = "telemetry_rrd")
+    {
+        bevy::log::error!("Reading {} requires the `telemetry_rrd` feature", path);
+        return;
+    }
     let TelemetrySourceKind::FileReplay { path } = &settings.source else {
         return;
     };
-    let content = match std::fs::read_to_string(path) {
-        Ok(content) => content,
+    store.max_events = settings.max_events;
+    let task = tasks.start("Telemetry import", path.clone());
+    match import_jsonl(&mut store, path) {
+        Ok(count) => {
+            bevy::log::info!("Loaded {} telemetry events", count);
+            tasks.set_detail(task, format!("{} events", count));
+            tasks.finish(task);
+        }
         Err(err) => {
-            bevy::log::error!("Failed to read telemetry file {}: {}", path, err);
-            return;
+            bevy::log::error!("Failed to read telemetry file {}: {:#}", path, err);
+            tasks.fail(task, format!("{:#}", err));
         }
-    };
+    }
+}
 
-    store.max_events = settings.max_events;
+/// Import a JSONL telemetry file into the store. Malformed lines are skipped with a
+/// logged warning.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn import_jsonl(store: &mut TelemetryStore, path: &str) -> anyhow::Result<usize> {
+    import_jsonl_with(store, path, |line_no, err| {
+        bevy::log::warn!("Failed to parse telemetry at line {}: {}", line_no, err);
+    })
+}
+
+/// Import a JSONL telemetry file into the store, passing the 1-based number and parse
+/// error of every malformed line to `skipped`, e.g. for tools that do not log.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn import_jsonl_with(
+    store: &mut TelemetryStore,
+    path: &str,
+    mut skipped: impl FnMut(usize, serde_json::Error),
+) -> anyhow::Result<usize> {
+    let content = std::fs::read_to_string(path)?;
+    let mut count = 0;
     for (line_no, line) in content.lines().enumerate() {
         if line.trim().is_empty() {
             continue;
         }
         match serde_json::from_str::<TelemetryEvent>(line) {
-            Ok(event) => store.push(event),
-            Err(err) => {
-                bevy::log::warn!("Failed to parse telemetry at line {}: {}", line_no + 1, err);
+            Ok(event) => {
+                store.push(event);
+                count += 1;
             }
+            Err(err) => skipped(line_no + 1, err),
+        }
+    }
+    Ok(count)
+}
+
+/// Import a Rerun recording into the store, keeping all of its timelines.
+#[cfg(all(feature = "telemetry_rrd", not(target_arch = "wasm32")))]
+pub fn import_rrd(store: &mut TelemetryStore, path: &str) -> anyhow::Result<usize> {
+    let mut events = rrd_reader::read_rrd(path)?;
+    // Push in time order so that eviction drops the oldest samples first.
+    events.sort_by(|a, b| a.time.value.total_cmp(&b.time.value));
+    let count = events.len();
+    for event in events {
+        store.push(event);
+    }
+    Ok(count)
+}
+
+#[cfg(all(feature = "telemetry_rrd", not(target_arch = "wasm32")))]
+fn load_rrd_replay(
+    settings: Res<TelemetrySettings>,
+    mut store: ResMut<TelemetryStore>,
+    mut tasks: ResMut<Tasks>,
+) {
+    let TelemetrySourceKind::RrdReplay { path } = &settings.source else {
+        return;
+    };
+    store.max_events = settings.max_events;
+    let task = tasks.start("Telemetry import", path.clone());
+    match import_rrd(&mut store, path) {
+        Ok(count) => {
+            bevy::log::info!("Loaded {} telemetry events from {}", count, path);
+            tasks.set_detail(task, format!("{} events", count));
+            tasks.finish(task);
+        }
+        Err(err) => {
+            bevy::log::error!("Failed to read rrd file {}: {:#}", path, err);
+            tasks.fail(task, format!("{:#}", err));
         }
     }
-    bevy::log::info!("Loaded {} telemetry events", store.events.len());
 }
 
 /// Telemetry source for live streams or replay files (e.g., Rerun/rrd).
@@ -329,10 +513,46 @@ impl TelemetrySource for EmptyTelemetrySource {
     }
 }
 
-fn update_playback_time(time: Res<Time>, mut playback: ResMut<TelemetryPlayback>) {
-    if playback.mode == TelemetryPlaybackMode::Live {
-        playback.time = time.elapsed_secs_f64();
+fn update_playback_time(
+    time: Res<Time>,
+    store: Res<TelemetryStore>,
+    mut playback: ResMut<TelemetryPlayback>,
+) {
+    match playback.mode {
+        TelemetryPlaybackMode::Live => {
+            playback.time = time.elapsed_secs_f64();
+        }
+        TelemetryPlaybackMode::Fixed => {
+            if !playback.playing {
+                return;
+            }
+            let Some((min, max)) = playback
+                .loop_range
+                .or_else(|| store.timeline_bounds(&playback.timeline))
+            else {
+                return;
+            };
+            let next = playback.time + time.delta_secs_f64() * playback.speed;
+            playback.time = if playback.loop_range.is_some() {
+                wrap_time(next, min, max)
+            } else if next >= max || next <= min {
+                // reached the end of the recording; stop instead of running off the timeline
+                playback.playing = false;
+                next.clamp(min, max)
+            } else {
+                next
+            };
+        }
+    }
+}
+
+/// Wrap `time` into `[min, max]`, handling negative playback speeds.
+fn wrap_time(time: f64, min: f64, max: f64) -> f64 {
+    let span = max - min;
+    if span <= 0.0 {
+        return min;
     }
+    min + (time - min).rem_euclid(span)
 }
 
 fn refresh_telemetry_state(
@@ -360,12 +580,13 @@ fn refresh_telemetry_state(
     state.last_revision = store.revision();
 }
 
-fn apply_telemetry_to_ecs(
+pub(crate) fn apply_telemetry_to_ecs(
+    mut commands: Commands,
     sync: Res<TelemetryEcsSync>,
     store: Res<TelemetryStore>,
     state: Res<TelemetryState>,
     mut sync_state: ResMut<TelemetryEcsSyncState>,
-    mut query: Query<(Entity, &Name, &mut Transform)>,
+    mut query: Query<(Entity, &Name, &mut Transform, Has<DrivenTransform>)>,
 ) {
     if !sync.enabled {
         return;
@@ -379,7 +600,7 @@ fn apply_telemetry_to_ecs(
     }
 
     let mut by_name = HashMap::new();
-    for (entity, name, _) in &query {
+    for (entity, name, ..) in &query {
         by_name.insert(name.as_str().to_string(), entity);
     }
 
@@ -388,29 +609,41 @@ fn apply_telemetry_to_ecs(
             let Some(entity) = by_name.get(&entity_name) else {
                 continue;
             };
-            let Ok((_, _, mut transform)) = query.get_mut(*entity) else {
+            let Ok((_, _, mut transform, driven)) = query.get_mut(*entity) else {
                 continue;
             };
-            match field.as_str() {
-                "translation" | "position" => {
-                    if let TelemetryPayload::Vec3 { value } = &event.payload {
-                        let v: Vec3 = value.clone().into();
-                        transform.translation = v;
-                    }
+            let applied = match (field.as_deref().unwrap_or_default(), &event.payload) {
+                (
+                    "",
+                    TelemetryPayload::Transform {
+                        translation,
+                        rotation,
+                        scale,
+                    },
+                ) => {
+                    *transform = Transform {
+                        translation: *translation,
+                        rotation: *rotation,
+                        scale: *scale,
+                    };
+                    true
                 }
-                "rotation" => {
-                    if let TelemetryPayload::Vec4 { value } = &event.payload {
-                        let v: [f32; 4] = value.clone().into();
-                        transform.rotation = Quat::from_xyzw(v[0], v[1], v[2], v[3]);
-                    }
+                ("translation" | "position", TelemetryPayload::Vec3 { value }) => {
+                    transform.translation = *value;
+                    true
                 }
-                "scale" => {
-                    if let TelemetryPayload::Vec3 { value } = &event.payload {
-                        let v: Vec3 = value.clone().into();
-                        transform.scale = v;
-                    }
+                ("rotation", TelemetryPayload::Vec4 { value }) => {
+                    transform.rotation = Quat::from_vec4(*value);
+                    true
                 }
-                _ => {}
+                ("scale", TelemetryPayload::Vec3 { value }) => {
+                    transform.scale = *value;
+                    true
+                }
+                _ => false,
+            };
+            if applied && !driven {
+                commands.entity(*entity).insert(DrivenTransform);
             }
         }
     }
@@ -420,17 +653,51 @@ fn apply_telemetry_to_ecs(
     sync_state.last_timeline = state.timeline.clone();
 }
 
-fn parse_entity_transform_path(path: &str) -> Option<(String, String)> {
-    let mut parts = path.split('/');
-    if parts.next()? != "entity" {
-        return None;
-    }
-    let entity_name = parts.next()?.to_string();
+/// Split `entity/<name>/transform[/<field>]` into the entity name and optional field.
+fn parse_entity_transform_path(path: &str) -> Option<(String, Option<String>)> {
+    let (entity_name, rest) = parse_entity_path(path)?;
+    let mut parts = rest.split('/');
     if parts.next()? != "transform" {
         return None;
     }
-    let field = parts.next()?.to_string();
-    Some((entity_name, field))
+    let field = parts.next().map(str::to_string);
+    Some((entity_name.to_string(), field))
+}
+
+/// Split `entity/<name>/<rest>` into the entity name and the remaining path.
+pub(crate) fn parse_entity_path(path: &str) -> Option<(&str, &str)> {
+    let rest = path.strip_prefix("entity/")?;
+    rest.split_once('/')
+}
+
+/// Drive robot joints from `entity/<robot name>/joints` joint-state telemetry.
+#[cfg(feature = "robot")]
+fn apply_telemetry_joint_states(
+    sync: Res<TelemetryEcsSync>,
+    state: Res<TelemetryState>,
+    mut robots: Query<(&Name, &mut crate::robot::RemoteRobotState)>,
+) {
+    if !sync.enabled || !state.is_changed() {
+        return;
+    }
+    for (path, event) in &state.latest {
+        let TelemetryPayload::JointState { names, positions } = &event.payload else {
+            continue;
+        };
+        let Some((robot_name, "joints")) = parse_entity_path(path) else {
+            continue;
+        };
+        for (name, mut remote) in &mut robots {
+            if name.as_str() == robot_name {
+                remote.set_joint_positions(
+                    names
+                        .iter()
+                        .cloned()
+                        .zip(positions.iter().map(|p| *p as f32)),
+                );
+            }
+        }
+    }
 }
 
 #[cfg(feature = "telemetry_rrd")]
@@ -481,6 +748,8 @@ fn record_telemetry_to_rrd(
 
 #[cfg(feature = "telemetry_rrd")]
 fn log_event_to_rerun(rec: &rerun::RecordingStream, event: &TelemetryEvent) {
+    use dimensify_protocol::TelemetryImageFormat;
+
     rec.set_duration_secs(event.time.timeline.clone(), event.time.value);
     let path = event.path.as_str();
 
@@ -489,15 +758,15 @@ fn log_event_to_rerun(rec: &rerun::RecordingStream, event: &TelemetryEvent) {
             let _ = rec.log(path, &rerun::Scalars::single(*value));
         }
         TelemetryPayload::Vec2 { value } => {
-            let v = value.0;
+            let v = value.to_array();
             let _ = rec.log(path, &rerun::Points2D::new([(v[0], v[1])]));
         }
         TelemetryPayload::Vec3 { value } => {
-            let v = value.0;
+            let v = value.to_array();
             let _ = rec.log(path, &rerun::Points3D::new([(v[0], v[1], v[2])]));
         }
         TelemetryPayload::Vec4 { value } => {
-            let v = value.0;
+            let v = value.to_array();
             let _ = rec.log(path, &rerun::Scalars::new([v[0], v[1], v[2], v[3]]));
         }
         TelemetryPayload::Text { value } => {
@@ -509,5 +778,200 @@ fn log_event_to_rerun(rec: &rerun::RecordingStream, event: &TelemetryEvent) {
                 event.path
             );
         }
+        TelemetryPayload::Transform {
+            translation,
+            rotation,
+            scale,
+        } => {
+            let _ = rec.log(
+                path,
+                &rerun::Transform3D::from_translation_rotation_scale(
+                    translation.to_array(),
+                    rerun::Quaternion::from_xyzw(rotation.to_array()),
+                    scale.to_array(),
+                ),
+            );
+        }
+        TelemetryPayload::JointState { names, positions } => {
+            for (name, position) in names.iter().zip(positions) {
+                let _ = rec.log(
+                    format!("{}/{}", path, name),
+                    &rerun::Scalars::single(*position),
+                );
+            }
+        }
+        TelemetryPayload::Points3d {
+            positions,
+            colors,
+            radius,
+        } => {
+            let mut points = rerun::Points3D::new(positions.iter().map(|p| p.to_array()));
+            if !colors.is_empty() {
+                points = points.with_colors(
+                    colors
+                        .iter()
+                        .map(|[r, g, b, a]| rerun::Color::from_unmultiplied_rgba(*r, *g, *b, *a)),
+                );
+            }
+            if let Some(radius) = radius {
+                points = points.with_radii([*radius]);
+            }
+            let _ = rec.log(path, &points);
+        }
+        TelemetryPayload::LineStrip3d { points, color } => {
+            let mut strips =
+                rerun::LineStrips3D::new([points.iter().map(|p| p.to_array()).collect::<Vec<_>>()]);
+            if let Some([r, g, b, a]) = color {
+                strips = strips.with_colors([rerun::Color::from_unmultiplied_rgba(*r, *g, *b, *a)]);
+            }
+            let _ = rec.log(path, &strips);
+        }
+        TelemetryPayload::Image {
+            width,
+            height,
+            format,
+            data,
+        } => {
+            let resolution = [*width, *height];
+            let _ = match format {
+                TelemetryImageFormat::Rgb8 => {
+                    rec.log(path, &rerun::Image::from_rgb24(data.clone(), resolution))
+                }
+                TelemetryImageFormat::Rgba8 => {
+                    rec.log(path, &rerun::Image::from_rgba32(data.clone(), resolution))
+                }
+                TelemetryImageFormat::DepthF32 => rec.log(
+                    path,
+                    &rerun::DepthImage::new(
+                        data.clone(),
+                        rerun::ImageFormat::depth(resolution, rerun::ChannelDatatype::F32),
+                    )
+                    .with_meter(1.0),
+                ),
+                TelemetryImageFormat::DepthU16 => rec.log(
+                    path,
+                    &rerun::DepthImage::new(
+                        data.clone(),
+                        rerun::ImageFormat::depth(resolution, rerun::ChannelDatatype::U16),
+                    )
+                    .with_meter(1000.0),
+                ),
+                TelemetryImageFormat::Png | TelemetryImageFormat::Jpeg => {
+                    rec.log(path, &rerun::EncodedImage::from_file_contents(data.clone()))
+                }
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use dimensify_protocol::{TelemetryEvent, TelemetryPayload, TelemetryTime};
+
+    use super::{
+        TelemetryEcsSync, TelemetryEcsSyncState, TelemetryState, TelemetryStore,
+        apply_telemetry_to_ecs, import_jsonl_with, wrap_time,
+    };
+    use crate::scene::DrivenTransform;
+
+    fn scalar(path: &str, timeline: &str, time: f64) -> TelemetryEvent {
+        TelemetryEvent {
+            path: path.to_string(),
+            time: TelemetryTime {
+                timeline: timeline.to_string(),
+                value: time,
+            },
+            payload: TelemetryPayload::Scalar { value: time },
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn only_applied_transform_telemetry_drives_entities() {
+        let mut state = TelemetryState {
+            timeline: "sim_time".to_string(),
+            ..default()
+        };
+        for (path, payload) in [
+            (
+                "entity/arm/transform/translation",
+                TelemetryPayload::Vec3 { value: Vec3::X },
+            ),
+            // a scalar is not a scale, so the hand keeps its transform
+            (
+                "entity/hand/transform/scale",
+                TelemetryPayload::Scalar { value: 2.0 },
+            ),
+        ] {
+            let mut event = scalar(path, "sim_time", 0.0);
+            event.payload = payload;
+            state.latest.insert(path.to_string(), event);
+        }
+        let mut app = App::new();
+        app.insert_resource(TelemetryEcsSync { enabled: true })
+            .insert_resource(state)
+            .init_resource::<TelemetryStore>()
+            .init_resource::<TelemetryEcsSyncState>()
+            .add_systems(Update, apply_telemetry_to_ecs);
+        let arm = app
+            .world_mut()
+            .spawn((Name::new("arm"), Transform::default()))
+            .id();
+        let hand = app
+            .world_mut()
+            .spawn((Name::new("hand"), Transform::default()))
+            .id();
+        app.update();
+
+        assert_eq!(
+            app.world().get::<Transform>(arm).unwrap().translation,
+            Vec3::X
+        );
+        assert!(app.world().get::<DrivenTransform>(arm).is_some());
+        assert!(app.world().get::<DrivenTransform>(hand).is_none());
+    }
+
+    #[test]
+    fn step_time_visits_events_across_paths() {
+        let mut store = TelemetryStore::new(100);
+        store.push(scalar("a", "sim_time", 0.0));
+        store.push(scalar("b", "sim_time", 0.5));
+        store.push(scalar("a", "sim_time", 1.0));
+        store.push(scalar("a", "frame", 3.0));
+
+        assert_eq!(store.step_time("sim_time", 0.0, true), Some(0.5));
+        assert_eq!(store.step_time("sim_time", 0.7, true), Some(1.0));
+        assert_eq!(store.step_time("sim_time", 1.0, true), None);
+        assert_eq!(store.step_time("sim_time", 1.0, false), Some(0.5));
+        assert_eq!(store.step_time("sim_time", 0.0, false), None);
+        assert_eq!(store.timelines(), vec!["frame", "sim_time"]);
+    }
+
+    #[test]
+    fn wrap_time_stays_in_range() {
+        assert_eq!(wrap_time(2.5, 1.0, 2.0), 1.5);
+        assert_eq!(wrap_time(0.5, 1.0, 2.0), 1.5);
+        assert_eq!(wrap_time(3.0, 2.0, 2.0), 2.0);
+    }
+
+    #[test]
+    fn malformed_jsonl_lines_are_reported() {
+        let path =
+            std::env::temp_dir().join(format!("dimensify_telemetry_{}.jsonl", std::process::id()));
+        let line = serde_json::to_string(&scalar("a", "sim", 1.0)).unwrap();
+        std::fs::write(&path, format!("{}\n\nnot json\n{}\n", line, line)).unwrap();
+
+        let mut store = TelemetryStore::new(10);
+        let mut skipped = Vec::new();
+        let count = import_jsonl_with(&mut store, path.to_str().unwrap(), |line_no, _| {
+            skipped.push(line_no)
+        })
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(store.len(), 2);
+        assert_eq!(skipped, [3]);
     }
 }
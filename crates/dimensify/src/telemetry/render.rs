@@ -0,0 +1,414 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::{CompressedImageFormats, ImageSampler, ImageType},
+    mesh::{Indices, PrimitiveTopology},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use dimensify_protocol::{TelemetryImageFormat, TelemetryPayload};
+
+use super::TelemetryState;
+
+/// Controls how telemetry geometry is drawn in the viewport.
+#[derive(Resource, Clone, Debug)]
+pub struct TelemetryRenderSettings {
+    /// Draw point clouds as meshes, and line strips and unbound transforms with gizmos.
+    pub draw_gizmos: bool,
+    /// Radius used for points that do not carry one.
+    pub default_point_radius: f32,
+    /// Axis length used for transform gizmos.
+    pub axes_length: f32,
+}
+
+impl Default for TelemetryRenderSettings {
+    fn default() -> Self {
+        Self {
+            draw_gizmos: true,
+            default_point_radius: 0.01,
+            axes_length: 0.2,
+        }
+    }
+}
+
+/// Latest image per telemetry path, converted into Bevy image assets.
+#[derive(Resource, Default)]
+pub struct TelemetryImages {
+    /// `(image handle, time of the telemetry event it was built from)`.
+    pub images: HashMap<String, (Handle<Image>, f64)>,
+}
+
+/// Entity drawing the latest point cloud of a telemetry path as one mesh.
+#[derive(Component, Debug)]
+pub struct TelemetryPointCloud {
+    pub path: String,
+    /// Time of the telemetry event the mesh was built from.
+    pub time: f64,
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<TelemetryRenderSettings>()
+        .init_resource::<TelemetryImages>()
+        .add_systems(
+            Update,
+            (
+                draw_telemetry_gizmos,
+                update_telemetry_point_clouds,
+                update_telemetry_images,
+            )
+                .after(super::refresh_telemetry_state),
+        );
+}
+
+fn draw_telemetry_gizmos(
+    settings: Res<TelemetryRenderSettings>,
+    state: Res<TelemetryState>,
+    mut gizmos: Gizmos,
+) {
+    if !settings.draw_gizmos {
+        return;
+    }
+    for (path, event) in &state.latest {
+        match &event.payload {
+            TelemetryPayload::LineStrip3d { points, color } => {
+                let color = color.map(rgba_color).unwrap_or(Color::WHITE);
+                gizmos.linestrip(points.iter().copied(), color);
+            }
+            // entity-bound transforms are applied to the ECS instead of drawn
+            TelemetryPayload::Transform {
+                translation,
+                rotation,
+                scale,
+            } if super::parse_entity_path(path).is_none() => {
+                let transform = Transform {
+                    translation: *translation,
+                    rotation: *rotation,
+                    scale: *scale,
+                };
+                gizmos.axes(transform, settings.axes_length);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Keep one mesh per point cloud path, rebuilt when a newer cloud arrives.
+fn update_telemetry_point_clouds(
+    mut commands: Commands,
+    settings: Res<TelemetryRenderSettings>,
+    state: Res<TelemetryState>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut clouds: Query<(Entity, &mut TelemetryPointCloud, &Mesh3d, &mut Visibility)>,
+) {
+    if !state.is_changed() && !settings.is_changed() {
+        return;
+    }
+    let visibility = match settings.draw_gizmos {
+        true => Visibility::Inherited,
+        false => Visibility::Hidden,
+    };
+    let mut drawn = HashSet::new();
+    for (entity, mut cloud, mesh, mut shown) in &mut clouds {
+        let Some((
+            time,
+            TelemetryPayload::Points3d {
+                positions,
+                colors,
+                radius,
+            },
+        )) = state
+            .latest
+            .get(&cloud.path)
+            .map(|event| (event.time.value, &event.payload))
+        else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        if cloud.time != time || settings.is_changed() {
+            let radius = radius.unwrap_or(settings.default_point_radius);
+            let _ = meshes.insert(mesh.id(), point_cloud_mesh(positions, colors, radius));
+            cloud.time = time;
+        }
+        shown.set_if_neq(visibility);
+        drawn.insert(cloud.path.clone());
+    }
+    for (path, event) in &state.latest {
+        let TelemetryPayload::Points3d {
+            positions,
+            colors,
+            radius,
+        } = &event.payload
+        else {
+            continue;
+        };
+        if drawn.contains(path) {
+            continue;
+        }
+        let radius = radius.unwrap_or(settings.default_point_radius);
+        commands.spawn((
+            Name::new(format!("Telemetry {}", path)),
+            TelemetryPointCloud {
+                path: path.clone(),
+                time: event.time.value,
+            },
+            Mesh3d(meshes.add(point_cloud_mesh(positions, colors, radius))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                unlit: true,
+                ..default()
+            })),
+            Transform::default(),
+            visibility,
+        ));
+    }
+}
+
+/// A point cloud as a single mesh, with every point an octahedron of `radius`.
+pub fn point_cloud_mesh(positions: &[Vec3], colors: &[[u8; 4]], radius: f32) -> Mesh {
+    const CORNERS: [Vec3; 6] = [
+        Vec3::X,
+        Vec3::NEG_X,
+        Vec3::Y,
+        Vec3::NEG_Y,
+        Vec3::Z,
+        Vec3::NEG_Z,
+    ];
+    // counter-clockwise seen from outside
+    const FACES: [u32; 24] = [
+        0, 2, 4, 2, 1, 4, 1, 3, 4, 3, 0, 4, 2, 0, 5, 1, 2, 5, 3, 1, 5, 0, 3, 5,
+    ];
+    let mut vertices = Vec::with_capacity(positions.len() * CORNERS.len());
+    let mut vertex_colors = Vec::with_capacity(positions.len() * CORNERS.len());
+    let mut indices = Vec::with_capacity(positions.len() * FACES.len());
+    for (i, position) in positions.iter().enumerate() {
+        let first = vertices.len() as u32;
+        let color = point_color(colors, i).to_linear().to_f32_array();
+        for corner in CORNERS {
+            vertices.push((*position + corner * radius).to_array());
+            vertex_colors.push(color);
+        }
+        indices.extend(FACES.iter().map(|index| first + index));
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vertices)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vertex_colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn point_color(colors: &[[u8; 4]], index: usize) -> Color {
+    match colors {
+        [] => Color::WHITE,
+        [single] => rgba_color(*single),
+        colors => colors
+            .get(index)
+            .copied()
+            .map(rgba_color)
+            .unwrap_or(Color::WHITE),
+    }
+}
+
+fn rgba_color([r, g, b, a]: [u8; 4]) -> Color {
+    Color::srgba_u8(r, g, b, a)
+}
+
+fn update_telemetry_images(
+    state: Res<TelemetryState>,
+    mut telemetry_images: ResMut<TelemetryImages>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for (path, event) in &state.latest {
+        let TelemetryPayload::Image {
+            width,
+            height,
+            format,
+            data,
+        } = &event.payload
+        else {
+            continue;
+        };
+        if let Some((_, time)) = telemetry_images.images.get(path)
+            && *time == event.time.value
+        {
+            continue;
+        }
+        let image = match telemetry_image(*width, *height, *format, data) {
+            Ok(image) => image,
+            Err(err) => {
+                bevy::log::warn!("Failed to decode telemetry image at {}: {}", path, err);
+                continue;
+            }
+        };
+        match telemetry_images.images.get_mut(path) {
+            Some((handle, time)) => {
+                // reuse the handle so that egui keeps the same texture id
+                let _ = images.insert(handle.id(), image);
+                *time = event.time.value;
+            }
+            None => {
+                let handle = images.add(image);
+                telemetry_images
+                    .images
+                    .insert(path.clone(), (handle, event.time.value));
+            }
+        }
+    }
+}
+
+/// Convert a telemetry image payload into an RGBA Bevy image.
+///
+/// Depth images are normalised to greyscale over their finite range.
+pub fn telemetry_image(
+    width: u32,
+    height: u32,
+    format: TelemetryImageFormat,
+    data: &[u8],
+) -> Result<Image, String> {
+    let pixels = (width as usize) * (height as usize);
+    let rgba = match format {
+        TelemetryImageFormat::Png | TelemetryImageFormat::Jpeg => {
+            let mime = if format == TelemetryImageFormat::Png {
+                "image/png"
+            } else {
+                "image/jpeg"
+            };
+            return Image::from_buffer(
+                data,
+                ImageType::MimeType(mime),
+                CompressedImageFormats::NONE,
+                true,
+                ImageSampler::default(),
+                RenderAssetUsages::default(),
+            )
+            .map_err(|err| err.to_string());
+        }
+        TelemetryImageFormat::Rgba8 => {
+            expect_len(data, pixels * 4)?;
+            data.to_vec()
+        }
+        TelemetryImageFormat::Rgb8 => {
+            expect_len(data, pixels * 3)?;
+            data.chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX])
+                .collect()
+        }
+        TelemetryImageFormat::DepthF32 => {
+            expect_len(data, pixels * 4)?;
+            let depth: Vec<f32> = data
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            depth_to_rgba(&depth)
+        }
+        TelemetryImageFormat::DepthU16 => {
+            expect_len(data, pixels * 2)?;
+            let depth: Vec<f32> = data
+                .chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]) as f32)
+                .collect();
+            depth_to_rgba(&depth)
+        }
+    };
+    Ok(Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        rgba,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    ))
+}
+
+fn expect_len(data: &[u8], expected: usize) -> Result<(), String> {
+    if data.len() != expected {
+        return Err(format!(
+            "expected {} bytes of pixel data, got {}",
+            expected,
+            data.len()
+        ));
+    }
+    Ok(())
+}
+
+fn depth_to_rgba(depth: &[f32]) -> Vec<u8> {
+    let (min, max) = depth
+        .iter()
+        .filter(|d| d.is_finite() && **d > 0.0)
+        .fold((f32::MAX, f32::MIN), |(min, max), d| {
+            (min.min(*d), max.max(*d))
+        });
+    let range = (max - min).max(f32::EPSILON);
+    depth
+        .iter()
+        .flat_map(|d| {
+            let v = if d.is_finite() && *d > 0.0 {
+                // near is bright, far is dark
+                (255.0 * (1.0 - (d - min) / range)) as u8
+            } else {
+                0
+            };
+            [v, v, v, u8::MAX]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{mesh::VertexAttributeValues, prelude::*};
+    use dimensify_protocol::TelemetryImageFormat;
+
+    use super::{depth_to_rgba, point_cloud_mesh, point_color, telemetry_image};
+
+    #[test]
+    fn point_clouds_become_one_mesh() {
+        let mesh = point_cloud_mesh(&[Vec3::ZERO, Vec3::X], &[[255, 0, 0, 255]], 0.5);
+
+        assert_eq!(mesh.count_vertices(), 12);
+        assert_eq!(mesh.indices().unwrap().len(), 48);
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("expected positions");
+        };
+        // the second point's corners surround it
+        assert_eq!(positions[6], [1.5, 0.0, 0.0]);
+        let Some(VertexAttributeValues::Float32x4(colors)) = mesh.attribute(Mesh::ATTRIBUTE_COLOR)
+        else {
+            panic!("expected vertex colours");
+        };
+        // a single colour applies to every point
+        assert_eq!(colors[11], [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn points_without_their_own_colour_are_white() {
+        let colors = [[0, 0, 0, 255], [255, 255, 255, 255]];
+        assert_eq!(point_color(&colors, 0), Color::srgba_u8(0, 0, 0, 255));
+        assert_eq!(point_color(&colors, 5), Color::WHITE);
+        assert_eq!(point_color(&[], 0), Color::WHITE);
+    }
+
+    #[test]
+    fn depth_is_bright_near_and_black_when_invalid() {
+        let rgba = depth_to_rgba(&[1.0, 2.0, 0.0, f32::NAN]);
+        let grey: Vec<u8> = rgba.chunks_exact(4).map(|pixel| pixel[0]).collect();
+        assert_eq!(grey, [255, 0, 0, 0]);
+    }
+
+    #[test]
+    fn raw_images_are_expanded_to_rgba() {
+        let image = telemetry_image(2, 1, TelemetryImageFormat::Rgb8, &[1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(image.data.unwrap(), [1, 2, 3, 255, 4, 5, 6, 255]);
+
+        assert!(telemetry_image(2, 1, TelemetryImageFormat::Rgb8, &[1, 2, 3]).is_err());
+    }
+}
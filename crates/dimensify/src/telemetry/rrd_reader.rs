@@ -0,0 +1,279 @@
+//! Read Rerun `.rrd` recordings back into [`TelemetryEvent`]s.
+//!
+//! Scalars, points, text and `Transform3D` are mapped; everything else in the
+//! recording (images, line strips, ...) is skipped.
+
+use std::{fs::File, io::BufReader, path::Path};
+
+use anyhow::Context;
+use dimensify_protocol::{TelemetryEvent, TelemetryPayload, TelemetryTime};
+use rerun::{
+    components::{Position2D, Position3D, RotationQuat, Scalar, Scale3D, Text, Translation3D},
+    external::re_log_encoding::{VersionPolicy, decoder::Decoder},
+    log::{Chunk, LogMsg},
+};
+
+/// Timeline used for static (timeless) rows.
+pub const STATIC_TIMELINE: &str = "static";
+
+/// Decode every supported row of an `.rrd` file into telemetry events.
+///
+/// Rows logged on several timelines produce one event per timeline, so the
+/// recording can be scrubbed on any of them.
+pub fn read_rrd(path: impl AsRef<Path>) -> anyhow::Result<Vec<TelemetryEvent>> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("failed to open {:?}", path))?;
+    let decoder = Decoder::new(VersionPolicy::Warn, BufReader::new(file))
+        .with_context(|| format!("failed to decode rrd header of {:?}", path))?;
+
+    let mut events = Vec::new();
+    for msg in decoder {
+        let msg = msg.with_context(|| format!("failed to decode message in {:?}", path))?;
+        let LogMsg::ArrowMsg(_, arrow_msg) = msg else {
+            continue;
+        };
+        match Chunk::from_arrow_msg(&arrow_msg) {
+            Ok(chunk) => chunk_to_events(&chunk, &mut events),
+            Err(err) => bevy::log::warn!("Skipping undecodable rrd chunk: {}", err),
+        }
+    }
+    Ok(events)
+}
+
+/// Per-row timestamps for every timeline in a chunk, as `(timeline, values)`.
+fn chunk_times(chunk: &Chunk) -> Vec<(String, Vec<f64>)> {
+    if chunk.is_static() {
+        return vec![(STATIC_TIMELINE.to_string(), vec![0.0; chunk.num_rows()])];
+    }
+    chunk
+        .timelines()
+        .iter()
+        .map(|(timeline, column)| {
+            let to_value = |raw: i64| match timeline.typ() {
+                // temporal timelines are stored in nanoseconds
+                rerun::time::TimeType::Time => raw as f64 * 1e-9,
+                rerun::time::TimeType::Sequence => raw as f64,
+            };
+            (
+                timeline.name().to_string(),
+                column.times_raw().iter().copied().map(to_value).collect(),
+            )
+        })
+        .collect()
+}
+
+fn chunk_to_events(chunk: &Chunk, events: &mut Vec<TelemetryEvent>) {
+    let entity_path = chunk.entity_path().to_string();
+    let path = entity_path.trim_start_matches('/');
+    let times = chunk_times(chunk);
+
+    let mut emit = |row: usize, path: String, payload: TelemetryPayload| {
+        for (timeline, values) in &times {
+            let Some(value) = values.get(row) else {
+                continue;
+            };
+            events.push(TelemetryEvent {
+                path: path.clone(),
+                time: TelemetryTime {
+                    timeline: timeline.clone(),
+                    value: *value,
+                },
+                payload: payload.clone(),
+                metadata: None,
+            });
+        }
+    };
+
+    // Scalars: one value is a scalar, four values round-trip our Vec4 encoding.
+    for (row, scalars) in chunk.iter_component::<Scalar>().enumerate() {
+        let values: Vec<f64> = scalars.iter().map(|scalar| scalar.0.0).collect();
+        match values.as_slice() {
+            [] => {}
+            [value] => emit(
+                row,
+                path.to_string(),
+                TelemetryPayload::Scalar { value: *value },
+            ),
+            [x, y, z, w] => emit(
+                row,
+                path.to_string(),
+                TelemetryPayload::Vec4 {
+                    value: [*x as f32, *y as f32, *z as f32, *w as f32].into(),
+                },
+            ),
+            values => {
+                for (i, value) in values.iter().enumerate() {
+                    emit(
+                        row,
+                        format!("{}/{}", path, i),
+                        TelemetryPayload::Scalar { value: *value },
+                    );
+                }
+            }
+        }
+    }
+
+    for (row, points) in chunk.iter_component::<Position2D>().enumerate() {
+        emit_indexed(points.iter().map(|p| p.0.0), path, |value| {
+            TelemetryPayload::Vec2 {
+                value: value.into(),
+            }
+        })
+        .for_each(|(path, payload)| emit(row, path, payload));
+    }
+
+    // A single 3D point round-trips our Vec3 encoding; batches are point clouds.
+    for (row, points) in chunk.iter_component::<Position3D>().enumerate() {
+        let payload = match points.as_slice() {
+            [] => continue,
+            [point] => TelemetryPayload::Vec3 {
+                value: point.0.0.into(),
+            },
+            points => TelemetryPayload::Points3d {
+                positions: points.iter().map(|p| p.0.0.into()).collect(),
+                colors: Vec::new(),
+                radius: None,
+            },
+        };
+        emit(row, path.to_string(), payload);
+    }
+
+    for (row, texts) in chunk.iter_component::<Text>().enumerate() {
+        if let Some(text) = texts.first() {
+            emit(
+                row,
+                path.to_string(),
+                TelemetryPayload::Text {
+                    value: text.as_str().to_string(),
+                },
+            );
+        }
+    }
+
+    // Transform3D is split into the `<path>/translation|rotation|scale` convention
+    // understood by the ECS sync.
+    for (row, translations) in chunk.iter_component::<Translation3D>().enumerate() {
+        if let Some(translation) = translations.first() {
+            emit(
+                row,
+                format!("{}/translation", path),
+                TelemetryPayload::Vec3 {
+                    value: translation.0.0.into(),
+                },
+            );
+        }
+    }
+    for (row, rotations) in chunk.iter_component::<RotationQuat>().enumerate() {
+        if let Some(rotation) = rotations.first() {
+            emit(
+                row,
+                format!("{}/rotation", path),
+                TelemetryPayload::Vec4 {
+                    value: rotation.0.0.into(),
+                },
+            );
+        }
+    }
+    for (row, scales) in chunk.iter_component::<Scale3D>().enumerate() {
+        if let Some(scale) = scales.first() {
+            emit(
+                row,
+                format!("{}/scale", path),
+                TelemetryPayload::Vec3 {
+                    value: scale.0.0.into(),
+                },
+            );
+        }
+    }
+}
+
+/// A single item keeps the entity path; batches get one `<path>/<index>` path per item.
+fn emit_indexed<T, I>(
+    items: I,
+    path: &str,
+    payload: impl Fn(T) -> TelemetryPayload,
+) -> impl Iterator<Item = (String, TelemetryPayload)>
+where
+    I: ExactSizeIterator<Item = T>,
+{
+    let single = items.len() == 1;
+    items.enumerate().map(move |(i, item)| {
+        let path = if single {
+            path.to_string()
+        } else {
+            format!("{}/{}", path, i)
+        };
+        (path, payload(item))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec3;
+    use dimensify_protocol::{TelemetryEvent, TelemetryPayload, TelemetryTime};
+
+    use super::read_rrd;
+    use crate::telemetry::log_event_to_rerun;
+
+    fn event(path: &str, time: f64, payload: TelemetryPayload) -> TelemetryEvent {
+        TelemetryEvent {
+            path: path.to_string(),
+            time: TelemetryTime {
+                timeline: "sim".to_string(),
+                value: time,
+            },
+            payload,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn recorded_telemetry_reads_back() {
+        let path =
+            std::env::temp_dir().join(format!("dimensify_telemetry_{}.rrd", std::process::id()));
+        let rec = rerun::RecordingStreamBuilder::new("dimensify_test")
+            .save(&path)
+            .unwrap();
+        for event in [
+            event("arm/speed", 1.0, TelemetryPayload::Scalar { value: 0.5 }),
+            event("arm/speed", 2.0, TelemetryPayload::Scalar { value: 0.75 }),
+            event(
+                "arm/target",
+                2.0,
+                TelemetryPayload::Vec3 {
+                    value: Vec3::new(1.0, 2.0, 3.0),
+                },
+            ),
+        ] {
+            log_event_to_rerun(&rec, &event);
+        }
+        rec.flush_blocking();
+        drop(rec);
+
+        let events = read_rrd(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // rows are also stamped on the recorder's own timelines
+        let read: Vec<&TelemetryEvent> = events
+            .iter()
+            .filter(|event| event.time.timeline == "sim")
+            .collect();
+        let at = |path: &str, time: f64| {
+            read.iter()
+                .find(|event| event.path == path && (event.time.value - time).abs() < 1e-6)
+                .map(|event| &event.payload)
+        };
+        assert_eq!(read.len(), 3);
+        assert!(matches!(
+            at("arm/speed", 1.0),
+            Some(TelemetryPayload::Scalar { value }) if *value == 0.5
+        ));
+        assert!(matches!(
+            at("arm/speed", 2.0),
+            Some(TelemetryPayload::Scalar { value }) if *value == 0.75
+        ));
+        assert!(matches!(
+            at("arm/target", 2.0),
+            Some(TelemetryPayload::Vec3 { value }) if *value == Vec3::new(1.0, 2.0, 3.0)
+        ));
+    }
+}
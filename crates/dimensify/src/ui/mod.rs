@@ -5,15 +5,17 @@ use bevy_egui::EguiPrimaryContextPass;
 
 #[cfg(feature = "physics")]
 pub(crate) mod rapier_debug_render;
+pub mod tasks_tab;
+#[cfg(all(feature = "protocol", not(target_arch = "wasm32")))]
+mod telemetry_export;
+#[cfg(feature = "protocol")]
+mod telemetry_images;
 #[cfg(feature = "protocol")]
 mod telemetry_timeline;
-pub mod widgets;
-use widgets::{
-    WidgetCommandQueue, WidgetPanel, WidgetRegistry, apply_widget_commands, register_demo_widgets,
-};
-
+#[cfg(all(feature = "protocol", not(target_arch = "wasm32")))]
+pub mod widget_stream;
 #[cfg(feature = "protocol")]
-use widgets::WidgetStreamSettings;
+pub mod widgets;
 // use bevy_editor_pls::EditorPlugin;
 
 /// Plugin with debugging utility intended for use during development only.
@@ -28,17 +30,22 @@ pub fn plugin(app: &mut App) {
         LogDiagnosticsPlugin::filtered(HashSet::new()),
         // EguiToastsPlugin::default(),
         // bevy_rapier3d::render::RapierDebugRenderPlugin::default(),
-    ))
-    .init_resource::<WidgetRegistry>()
-    .init_resource::<WidgetCommandQueue>()
-    .init_resource::<WidgetPanel>()
-    .add_systems(Startup, register_demo_widgets)
-    .add_systems(Update, apply_widget_commands);
+    ));
+    #[cfg(feature = "protocol")]
+    {
+        if !app.world().contains_resource::<widgets::WidgetRegistry>() {
+            app.add_plugins(widgets::plugin);
+        }
+        app.add_systems(Startup, widgets::register_demo_widgets);
+    }
 
     #[cfg(feature = "protocol")]
     app.add_systems(
         EguiPrimaryContextPass,
-        telemetry_timeline::telemetry_timeline_ui,
+        (
+            telemetry_timeline::telemetry_timeline_ui,
+            telemetry_images::telemetry_images_ui,
+        ),
     )
     // TODO: add back in when bevy_editor_pls is updated to use
     // newer bevy_egui version
@@ -48,8 +55,19 @@ pub fn plugin(app: &mut App) {
     #[cfg(feature = "protocol")]
     #[cfg(not(target_arch = "wasm32"))]
     {
-        app.init_resource::<WidgetStreamSettings>();
-        app.add_systems(Startup, widgets::load_widget_commands_from_source);
+        app.init_resource::<telemetry_export::TelemetryExportDialog>()
+            .add_systems(
+                EguiPrimaryContextPass,
+                telemetry_export::telemetry_export_ui
+                    .after(telemetry_timeline::telemetry_timeline_ui),
+            )
+            .add_systems(Update, telemetry_export::poll_telemetry_export);
+        if !app
+            .world()
+            .contains_resource::<widget_stream::WidgetStreamSettings>()
+        {
+            app.add_plugins(widget_stream::plugin);
+        }
     }
 
     // .insert_gizmo_group(
@@ -0,0 +1,104 @@
+//! The tasks tab, listing the [`Tasks`] of the viewer.
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use dimensify_ui::{
+    command_palette::{PaletteAction, RegisterPaletteAction},
+    tabs::{PanelEntry, PanelLocation, PanelRegistry, ViewerTab},
+};
+
+use crate::tasks::{Task, TaskId, TaskStatus, Tasks};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<PanelRegistry>();
+    app.world_mut()
+        .resource_mut::<PanelRegistry>()
+        .register(PanelEntry {
+            title: "Tasks".into(),
+            location: PanelLocation::Bottom,
+            default_enabled: true,
+            factory: std::sync::Arc::new(|| Box::new(TasksTab)),
+        });
+    app.register_palette_action(PaletteAction::new("Clear finished tasks", |world| {
+        world.resource_mut::<Tasks>().clear_finished()
+    }));
+}
+
+pub struct TasksTab;
+
+impl ViewerTab for TasksTab {
+    fn title(&self) -> &str {
+        "Tasks"
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let mut tasks = world.get_resource_or_init::<Tasks>();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} running", tasks.running()));
+            if ui.button("Clear finished").clicked() {
+                tasks.clear_finished();
+            }
+        });
+        if tasks.iter().next().is_none() {
+            ui.weak("No tasks yet.");
+            return;
+        }
+        let mut cancel = Vec::new();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("tasks")
+                .striped(true)
+                .num_columns(5)
+                .show(ui, |ui| {
+                    // newest first
+                    for task in tasks.iter().rev() {
+                        task_row_ui(ui, task, &mut cancel);
+                        ui.end_row();
+                    }
+                });
+        });
+        for id in cancel {
+            tasks.request_cancel(id);
+        }
+    }
+}
+
+fn task_row_ui(ui: &mut egui::Ui, task: &Task, cancel: &mut Vec<TaskId>) {
+    ui.strong(task.kind);
+    match task.count {
+        1 => ui.label(task.label.as_str()),
+        count => ui.label(format!("{} (×{})", task.label, count)),
+    };
+    match &task.status {
+        TaskStatus::Running if task.cancel_requested => {
+            ui.label("cancelling…");
+        }
+        TaskStatus::Running => {
+            let bar = match task.progress {
+                Some(progress) => egui::ProgressBar::new(progress).show_percentage(),
+                None => egui::ProgressBar::new(0.0).animate(true),
+            };
+            let bar = match &task.detail {
+                Some(detail) => bar.text(detail.as_str()),
+                None => bar,
+            };
+            ui.add(bar.desired_width(160.0));
+        }
+        TaskStatus::Done => {
+            ui.label(task.detail.as_deref().unwrap_or("done"));
+        }
+        TaskStatus::Failed(reason) => {
+            ui.colored_label(ui.visuals().error_fg_color, reason.as_str());
+        }
+        TaskStatus::Cancelled => {
+            ui.weak("cancelled");
+        }
+    }
+    ui.label(format!("{:.1} s", task.elapsed().as_secs_f32()));
+    if task.cancellable && task.is_running() && !task.cancel_requested {
+        if ui.small_button("Cancel").clicked() {
+            cancel.push(task.id);
+        }
+    } else {
+        ui.label("");
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeSet;
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, block_on, futures_lite::future},
+};
+use bevy_egui::{EguiContexts, egui};
+use dimensify_protocol::TelemetryEvent;
+
+use crate::{
+    tasks::{TaskId, Tasks},
+    telemetry::{
+        TelemetryPlayback, TelemetryStore,
+        export::{TelemetryExportFormat, TelemetryExportSelection, export_events},
+    },
+};
+
+/// State of the telemetry export window, opened from the timeline panel.
+#[derive(Resource)]
+pub struct TelemetryExportDialog {
+    pub open: bool,
+    format: TelemetryExportFormat,
+    output: String,
+    /// Selected paths; empty exports every path.
+    paths: BTreeSet<String>,
+    /// Limit the export to the playback loop range.
+    use_loop_range: bool,
+    status: Option<Result<String, String>>,
+    /// The export being written in the background.
+    job: Option<(TaskId, Task<Result<String, String>>)>,
+}
+
+impl Default for TelemetryExportDialog {
+    fn default() -> Self {
+        Self {
+            open: false,
+            format: TelemetryExportFormat::Csv,
+            output: "telemetry.csv".to_string(),
+            paths: BTreeSet::new(),
+            use_loop_range: true,
+            status: None,
+            job: None,
+        }
+    }
+}
+
+pub fn telemetry_export_ui(
+    mut contexts: EguiContexts,
+    mut dialog: ResMut<TelemetryExportDialog>,
+    playback: Res<TelemetryPlayback>,
+    store: Res<TelemetryStore>,
+    mut tasks: ResMut<Tasks>,
+) {
+    if !dialog.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let dialog = &mut *dialog;
+    let mut open = dialog.open;
+    egui::Window::new("Export telemetry")
+        .open(&mut open)
+        .resizable(true)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Format");
+                let previous = dialog.format;
+                egui::ComboBox::from_id_salt("telemetry_export_format")
+                    .selected_text(dialog.format.extension())
+                    .show_ui(ui, |ui| {
+                        for format in TelemetryExportFormat::ALL {
+                            ui.add_enabled_ui(format.is_supported(), |ui| {
+                                ui.selectable_value(&mut dialog.format, format, format.extension());
+                            });
+                        }
+                    });
+                if dialog.format != previous {
+                    dialog.output = std::path::Path::new(&dialog.output)
+                        .with_extension(dialog.format.extension())
+                        .to_string_lossy()
+                        .into_owned();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Output");
+                ui.text_edit_singleline(&mut dialog.output);
+            });
+
+            ui.label(format!("Timeline: {}", playback.timeline));
+            let range = dialog
+                .use_loop_range
+                .then_some(playback.loop_range)
+                .flatten();
+            ui.add_enabled_ui(playback.loop_range.is_some(), |ui| {
+                ui.checkbox(&mut dialog.use_loop_range, "Only the loop range");
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Paths");
+                if ui.small_button("clear").clicked() {
+                    dialog.paths.clear();
+                }
+                if dialog.paths.is_empty() {
+                    ui.weak("(all)");
+                }
+            });
+            let mut paths = store.paths(&playback.timeline);
+            paths.sort();
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for path in paths {
+                        let mut selected = dialog.paths.contains(&path);
+                        if ui.checkbox(&mut selected, &path).changed() {
+                            if selected {
+                                dialog.paths.insert(path);
+                            } else {
+                                dialog.paths.remove(&path);
+                            }
+                        }
+                    }
+                });
+
+            ui.separator();
+            let exporting = dialog.job.is_some();
+            if ui
+                .add_enabled(!exporting, egui::Button::new("Export"))
+                .clicked()
+            {
+                let selection = TelemetryExportSelection {
+                    timeline: playback.timeline.clone(),
+                    paths: dialog.paths.iter().cloned().collect(),
+                    range,
+                };
+                // the export writes a copy, as the store keeps changing while it runs
+                let events: Vec<TelemetryEvent> = store
+                    .iter()
+                    .filter(|event| selection.matches(event))
+                    .cloned()
+                    .collect();
+                let format = dialog.format;
+                let output = dialog.output.clone();
+                let task = tasks.start("Telemetry export", output.clone());
+                let job = AsyncComputeTaskPool::get().spawn(async move {
+                    let events: Vec<&TelemetryEvent> = events.iter().collect();
+                    export_events(&events, &selection.timeline, format, &output)
+                        .map(|count| format!("Exported {} events to {}", count, output))
+                        .map_err(|err| format!("{:#}", err))
+                });
+                dialog.job = Some((task, job));
+                dialog.status = None;
+            }
+            if exporting {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Exporting…");
+                });
+            }
+            match &dialog.status {
+                Some(Ok(message)) => {
+                    ui.label(message);
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+                None => {}
+            }
+        });
+    dialog.open = open;
+}
+
+/// End the task of a finished export and show its outcome in the dialog.
+pub fn poll_telemetry_export(mut dialog: ResMut<TelemetryExportDialog>, mut tasks: ResMut<Tasks>) {
+    let Some((task, job)) = &mut dialog.job else {
+        return;
+    };
+    let Some(status) = block_on(future::poll_once(job)) else {
+        return;
+    };
+    match &status {
+        Ok(message) => {
+            tasks.set_detail(*task, message.clone());
+            tasks.finish(*task);
+        }
+        Err(err) => tasks.fail(*task, err.clone()),
+    }
+    dialog.job = None;
+    dialog.status = Some(status);
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiTextureHandle, egui};
+
+use crate::telemetry::render::TelemetryImages;
+
+/// Largest edge length (in points) an image is drawn at inside its window.
+const MAX_IMAGE_EDGE: f32 = 320.0;
+
+/// One floating window per telemetry image path.
+pub fn telemetry_images_ui(
+    mut contexts: EguiContexts,
+    telemetry_images: Res<TelemetryImages>,
+    images: Res<Assets<Image>>,
+) {
+    let mut textures = Vec::with_capacity(telemetry_images.images.len());
+    for (path, (handle, _)) in &telemetry_images.images {
+        let Some(image) = images.get(handle) else {
+            continue;
+        };
+        let size = image.size_f32();
+        let texture = contexts.add_image(EguiTextureHandle::Strong(handle.clone()));
+        textures.push((path.clone(), texture, size));
+    }
+    if textures.is_empty() {
+        return;
+    }
+    textures.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    for (path, texture, size) in textures {
+        let scale = (MAX_IMAGE_EDGE / size.max_element()).min(1.0);
+        egui::Window::new(&path)
+            .id(egui::Id::new(("telemetry_image", &path)))
+            .default_open(true)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.image(egui::load::SizedTexture::new(
+                    texture,
+                    egui::vec2(size.x * scale, size.y * scale),
+                ));
+            });
+    }
+}
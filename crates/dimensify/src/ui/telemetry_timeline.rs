@@ -6,21 +6,27 @@ use crate::telemetry::{
     TelemetryStore,
 };
 
+/// Playback speed presets offered in the speed dropdown.
+const PLAYBACK_SPEEDS: [f64; 7] = [0.1, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
 pub fn telemetry_timeline_ui(
     mut contexts: EguiContexts,
     mut playback: ResMut<TelemetryPlayback>,
     store: Res<TelemetryStore>,
     mut recording: ResMut<TelemetryRecordingState>,
     mut ecs_sync: ResMut<TelemetryEcsSync>,
+    #[cfg(not(target_arch = "wasm32"))] mut export_dialog: ResMut<
+        super::telemetry_export::TelemetryExportDialog,
+    >,
 ) {
     let Ok(ctx) = contexts.ctx_mut() else {
         return;
     };
 
+    let timelines = store.timelines();
     let timeline_bounds = store.timeline_bounds(&playback.timeline);
 
     let rrd_supported = cfg!(feature = "telemetry_rrd");
-    let mut show_timeline = recording.enabled;
 
     egui::TopBottomPanel::bottom("telemetry_timeline_panel")
         .resizable(false)
@@ -40,7 +46,20 @@ pub fn telemetry_timeline_ui(
                 }
 
                 ui.label("Timeline");
-                ui.text_edit_singleline(&mut playback.timeline);
+                let mut selected = playback.timeline.clone();
+                egui::ComboBox::from_id_salt("telemetry_timeline_select")
+                    .selected_text(selected.as_str())
+                    .show_ui(ui, |ui| {
+                        for timeline in &timelines {
+                            ui.selectable_value(&mut selected, timeline.clone(), timeline);
+                        }
+                    });
+                if selected != playback.timeline {
+                    playback.timeline = selected;
+                    // loop ranges are expressed in the units of the previous timeline
+                    playback.loop_range = None;
+                    playback.playing = false;
+                }
 
                 let mut live = playback.mode == TelemetryPlaybackMode::Live;
                 if ui.checkbox(&mut live, "Live").changed() {
@@ -49,6 +68,9 @@ pub fn telemetry_timeline_ui(
                     } else {
                         TelemetryPlaybackMode::Fixed
                     };
+                    if live {
+                        playback.playing = false;
+                    }
                 }
 
                 if let Some(err) = recording.error.as_ref() {
@@ -57,64 +79,158 @@ pub fn telemetry_timeline_ui(
 
                 ui.add_space(8.0);
                 ui.checkbox(&mut ecs_sync.enabled, "Apply to ECS");
-            });
 
-            if recording.enabled {
-                show_timeline = true;
-            }
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui.button("Export…").clicked() {
+                    export_dialog.open = true;
+                }
+            });
 
-            if show_timeline {
-                ui.add_space(4.0);
-                match timeline_bounds {
-                    Some((min, max)) if max >= min => {
-                        let mut time = playback.time;
-                        let slider = egui::Slider::new(&mut time, min..=max)
-                            .text("time")
-                            .clamping(egui::SliderClamping::Always);
-                        let response = ui.add(slider);
-                        if response.changed() {
-                            playback.time = time;
-                            playback.mode = TelemetryPlaybackMode::Fixed;
-                        }
-                        let rect = response.rect;
-                        if rect.is_positive() {
-                            let min_f = min as f32;
-                            let max_f = max as f32;
-                            let live_x =
-                                egui::emath::remap_clamp(max_f, min_f..=max_f, rect.x_range());
-                            let scrub_x = egui::emath::remap_clamp(
-                                time as f32,
-                                min_f..=max_f,
-                                rect.x_range(),
-                            );
-                            let painter = ui.painter();
-                            let live_color = egui::Color32::from_rgb(255, 200, 80);
-                            let scrub_color = egui::Color32::from_rgb(120, 200, 255);
-                            painter.line_segment(
-                                [
-                                    egui::pos2(live_x, rect.top()),
-                                    egui::pos2(live_x, rect.bottom()),
-                                ],
-                                egui::Stroke::new(2.0, live_color),
-                            );
-                            painter.circle_filled(
-                                egui::pos2(scrub_x, rect.center().y),
-                                3.5,
-                                scrub_color,
-                            );
-                        }
-                        ui.horizontal(|ui| {
-                            ui.label(format!("range: {:.3}s .. {:.3}s", min, max));
-                            if ui.button("Reset to live").clicked() {
-                                playback.time = max;
-                                playback.mode = TelemetryPlaybackMode::Live;
-                            }
-                        });
-                    }
-                    _ => {
-                        ui.label("No telemetry on this timeline yet.");
-                    }
+            ui.add_space(4.0);
+            match timeline_bounds {
+                Some((min, max)) if max >= min => {
+                    playback_controls(ui, &mut playback, &store, min, max);
+                    scrubber(ui, &mut playback, min, max);
+                }
+                _ => {
+                    ui.label("No telemetry on this timeline yet.");
                 }
             }
         });
 }
+
+/// Play/pause, event stepping, speed and loop-range controls.
+fn playback_controls(
+    ui: &mut egui::Ui,
+    playback: &mut TelemetryPlayback,
+    store: &TelemetryStore,
+    min: f64,
+    max: f64,
+) {
+    ui.horizontal(|ui| {
+        if ui
+            .button("⏮")
+            .on_hover_text("Step back one event")
+            .clicked()
+        {
+            playback.step(store, false);
+        }
+
+        let play_label = if playback.playing { "⏸" } else { "▶" };
+        if ui
+            .button(play_label)
+            .on_hover_text("Play / pause")
+            .clicked()
+        {
+            if playback.playing {
+                playback.playing = false;
+            } else {
+                let (start, end) = playback.loop_range.unwrap_or((min, max));
+                // restart from the beginning when resuming at the end of the range
+                let time = if playback.time >= end || playback.time < start {
+                    start
+                } else {
+                    playback.time
+                };
+                playback.seek(time);
+                playback.playing = true;
+            }
+        }
+
+        if ui
+            .button("⏭")
+            .on_hover_text("Step forward one event")
+            .clicked()
+        {
+            playback.step(store, true);
+        }
+
+        ui.separator();
+        ui.label("Speed");
+        egui::ComboBox::from_id_salt("telemetry_playback_speed")
+            .selected_text(format!("{}x", playback.speed))
+            .width(64.0)
+            .show_ui(ui, |ui| {
+                for speed in PLAYBACK_SPEEDS {
+                    ui.selectable_value(&mut playback.speed, speed, format!("{}x", speed));
+                }
+            });
+
+        ui.separator();
+        let mut looping = playback.loop_range.is_some();
+        if ui.checkbox(&mut looping, "Loop").changed() {
+            playback.loop_range = looping.then_some((min, max));
+        }
+        if let Some((start, end)) = playback.loop_range.as_mut() {
+            let speed = ((max - min) / 500.0).max(1e-3);
+            ui.add(
+                egui::DragValue::new(start)
+                    .range(min..=*end)
+                    .speed(speed)
+                    .prefix("from "),
+            );
+            ui.add(
+                egui::DragValue::new(end)
+                    .range(*start..=max)
+                    .speed(speed)
+                    .prefix("to "),
+            );
+        }
+
+        ui.separator();
+        ui.label(format!("range: {:.3}s .. {:.3}s", min, max));
+        if ui.button("Reset to live").clicked() {
+            playback.time = max;
+            playback.playing = false;
+            playback.mode = TelemetryPlaybackMode::Live;
+        }
+    });
+}
+
+/// Time slider spanning the timeline bounds, with loop range and live markers.
+fn scrubber(ui: &mut egui::Ui, playback: &mut TelemetryPlayback, min: f64, max: f64) {
+    let mut time = playback.time;
+    let slider = egui::Slider::new(&mut time, min..=max)
+        .text("time")
+        .clamping(egui::SliderClamping::Always);
+    let response = ui.add(slider);
+    if response.changed() {
+        // scrubbing takes over from automatic playback
+        playback.playing = false;
+        playback.seek(time);
+    }
+
+    let rect = response.rect;
+    if !rect.is_positive() {
+        return;
+    }
+    let min_f = min as f32;
+    let max_f = max as f32;
+    let to_x = |value: f64| egui::emath::remap_clamp(value as f32, min_f..=max_f, rect.x_range());
+    let painter = ui.painter();
+
+    if let Some((start, end)) = playback.loop_range {
+        let loop_color = egui::Color32::from_rgba_unmultiplied(120, 255, 160, 40);
+        painter.rect_filled(
+            egui::Rect::from_x_y_ranges(to_x(start)..=to_x(end), rect.y_range()),
+            0.0,
+            loop_color,
+        );
+    }
+
+    let live_color = egui::Color32::from_rgb(255, 200, 80);
+    let scrub_color = egui::Color32::from_rgb(120, 200, 255);
+    let live_x = to_x(max);
+    painter.line_segment(
+        [
+            egui::pos2(live_x, rect.top()),
+            egui::pos2(live_x, rect.bottom()),
+        ],
+        egui::Stroke::new(2.0, live_color),
+    );
+    painter.circle_filled(
+        egui::pos2(to_x(playback.time), rect.center().y),
+        3.5,
+        scrub_color,
+    );
+}
@@ -0,0 +1,357 @@
+//! Widget commands read from `DIMENSIFY_WIDGET_SOURCE`, polled while the viewer runs so
+//! panels can be edited without a restart.
+//!
+//! A JSONL file applies appended lines as they arrive; any other edit replays the whole
+//! file and removes the widgets it no longer defines. The DB source reads a `widgets`
+//! table of a SQLite file (`widget_db` feature), one row per widget, and applies the rows
+//! that changed.
+
+use std::time::{Duration, SystemTime};
+
+use bevy::prelude::*;
+
+use super::widgets::{self, WidgetCommand, WidgetCommandQueue, WidgetRegistry};
+use crate::stream::DataSource;
+
+/// How often the widget source is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub fn plugin(app: &mut App) {
+    if !app.world().contains_resource::<WidgetRegistry>() {
+        app.add_plugins(widgets::plugin);
+    }
+    app.init_resource::<WidgetStreamSettings>()
+        .init_resource::<WidgetSourceState>()
+        .add_systems(
+            Update,
+            poll_widget_source.before(widgets::apply_widget_commands),
+        );
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct WidgetStreamSettings {
+    pub source: DataSource,
+}
+
+impl Default for WidgetStreamSettings {
+    fn default() -> Self {
+        let source = match std::env::var("DIMENSIFY_WIDGET_SOURCE")
+            .unwrap_or_else(|_| "local".to_string())
+            .as_str()
+        {
+            "file" => std::env::var("DIMENSIFY_WIDGET_FILE")
+                .ok()
+                .map(|path| DataSource::FileReplay { path })
+                .unwrap_or(DataSource::Local),
+            "db" => std::env::var("DIMENSIFY_WIDGET_DB_ADDR")
+                .ok()
+                .map(|addr| DataSource::Db { addr })
+                .unwrap_or(DataSource::Local),
+            _ => DataSource::Local,
+        };
+        Self { source }
+    }
+}
+
+/// What was last read from the widget source.
+#[derive(Resource, Default)]
+struct WidgetSourceState {
+    timer: Option<Timer>,
+    /// Set once the source was read, to log reloads only.
+    loaded: bool,
+    /// Set while the source cannot be read, so the failure is logged once.
+    failing: bool,
+    /// Modification time and length of the file when it was last read.
+    stamp: Option<(SystemTime, u64)>,
+    file: FileWidgets,
+    #[cfg(feature = "widget_db")]
+    rows: Vec<(String, String)>,
+}
+
+fn poll_widget_source(
+    time: Res<Time>,
+    settings: Res<WidgetStreamSettings>,
+    mut state: ResMut<WidgetSourceState>,
+    mut queue: ResMut<WidgetCommandQueue>,
+) {
+    let state = &mut *state;
+    // the first poll happens right away
+    if let Some(timer) = &mut state.timer {
+        if !timer.tick(time.delta()).just_finished() {
+            return;
+        }
+    } else {
+        state.timer = Some(Timer::new(POLL_INTERVAL, TimerMode::Repeating));
+    }
+
+    let (path, commands) = match &settings.source {
+        DataSource::Local => return,
+        DataSource::FileReplay { path } => (path, read_widget_file(path, state)),
+        DataSource::Db { addr } => (addr, read_widget_db(addr, state)),
+    };
+    match commands {
+        Ok(commands) => {
+            if state.loaded && !commands.is_empty() {
+                info!("Reloaded {} widget commands from {}", commands.len(), path);
+            }
+            for command in commands {
+                queue.push(command);
+            }
+            state.loaded = true;
+            state.failing = false;
+        }
+        Err(err) => {
+            if !state.failing {
+                warn!("Failed to read widget source {}: {}", path, err);
+                state.failing = true;
+            }
+        }
+    }
+}
+
+/// Commands for the changes of the widget file, or none if it is unchanged.
+fn read_widget_file(
+    path: &str,
+    state: &mut WidgetSourceState,
+) -> Result<Vec<WidgetCommand>, String> {
+    let stamp = std::fs::metadata(path)
+        .and_then(|meta| Ok((meta.modified()?, meta.len())))
+        .map_err(|err| err.to_string())?;
+    if state.stamp == Some(stamp) {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    state.stamp = Some(stamp);
+    Ok(state.file.reload(&content))
+}
+
+/// The lines of a widget file as last applied, and the widgets they leave defined.
+#[derive(Default)]
+struct FileWidgets {
+    lines: Vec<String>,
+    live: Vec<String>,
+}
+
+impl FileWidgets {
+    /// Commands that bring the widgets from the last applied file to `content`.
+    fn reload(&mut self, content: &str) -> Vec<WidgetCommand> {
+        let lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let appended = lines.starts_with(&self.lines);
+        let start = if appended { self.lines.len() } else { 0 };
+        let commands = parse_lines(&lines[start..], start);
+
+        let mut changes = Vec::new();
+        if appended {
+            track_live(&mut self.live, &commands);
+        } else {
+            let mut live = Vec::new();
+            track_live(&mut live, &commands);
+            changes.extend(
+                self.live
+                    .iter()
+                    .filter(|id| !live.contains(id))
+                    .map(|id| WidgetCommand::Remove { id: id.clone() }),
+            );
+            self.live = live;
+        }
+        changes.extend(commands);
+        self.lines = lines;
+        changes
+    }
+}
+
+/// Parse one JSONL widget command, see [`widgets::widget_from_json`].
+fn parse_widget(line: &str) -> Result<WidgetCommand, String> {
+    let value = serde_json::from_str(line).map_err(|err| err.to_string())?;
+    widgets::widget_from_json(value)
+}
+
+fn parse_lines(lines: &[String], first_line: usize) -> Vec<WidgetCommand> {
+    let mut commands = Vec::new();
+    for (line_no, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_widget(line) {
+            Ok(command) => commands.push(command),
+            Err(err) => {
+                warn!(
+                    "Failed to parse widget command at line {}: {}",
+                    first_line + line_no + 1,
+                    err
+                );
+            }
+        }
+    }
+    commands
+}
+
+/// Update the ids of the defined widgets with `commands`.
+fn track_live(live: &mut Vec<String>, commands: &[WidgetCommand]) {
+    for command in commands {
+        match command {
+            WidgetCommand::Remove { id } => live.retain(|known| known != id),
+            command if !live.iter().any(|known| known == command.id()) => {
+                live.push(command.id().to_string());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Commands for the rows of the `widgets` table that changed since the last read:
+///
+/// ```sql
+/// CREATE TABLE widgets (id TEXT PRIMARY KEY, command TEXT NOT NULL);
+/// ```
+///
+/// `command` is a JSONL widget command whose `id` matches the row; deleting the row
+/// removes the widget.
+#[cfg(feature = "widget_db")]
+fn read_widget_db(path: &str, state: &mut WidgetSourceState) -> Result<Vec<WidgetCommand>, String> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let query = || {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut statement = connection.prepare("SELECT id, command FROM widgets ORDER BY rowid")?;
+        statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(String, String)>, rusqlite::Error>>()
+    };
+    let rows = query().map_err(|err| err.to_string())?;
+
+    let mut changes: Vec<WidgetCommand> = state
+        .rows
+        .iter()
+        .filter(|(id, _)| !rows.iter().any(|(known, _)| known == id))
+        .map(|(id, _)| WidgetCommand::Remove { id: id.clone() })
+        .collect();
+    for (id, command) in &rows {
+        if state.rows.contains(&(id.clone(), command.clone())) {
+            continue;
+        }
+        match parse_widget(command) {
+            Ok(command) if command.id() == id.as_str() => changes.push(command),
+            Ok(command) => warn!(
+                "Widget row '{}' defines a widget with id '{}'",
+                id,
+                command.id()
+            ),
+            Err(err) => warn!("Failed to parse widget row '{}': {}", id, err),
+        }
+    }
+    state.rows = rows;
+    Ok(changes)
+}
+
+#[cfg(not(feature = "widget_db"))]
+fn read_widget_db(
+    _path: &str,
+    _state: &mut WidgetSourceState,
+) -> Result<Vec<WidgetCommand>, String> {
+    Err("the widget DB source needs the `widget_db` feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileWidgets;
+    use crate::ui::widgets::WidgetCommand;
+
+    fn ids(commands: &[WidgetCommand]) -> Vec<String> {
+        commands
+            .iter()
+            .map(|command| match command {
+                WidgetCommand::Remove { id } => format!("-{id}"),
+                command => command.id().to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reload_applies_appended_lines_and_removes_deleted_widgets() {
+        let label = |id: &str| format!(r#"{{"type":"Label","id":"{id}","text":"{id}"}}"#);
+        let mut file = FileWidgets::default();
+
+        let content = [label("a"), label("b")].join("\n");
+        assert_eq!(ids(&file.reload(&content)), ["a", "b"]);
+
+        let content = [label("a"), label("b"), label("c")].join("\n");
+        assert_eq!(ids(&file.reload(&content)), ["c"]);
+
+        let content = [label("a"), label("c")].join("\n");
+        assert_eq!(ids(&file.reload(&content)), ["-b", "a", "c"]);
+        assert_eq!(file.live, ["a", "c"]);
+    }
+
+    #[cfg(feature = "widget_db")]
+    #[test]
+    fn db_rows_apply_changed_widgets_and_remove_deleted_ones() {
+        use bevy::prelude::*;
+        use rusqlite::Connection;
+
+        use super::{WidgetSourceState, read_widget_db};
+        use crate::ui::widgets::{
+            WidgetCommandQueue, WidgetPanel, WidgetRegistry, apply_widget_commands,
+        };
+
+        let label =
+            |id: &str, text: &str| format!(r#"{{"type":"Label","id":"{id}","text":"{text}"}}"#);
+        let path =
+            std::env::temp_dir().join(format!("dimensify_widgets_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let db = Connection::open(&path).unwrap();
+        db.execute(
+            "CREATE TABLE widgets (id TEXT PRIMARY KEY, command TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+        let upsert = |id: &str, text: &str| {
+            db.execute(
+                "INSERT OR REPLACE INTO widgets (id, command) VALUES (?1, ?2)",
+                [id, label(id, text).as_str()],
+            )
+            .unwrap();
+        };
+
+        let mut world = World::new();
+        world.init_resource::<WidgetRegistry>();
+        world.init_resource::<WidgetCommandQueue>();
+        world.init_resource::<WidgetPanel>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(apply_widget_commands);
+        let mut state = WidgetSourceState::default();
+        let path_str = path.to_str().unwrap();
+        let mut poll = |world: &mut World| {
+            let commands = read_widget_db(path_str, &mut state).unwrap();
+            let changed = ids(&commands);
+            for command in commands {
+                world.resource_mut::<WidgetCommandQueue>().push(command);
+            }
+            schedule.run(world);
+            changed
+        };
+
+        upsert("a", "first");
+        upsert("b", "second");
+        assert_eq!(poll(&mut world), ["a", "b"]);
+        assert!(world.resource::<WidgetRegistry>().contains("a"));
+        assert!(world.resource::<WidgetRegistry>().contains("b"));
+
+        // unchanged rows are not applied again
+        assert!(poll(&mut world).is_empty());
+
+        upsert("b", "edited");
+        upsert("c", "third");
+        db.execute("DELETE FROM widgets WHERE id = 'a'", [])
+            .unwrap();
+        assert_eq!(poll(&mut world), ["-a", "b", "c"]);
+        let registry = world.resource::<WidgetRegistry>();
+        assert!(!registry.contains("a"));
+        assert!(registry.contains("b"));
+        assert!(registry.contains("c"));
+        assert_eq!(world.resource::<WidgetPanel>().widgets, ["b", "c"]);
+
+        drop(db);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,17 +1,43 @@
-#[cfg(feature = "protocol")]
-use crate::stream::DataSource;
 use bevy::{
     ecs::{
         query::{QueryFilter, With},
         system::{SystemParam, SystemState},
         world::{Mut, World},
     },
-    prelude::{Entity, Res, ResMut, Resource},
+    prelude::{
+        App, Commands, Entity, IntoScheduleConfigs, Message, MessageWriter, Res, ResMut, Resource,
+        Update,
+    },
     window::PrimaryWindow,
 };
-use bevy_egui::{EguiContext, EguiContexts, egui};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use bevy_egui::{EguiContext, EguiContexts, EguiPrimaryContextPass, egui};
+pub use dimensify_protocol::{WidgetCommand, WidgetTab, WidgetValue};
+use dimensify_ui::{
+    RegisterPanel, UnregisterPanel,
+    plot::line_plot,
+    tabs::{PanelEntry, PanelLocation, PanelRegistry, ViewerTab},
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// The widget panel: registry, command queue, interaction messages and its window.
+pub fn plugin(app: &mut App) {
+    app.init_resource::<WidgetRegistry>()
+        .init_resource::<WidgetCommandQueue>()
+        .init_resource::<WidgetPanel>()
+        .add_message::<WidgetInteraction>()
+        .add_systems(
+            Update,
+            (
+                apply_widget_commands,
+                emit_widget_interactions,
+                dock_widget_panels.after(apply_widget_commands),
+            ),
+        )
+        .add_systems(EguiPrimaryContextPass, widget_panel_ui);
+}
 
 pub trait RootWidgetSystemExt {
     /// Adds a root widget to the primary window.
@@ -259,6 +285,53 @@ where
 #[derive(Resource, Default)]
 pub struct WidgetRegistry {
     widgets: HashMap<String, Box<dyn DynWidget>>,
+    /// Groups, grids, tabs and panels, which show other widgets by id.
+    layouts: HashMap<String, WidgetLayout>,
+    /// Ids of the named panels, in creation order.
+    panels: Vec<String>,
+    /// The command behind each widget created from one, with the values the user set.
+    definitions: Vec<WidgetCommand>,
+    /// Filled by the widget closures while they are shown.
+    interactions: Arc<Mutex<Vec<WidgetInteraction>>>,
+}
+
+/// A user interaction with a widget created from a [`WidgetCommand`].
+#[derive(Message, Debug, Clone, PartialEq)]
+pub enum WidgetInteraction {
+    Clicked { id: String },
+    Toggled { id: String, checked: bool },
+    Changed { id: String, value: WidgetValue },
+}
+
+/// How a container widget arranges its children.
+enum WidgetLayout {
+    Horizontal(Vec<String>),
+    Vertical(Vec<String>),
+    Collapsing {
+        text: String,
+        open: bool,
+        children: Vec<String>,
+    },
+    Grid {
+        columns: usize,
+        children: Vec<String>,
+    },
+    Tabs {
+        tabs: Vec<WidgetTab>,
+        selected: usize,
+    },
+}
+
+impl WidgetLayout {
+    fn children(&self) -> Box<dyn Iterator<Item = &String> + '_> {
+        match self {
+            Self::Horizontal(children)
+            | Self::Vertical(children)
+            | Self::Collapsing { children, .. }
+            | Self::Grid { children, .. } => Box::new(children.iter()),
+            Self::Tabs { tabs, .. } => Box::new(tabs.iter().flat_map(|tab| &tab.children)),
+        }
+    }
 }
 
 impl WidgetRegistry {
@@ -266,64 +339,265 @@ impl WidgetRegistry {
     where
         F: FnMut(&mut egui::Ui) -> egui::Response + Send + Sync + 'static,
     {
-        self.widgets.insert(id.into(), Box::new(widget));
+        let id = id.into();
+        self.layouts.remove(&id);
+        self.panels.retain(|panel| *panel != id);
+        self.widgets.insert(id, Box::new(widget));
     }
 
     pub fn unregister(&mut self, id: &str) -> Option<Box<dyn DynWidget>> {
+        self.definitions.retain(|command| command.id() != id);
+        self.layouts.remove(id);
+        self.panels.retain(|panel| panel != id);
         self.widgets.remove(id)
     }
 
+    fn register_layout(&mut self, id: String, mut layout: WidgetLayout) {
+        // keep the selected tab when the tabs are updated
+        if let (
+            WidgetLayout::Tabs { tabs, selected },
+            Some(WidgetLayout::Tabs {
+                selected: previous, ..
+            }),
+        ) = (&mut layout, self.layouts.get(&id))
+        {
+            *selected = (*previous).min(tabs.len().saturating_sub(1));
+        }
+        self.widgets.remove(&id);
+        self.panels.retain(|panel| *panel != id);
+        self.layouts.insert(id, layout);
+    }
+
+    fn register_panel(&mut self, id: String, children: Vec<String>) {
+        self.register_layout(id.clone(), WidgetLayout::Vertical(children));
+        self.panels.push(id);
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.widgets.contains_key(id) || self.layouts.contains_key(id)
+    }
+
+    /// Ids of the named panels, in creation order.
+    pub fn panels(&self) -> &[String] {
+        &self.panels
+    }
+
+    /// Commands that recreate the widgets made from commands, as the user left them.
+    pub fn definitions(&self) -> &[WidgetCommand] {
+        &self.definitions
+    }
+
+    fn define(&mut self, command: &WidgetCommand) {
+        let id = command.id();
+        if matches!(command, WidgetCommand::Remove { .. }) {
+            self.definitions.retain(|known| known.id() != id);
+        } else if let Some(known) = self.definitions.iter_mut().find(|known| known.id() == id) {
+            *known = command.clone();
+        } else {
+            self.definitions.push(command.clone());
+        }
+    }
+
+    /// Keep the value a user set in the definition of the widget.
+    fn update_definition(&mut self, interaction: &WidgetInteraction) {
+        let id = match interaction {
+            WidgetInteraction::Clicked { .. } => return,
+            WidgetInteraction::Toggled { id, .. } | WidgetInteraction::Changed { id, .. } => id,
+        };
+        let Some(command) = self.definitions.iter_mut().find(|known| known.id() == id) else {
+            return;
+        };
+        match (command, interaction) {
+            (
+                WidgetCommand::Checkbox { checked, .. },
+                WidgetInteraction::Toggled { checked: new, .. },
+            ) => *checked = *new,
+            (
+                WidgetCommand::Slider { value, .. } | WidgetCommand::NumberInput { value, .. },
+                WidgetInteraction::Changed {
+                    value: WidgetValue::Number(new),
+                    ..
+                },
+            ) => *value = *new,
+            (
+                WidgetCommand::Dropdown { selected, .. },
+                WidgetInteraction::Changed {
+                    value: WidgetValue::Selected(new),
+                    ..
+                },
+            ) => *selected = *new,
+            (
+                WidgetCommand::TextInput { value, .. },
+                WidgetInteraction::Changed {
+                    value: WidgetValue::Text(new),
+                    ..
+                },
+            ) => value.clone_from(new),
+            (
+                WidgetCommand::ColorPicker { rgba, .. },
+                WidgetInteraction::Changed {
+                    value: WidgetValue::Color(new),
+                    ..
+                },
+            ) => *rgba = *new,
+            _ => {}
+        }
+    }
+
+    /// Whether the widget belongs in the default window: it is neither a named panel
+    /// nor the child of a container.
+    pub fn is_top_level(&self, id: &str) -> bool {
+        !self.panels.iter().any(|panel| panel == id)
+            && !self
+                .layouts
+                .values()
+                .any(|layout| layout.children().any(|child| child == id))
+    }
+
     pub fn show(&mut self, id: &str, ui: &mut egui::Ui) -> Option<egui::Response> {
+        // taken out while shown, so a container listing itself does not recurse
+        if let Some(mut layout) = self.layouts.remove(id) {
+            let response = self.show_layout(id, &mut layout, ui);
+            self.layouts.insert(id.to_string(), layout);
+            return Some(response);
+        }
         let Some(widget) = self.widgets.get_mut(id) else {
             bevy::log::warn!("WidgetRegistry missing widget '{}'", id);
             return None;
         };
         Some(widget.show(ui))
     }
+
+    fn show_layout(
+        &mut self,
+        id: &str,
+        layout: &mut WidgetLayout,
+        ui: &mut egui::Ui,
+    ) -> egui::Response {
+        match layout {
+            WidgetLayout::Horizontal(children) => {
+                ui.horizontal(|ui| self.show_children(children, ui))
+                    .response
+            }
+            WidgetLayout::Vertical(children) => {
+                ui.vertical(|ui| self.show_children(children, ui)).response
+            }
+            WidgetLayout::Collapsing {
+                text,
+                open,
+                children,
+            } => {
+                egui::CollapsingHeader::new(text.as_str())
+                    .id_salt(id)
+                    .default_open(*open)
+                    .show(ui, |ui| self.show_children(children, ui))
+                    .header_response
+            }
+            WidgetLayout::Grid { columns, children } => {
+                egui::Grid::new(id)
+                    .show(ui, |ui| {
+                        for row in children.chunks((*columns).max(1)) {
+                            for child in row {
+                                if self.contains(child) {
+                                    self.show(child, ui);
+                                } else {
+                                    ui.label("");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    })
+                    .response
+            }
+            WidgetLayout::Tabs { tabs, selected } => {
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        for (index, tab) in tabs.iter().enumerate() {
+                            ui.selectable_value(selected, index, tab.title.as_str());
+                        }
+                    });
+                    ui.separator();
+                    if let Some(tab) = tabs.get(*selected) {
+                        self.show_children(&tab.children, ui);
+                    }
+                })
+                .response
+            }
+        }
+    }
+
+    /// Show the children that exist; a container may arrive before its children.
+    fn show_children(&mut self, children: &[String], ui: &mut egui::Ui) {
+        for child in children {
+            if self.contains(child) {
+                self.show(child, ui);
+            }
+        }
+    }
+
+    fn record(&self) -> impl Fn(WidgetInteraction) + Send + Sync + 'static {
+        let interactions = self.interactions.clone();
+        move |interaction| {
+            if let Ok(mut interactions) = interactions.lock() {
+                interactions.push(interaction);
+            }
+        }
+    }
+
+    fn drain_interactions(&self) -> Vec<WidgetInteraction> {
+        self.interactions
+            .lock()
+            .map(|mut interactions| interactions.drain(..).collect())
+            .unwrap_or_default()
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
-pub enum WidgetCommand {
-    Label {
-        id: String,
-        text: String,
-    },
-    Button {
-        id: String,
-        text: String,
-    },
-    Checkbox {
-        id: String,
-        text: String,
-        checked: bool,
-    },
+/// Read a widget command written with its variant in a `type` field, as in widget
+/// files and the console: `{"type": "Slider", "id": "gain", ...}`.
+pub fn widget_from_json(mut value: serde_json::Value) -> Result<WidgetCommand, String> {
+    let serde_json::Value::Object(fields) = &mut value else {
+        return Err("a widget command must be a JSON object".to_string());
+    };
+    let Some(serde_json::Value::String(kind)) = fields.remove("type") else {
+        return Err("a widget command needs a `type`".to_string());
+    };
+    let tagged = serde_json::Value::Object(serde_json::Map::from_iter([(kind, value)]));
+    serde_json::from_value(tagged).map_err(|err| err.to_string())
 }
 
-#[cfg(feature = "protocol")]
-#[derive(Resource, Clone, Debug)]
-pub struct WidgetStreamSettings {
-    pub source: DataSource,
+/// Write a widget command with its variant in a `type` field, see [`widget_from_json`].
+pub fn widget_to_json(command: &WidgetCommand) -> serde_json::Value {
+    let Ok(serde_json::Value::Object(tagged)) = serde_json::to_value(command) else {
+        unreachable!("widget commands serialize to a map");
+    };
+    let (kind, mut value) = tagged.into_iter().next().expect("one variant");
+    if let serde_json::Value::Object(fields) = &mut value {
+        fields.insert("type".to_string(), serde_json::Value::String(kind));
+    }
+    value
 }
 
-#[cfg(feature = "protocol")]
-impl Default for WidgetStreamSettings {
-    fn default() -> Self {
-        let source = match std::env::var("DIMENSIFY_WIDGET_SOURCE")
-            .unwrap_or_else(|_| "local".to_string())
-            .as_str()
-        {
-            "file" => std::env::var("DIMENSIFY_WIDGET_FILE")
-                .ok()
-                .map(|path| DataSource::FileReplay { path })
-                .unwrap_or(DataSource::Local),
-            "db" => std::env::var("DIMENSIFY_WIDGET_DB_ADDR")
-                .ok()
-                .map(|addr| DataSource::Db { addr })
-                .unwrap_or(DataSource::Local),
-            _ => DataSource::Local,
-        };
-        Self { source }
+/// `serde(with)` adapter storing widget commands in the form of [`widget_to_json`].
+pub mod tagged_widgets {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+    use super::{WidgetCommand, widget_from_json, widget_to_json};
+
+    pub fn serialize<S: Serializer>(
+        commands: &[WidgetCommand],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let values: Vec<_> = commands.iter().map(widget_to_json).collect();
+        values.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<WidgetCommand>, D::Error> {
+        Vec::<serde_json::Value>::deserialize(deserializer)?
+            .into_iter()
+            .map(|value| widget_from_json(value).map_err(D::Error::custom))
+            .collect()
     }
 }
 
@@ -345,6 +619,8 @@ impl WidgetCommandQueue {
 #[derive(Resource, Default)]
 pub struct WidgetPanel {
     pub widgets: Vec<String>,
+    /// Named panels registered as tabs of the docked UI layout.
+    pub docked: Vec<String>,
 }
 
 pub fn register_demo_widgets(mut queue: ResMut<WidgetCommandQueue>) {
@@ -363,112 +639,342 @@ pub fn register_demo_widgets(mut queue: ResMut<WidgetCommandQueue>) {
     });
 }
 
-#[cfg(feature = "protocol")]
-#[cfg(not(target_arch = "wasm32"))]
-pub fn load_widget_commands_from_source(
-    settings: Res<WidgetStreamSettings>,
-    mut queue: ResMut<WidgetCommandQueue>,
-) {
-    match &settings.source {
-        DataSource::FileReplay { path } => {
-            let content = match std::fs::read_to_string(path) {
-                Ok(content) => content,
-                Err(err) => {
-                    bevy::log::error!("Failed to read widget file {}: {}", path, err);
-                    return;
-                }
-            };
-
-            for (line_no, line) in content.lines().enumerate() {
-                if line.trim().is_empty() {
-                    continue;
-                }
-                match serde_json::from_str::<WidgetCommand>(line) {
-                    Ok(command) => queue.push(command),
-                    Err(err) => {
-                        bevy::log::warn!(
-                            "Failed to parse widget command at line {}: {}",
-                            line_no + 1,
-                            err
-                        );
-                    }
-                }
-            }
-        }
-        DataSource::Db { .. } => {
-            bevy::log::warn!("Widget DB source is not implemented yet");
-        }
-        DataSource::Local => {}
-    }
-}
-
+/// Register, replace or remove the widgets of queued commands. A replaced widget keeps
+/// its place in the panel.
 pub fn apply_widget_commands(
     mut registry: ResMut<WidgetRegistry>,
     mut queue: ResMut<WidgetCommandQueue>,
     mut panel: ResMut<WidgetPanel>,
 ) {
     for command in queue.drain() {
-        match command {
+        registry.define(&command);
+        let record = registry.record();
+        let id = match command {
             WidgetCommand::Label { id, text } => {
-                let label_text = text.clone();
                 registry.register(id.clone(), move |ui: &mut egui::Ui| {
-                    ui.add(ELabel::new(label_text.clone()))
+                    ui.add(ELabel::new(text.clone()))
                 });
-                if !panel.widgets.contains(&id) {
-                    panel.widgets.push(id);
-                }
+                id
             }
             WidgetCommand::Button { id, text } => {
-                let button_text = text.clone();
+                let button_id = id.clone();
                 registry.register(id.clone(), move |ui: &mut egui::Ui| {
-                    let response = ui.button(button_text.clone());
+                    let response = ui.button(text.clone());
                     if response.clicked() {
-                        bevy::log::info!("WidgetRegistry button clicked: {}", button_text);
+                        bevy::log::info!("WidgetRegistry button clicked: {}", text);
+                        record(WidgetInteraction::Clicked {
+                            id: button_id.clone(),
+                        });
                     }
                     response
                 });
-                if !panel.widgets.contains(&id) {
-                    panel.widgets.push(id);
-                }
+                id
             }
             WidgetCommand::Checkbox { id, text, checked } => {
-                let checkbox_text = text.clone();
                 let mut value = checked;
+                let checkbox_id = id.clone();
                 registry.register(id.clone(), move |ui: &mut egui::Ui| {
-                    let response = ui.add(ECheckboxButton::new(checkbox_text.clone(), &mut value));
+                    let response = ui.add(ECheckboxButton::new(text.clone(), &mut value));
                     if response.changed() {
-                        bevy::log::info!(
-                            "WidgetRegistry checkbox changed: {} -> {}",
-                            checkbox_text,
-                            value
-                        );
+                        bevy::log::info!("WidgetRegistry checkbox changed: {} -> {}", text, value);
+                        record(WidgetInteraction::Toggled {
+                            id: checkbox_id.clone(),
+                            checked: value,
+                        });
                     }
                     response
                 });
-                if !panel.widgets.contains(&id) {
-                    panel.widgets.push(id);
-                }
+                id
+            }
+            WidgetCommand::Slider {
+                id,
+                text,
+                mut value,
+                min,
+                max,
+            } => {
+                let slider_id = id.clone();
+                registry.register(id.clone(), move |ui: &mut egui::Ui| {
+                    let response =
+                        ui.add(egui::Slider::new(&mut value, min..=max).text(text.as_str()));
+                    if response.changed() {
+                        record(WidgetInteraction::Changed {
+                            id: slider_id.clone(),
+                            value: WidgetValue::Number(value),
+                        });
+                    }
+                    response
+                });
+                id
+            }
+            WidgetCommand::NumberInput {
+                id,
+                text,
+                mut value,
+            } => {
+                let input_id = id.clone();
+                registry.register(id.clone(), move |ui: &mut egui::Ui| {
+                    let response = ui
+                        .horizontal(|ui| {
+                            ui.label(text.as_str());
+                            ui.add(egui::DragValue::new(&mut value))
+                        })
+                        .inner;
+                    if response.changed() {
+                        record(WidgetInteraction::Changed {
+                            id: input_id.clone(),
+                            value: WidgetValue::Number(value),
+                        });
+                    }
+                    response
+                });
+                id
+            }
+            WidgetCommand::Dropdown {
+                id,
+                text,
+                options,
+                mut selected,
+            } => {
+                let dropdown_id = id.clone();
+                registry.register(id.clone(), move |ui: &mut egui::Ui| {
+                    let before = selected;
+                    let response = egui::ComboBox::from_label(text.as_str())
+                        .selected_text(options.get(selected).map_or("", String::as_str))
+                        .show_ui(ui, |ui| {
+                            for (index, option) in options.iter().enumerate() {
+                                ui.selectable_value(&mut selected, index, option.as_str());
+                            }
+                        })
+                        .response;
+                    if selected != before {
+                        record(WidgetInteraction::Changed {
+                            id: dropdown_id.clone(),
+                            value: WidgetValue::Selected(selected),
+                        });
+                    }
+                    response
+                });
+                id
+            }
+            WidgetCommand::TextInput {
+                id,
+                text,
+                mut value,
+            } => {
+                let input_id = id.clone();
+                registry.register(id.clone(), move |ui: &mut egui::Ui| {
+                    let response = ui
+                        .horizontal(|ui| {
+                            ui.label(text.as_str());
+                            ui.text_edit_singleline(&mut value)
+                        })
+                        .inner;
+                    // once per edit rather than per keystroke
+                    if response.lost_focus() {
+                        record(WidgetInteraction::Changed {
+                            id: input_id.clone(),
+                            value: WidgetValue::Text(value.clone()),
+                        });
+                    }
+                    response
+                });
+                id
+            }
+            WidgetCommand::ColorPicker { id, text, mut rgba } => {
+                let picker_id = id.clone();
+                registry.register(id.clone(), move |ui: &mut egui::Ui| {
+                    let response = ui
+                        .horizontal(|ui| {
+                            let response = ui.color_edit_button_rgba_unmultiplied(&mut rgba);
+                            ui.label(text.as_str());
+                            response
+                        })
+                        .inner;
+                    if response.changed() {
+                        record(WidgetInteraction::Changed {
+                            id: picker_id.clone(),
+                            value: WidgetValue::Color(rgba),
+                        });
+                    }
+                    response
+                });
+                id
             }
+            WidgetCommand::Plot { id, text, points } => {
+                registry.register(id.clone(), move |ui: &mut egui::Ui| {
+                    ui.label(text.as_str());
+                    line_plot(ui, &points, 120.0)
+                });
+                id
+            }
+            WidgetCommand::Separator { id } => {
+                registry.register(id.clone(), |ui: &mut egui::Ui| ui.separator());
+                id
+            }
+            WidgetCommand::Horizontal { id, children } => {
+                registry.register_layout(id.clone(), WidgetLayout::Horizontal(children));
+                id
+            }
+            WidgetCommand::Vertical { id, children } => {
+                registry.register_layout(id.clone(), WidgetLayout::Vertical(children));
+                id
+            }
+            WidgetCommand::Collapsing {
+                id,
+                text,
+                open,
+                children,
+            } => {
+                let layout = WidgetLayout::Collapsing {
+                    text,
+                    open,
+                    children,
+                };
+                registry.register_layout(id.clone(), layout);
+                id
+            }
+            WidgetCommand::Grid {
+                id,
+                columns,
+                children,
+            } => {
+                registry.register_layout(id.clone(), WidgetLayout::Grid { columns, children });
+                id
+            }
+            WidgetCommand::Tabs { id, tabs } => {
+                let layout = WidgetLayout::Tabs { tabs, selected: 0 };
+                registry.register_layout(id.clone(), layout);
+                id
+            }
+            WidgetCommand::Panel { id, children } => {
+                panel.widgets.retain(|widget| *widget != id);
+                registry.register_panel(id, children);
+                continue;
+            }
+            WidgetCommand::Remove { id } => {
+                registry.unregister(&id);
+                panel.widgets.retain(|widget| *widget != id);
+                continue;
+            }
+        };
+        if !panel.widgets.contains(&id) {
+            panel.widgets.push(id);
         }
     }
 }
 
-pub fn widget_registry_demo_ui(
+/// Forward widget interactions recorded during the last UI pass as messages.
+pub fn emit_widget_interactions(
+    mut registry: ResMut<WidgetRegistry>,
+    mut interactions: MessageWriter<WidgetInteraction>,
+) {
+    let recorded = registry.drain_interactions();
+    if recorded.is_empty() {
+        return;
+    }
+    for interaction in &recorded {
+        registry.update_definition(interaction);
+    }
+    interactions.write_batch(recorded);
+}
+
+/// Window with the top-level widgets of [`WidgetPanel`], shown while there are any, and
+/// one window per named panel that is not docked.
+pub fn widget_panel_ui(
     mut contexts: EguiContexts,
     mut registry: ResMut<WidgetRegistry>,
     panel: Res<WidgetPanel>,
 ) {
+    let top_level: Vec<String> = panel
+        .widgets
+        .iter()
+        .filter(|id| registry.is_top_level(id))
+        .cloned()
+        .collect();
+    let floating: Vec<String> = registry
+        .panels()
+        .iter()
+        .filter(|id| !panel.docked.contains(*id))
+        .cloned()
+        .collect();
+    if top_level.is_empty() && floating.is_empty() {
+        return;
+    }
     let Ok(ctx) = contexts.ctx_mut() else {
         return;
     };
 
-    egui::Window::new("Widget Registry").show(ctx, |ui| {
-        for id in &panel.widgets {
-            if registry.show(id, ui).is_some() {
-                ui.add_space(4.0);
+    if !top_level.is_empty() {
+        egui::Window::new("Widgets").show(ctx, |ui| {
+            for id in &top_level {
+                if registry.show(id, ui).is_some() {
+                    ui.add_space(4.0);
+                }
             }
+        });
+    }
+    for id in &floating {
+        egui::Window::new(id.as_str())
+            .id(egui::Id::new(("widget_panel", id)))
+            .show(ctx, |ui| registry.show(id, ui));
+    }
+}
+
+/// Register named panels as tabs of the docked UI layout while it is in use, so the
+/// layout file can place them by title. Panels it does not place join the right side;
+/// a panel named like a built-in panel stays in its own window.
+pub fn dock_widget_panels(
+    mut commands: Commands,
+    registry: Res<WidgetRegistry>,
+    layout: Option<Res<PanelRegistry>>,
+    mut panel: ResMut<WidgetPanel>,
+) {
+    let Some(layout) = layout else {
+        return;
+    };
+    let panel = &mut *panel;
+    panel.docked.retain(|id| {
+        let exists = registry.panels().contains(id);
+        if !exists {
+            commands.queue(UnregisterPanel(id.clone()));
         }
+        exists
     });
+    for id in registry.panels() {
+        if panel.docked.contains(id) || layout.contains(id) {
+            continue;
+        }
+        let title = id.clone();
+        commands.queue(RegisterPanel(PanelEntry {
+            title: id.clone().into(),
+            location: PanelLocation::Right,
+            default_enabled: true,
+            factory: Arc::new(move || Box::new(WidgetPanelTab(title.clone()))),
+        }));
+        panel.docked.push(id.clone());
+    }
+}
+
+/// Whether `id` is taken by a panel of the docked UI that is not a widget panel, e.g.
+/// "Console".
+pub fn is_builtin_panel(id: &str, registry: &WidgetRegistry, layout: &PanelRegistry) -> bool {
+    layout.contains(id) && !registry.panels().iter().any(|panel| panel == id)
+}
+
+/// A docked named panel.
+struct WidgetPanelTab(String);
+
+impl ViewerTab for WidgetPanelTab {
+    fn title(&self) -> &str {
+        &self.0
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        if let Some(mut registry) = world.get_resource_mut::<WidgetRegistry>() {
+            registry.show(&self.0, ui);
+        }
+    }
 }
 
 #[must_use = "You should put this widget in an ui with `ui.add(widget);`"]
@@ -1052,10 +1558,15 @@ fn with_opacity(color: egui::Color32, opacity: f32) -> egui::Color32 {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use super::{
-        WidgetCommand, WidgetCommandQueue, WidgetPanel, WidgetRegistry, apply_widget_commands,
+        WidgetCommand, WidgetCommandQueue, WidgetInteraction, WidgetPanel, WidgetRegistry,
+        apply_widget_commands, emit_widget_interactions, is_builtin_panel, widget_from_json,
+        widget_to_json,
     };
-    use bevy::prelude::{Schedule, World};
+    use bevy::prelude::{IntoScheduleConfigs, Messages, Schedule, World};
+    use dimensify_ui::tabs::{ConsoleTab, PanelEntry, PanelLocation, PanelRegistry};
 
     #[test]
     fn apply_widget_commands_registers_widgets() {
@@ -1093,4 +1604,184 @@ mod tests {
         assert!(registry.unregister("button").is_some());
         assert!(registry.unregister("checkbox").is_some());
     }
+
+    #[test]
+    fn removed_widgets_leave_the_panel() {
+        let mut world = World::new();
+        world.insert_resource(WidgetRegistry::default());
+        world.insert_resource(WidgetCommandQueue::default());
+        world.insert_resource(WidgetPanel::default());
+
+        {
+            let mut queue = world.resource_mut::<WidgetCommandQueue>();
+            queue.push(WidgetCommand::Label {
+                id: "label".to_string(),
+                text: "Label".to_string(),
+            });
+            queue.push(WidgetCommand::Slider {
+                id: "slider".to_string(),
+                text: "Slider".to_string(),
+                value: 0.5,
+                min: 0.0,
+                max: 1.0,
+            });
+            queue.push(WidgetCommand::Remove {
+                id: "slider".to_string(),
+            });
+        }
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(apply_widget_commands);
+        schedule.run(&mut world);
+
+        assert_eq!(world.resource::<WidgetPanel>().widgets, vec!["label"]);
+        let mut registry = world.resource_mut::<WidgetRegistry>();
+        assert!(registry.unregister("slider").is_none());
+        assert!(registry.unregister("label").is_some());
+    }
+
+    #[test]
+    fn widget_files_tag_commands_with_their_type() {
+        let line =
+            r#"{"type":"Slider","id":"gain","text":"Gain","value":1.0,"min":0.0,"max":10.0}"#;
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        let command = widget_from_json(value.clone()).unwrap();
+        assert_eq!(
+            command,
+            WidgetCommand::Slider {
+                id: "gain".to_string(),
+                text: "Gain".to_string(),
+                value: 1.0,
+                min: 0.0,
+                max: 10.0,
+            }
+        );
+        assert_eq!(widget_to_json(&command), value);
+        assert!(widget_from_json(serde_json::json!({"id": "gain"})).is_err());
+    }
+
+    #[test]
+    fn definitions_keep_the_values_users_set() {
+        let mut world = World::new();
+        world.insert_resource(WidgetRegistry::default());
+        world.insert_resource(WidgetCommandQueue::default());
+        world.insert_resource(WidgetPanel::default());
+        world.init_resource::<Messages<WidgetInteraction>>();
+        world
+            .resource_mut::<WidgetCommandQueue>()
+            .push(WidgetCommand::Checkbox {
+                id: "enabled".to_string(),
+                text: "Enabled".to_string(),
+                checked: false,
+            });
+        let mut schedule = Schedule::default();
+        schedule.add_systems((apply_widget_commands, emit_widget_interactions).chain());
+        schedule.run(&mut world);
+
+        let record = world.resource::<WidgetRegistry>().record();
+        record(WidgetInteraction::Toggled {
+            id: "enabled".to_string(),
+            checked: true,
+        });
+        schedule.run(&mut world);
+        assert!(matches!(
+            world.resource::<WidgetRegistry>().definitions(),
+            [WidgetCommand::Checkbox { checked: true, .. }]
+        ));
+
+        world
+            .resource_mut::<WidgetCommandQueue>()
+            .push(WidgetCommand::Remove {
+                id: "enabled".to_string(),
+            });
+        schedule.run(&mut world);
+        assert!(world.resource::<WidgetRegistry>().definitions().is_empty());
+    }
+
+    #[test]
+    fn containers_and_panels_own_their_children() {
+        let mut world = World::new();
+        world.insert_resource(WidgetRegistry::default());
+        world.insert_resource(WidgetCommandQueue::default());
+        world.insert_resource(WidgetPanel::default());
+
+        {
+            let mut queue = world.resource_mut::<WidgetCommandQueue>();
+            queue.push(WidgetCommand::Label {
+                id: "gain".to_string(),
+                text: "Gain".to_string(),
+            });
+            queue.push(WidgetCommand::Label {
+                id: "status".to_string(),
+                text: "Status".to_string(),
+            });
+            queue.push(WidgetCommand::Horizontal {
+                id: "row".to_string(),
+                children: vec!["gain".to_string()],
+            });
+            queue.push(WidgetCommand::Panel {
+                id: "Tuning".to_string(),
+                children: vec!["row".to_string()],
+            });
+        }
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(apply_widget_commands);
+        schedule.run(&mut world);
+
+        let registry = world.resource::<WidgetRegistry>();
+        assert_eq!(registry.panels(), ["Tuning".to_string()]);
+        assert!(!registry.is_top_level("gain"));
+        assert!(!registry.is_top_level("row"));
+        assert!(!registry.is_top_level("Tuning"));
+        assert!(registry.is_top_level("status"));
+        assert!(
+            !world
+                .resource::<WidgetPanel>()
+                .widgets
+                .contains(&"Tuning".to_string())
+        );
+
+        world
+            .resource_mut::<WidgetCommandQueue>()
+            .push(WidgetCommand::Remove {
+                id: "row".to_string(),
+            });
+        schedule.run(&mut world);
+        let registry = world.resource::<WidgetRegistry>();
+        assert!(!registry.contains("row"));
+        assert!(registry.is_top_level("gain"));
+    }
+
+    #[test]
+    fn widget_panels_cannot_take_builtin_titles() {
+        let mut layout = PanelRegistry::default();
+        for title in ["Console", "Gains"] {
+            layout.register(PanelEntry {
+                title: title.into(),
+                location: PanelLocation::Bottom,
+                default_enabled: true,
+                factory: Arc::new(|| Box::new(ConsoleTab)),
+            });
+        }
+        let mut world = World::new();
+        world.insert_resource(WidgetRegistry::default());
+        world.insert_resource(WidgetCommandQueue::default());
+        world.insert_resource(WidgetPanel::default());
+        world
+            .resource_mut::<WidgetCommandQueue>()
+            .push(WidgetCommand::Panel {
+                id: "Gains".to_string(),
+                children: Vec::new(),
+            });
+        let mut schedule = Schedule::default();
+        schedule.add_systems(apply_widget_commands);
+        schedule.run(&mut world);
+
+        let registry = world.resource::<WidgetRegistry>();
+        assert!(is_builtin_panel("Console", registry, &layout));
+        // docked widget panels are registered like built-in ones
+        assert!(!is_builtin_panel("Gains", registry, &layout));
+        assert!(!is_builtin_panel("Plots", registry, &layout));
+    }
 }
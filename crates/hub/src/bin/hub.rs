@@ -0,0 +1,30 @@
+//! Standalone hub: controllers and viewers connect to it as clients.
+//!
+//! Configured with the usual `DIMENSIFY_TRANSPORT_*` variables; the connection
+//! is always `server` and the endpoint always `hub`.
+
+use bevy::{log::LogPlugin, prelude::*};
+use dimensify_hub::HubPlugin;
+use dimensify_transport::{
+    TransportConfig, TransportConnection, TransportEndpoint, TransportRuntimePlugin,
+};
+
+fn main() {
+    let mut config = TransportConfig::from_env();
+    config.connection = TransportConnection::Server;
+    config.endpoint = TransportEndpoint::Hub;
+
+    App::new()
+        .add_plugins((MinimalPlugins, LogPlugin::default()))
+        .add_plugins(TransportRuntimePlugin { config })
+        .add_plugins(HubPlugin)
+        .add_systems(Startup, |config: Res<TransportConfig>| {
+            for listener in config.listeners() {
+                info!(
+                    "dimensify hub listening on {} ({:?})",
+                    listener.addr, listener.mode
+                );
+            }
+        })
+        .run();
+}
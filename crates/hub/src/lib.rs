@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "relay")]
+pub mod relay;
+#[cfg(feature = "relay")]
+pub use relay::{HubPlugin, HubState};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum HubMode {
     Server,
@@ -0,0 +1,1043 @@
+//! Relay between controllers and viewers.
+//!
+//! Every peer connects to the hub as a client and announces itself with a
+//! `TransportHello`, read into a [`TransportPeer`]. Peers need the role their
+//! token grants: any role to mirror the scene as a viewer, and the role each
+//! request requires as a controller. Controller requests are forwarded to every viewer; only the
+//! *primary* viewer (the one connected longest) answers back to the controller.
+//!
+//! Each viewer receives at most one request at a time, so responses can be matched
+//! to the request that caused them. Controllers may have many requests in flight;
+//! every request gets a ticket, and responses go back in the order the tickets were
+//! issued. `ApplyCommand`s are kept in an authoritative command log that is replayed
+//! to viewers joining late; despawning an entity drops its commands from the log and
+//! forgets its ids.
+//!
+//! Bulk transfers from controllers are relayed to every viewer as they arrive.
+//!
+//! Entity ids are viewer-local. The hub speaks *canonical* ids with controllers
+//! (the id assigned by the first viewer to answer the `Spawn`) and translates them
+//! for every viewer.
+//!
+//! The hub subscribes every viewer to all [`ViewerEvent`]s and answers
+//! `Subscribe` itself, forwarding events from any viewer to the controllers
+//! subscribed to their kind. `Stats` is answered with the hub's own links.
+//!
+//! Widgets are relayed like commands and replayed to late viewers; their events go
+//! to the controller that created the widget, and its widgets are removed from every
+//! viewer when it leaves.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use bevy::prelude::*;
+use dimensify_protocol::{
+    AccessRole, EntityInfo, MessageChannel, TelemetryBinding, ViewerEvent, ViewerEventKind,
+    WidgetCommand, WorldCommand,
+};
+use dimensify_transport::{
+    LinkStats, PeerStatsData, ProtoRequest, ProtoResponse, StreamBytes, StreamReliable,
+    TransportConfig, TransportEndpoint, TransportPeer, authenticate_peers, peer_stats,
+};
+use lightyear::prelude::{LinkOf, MessageReceiver, MessageSender};
+
+pub struct HubPlugin;
+
+impl Plugin for HubPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HubState>()
+            .register_required_components::<LinkOf, ControllerResponses>()
+            .add_systems(
+                Update,
+                (
+                    register_peers,
+                    remove_departed_widgets,
+                    fail_orphaned_tickets,
+                    receive_controller_requests,
+                    answer_stats_requests,
+                    route_viewer_responses,
+                    route_viewer_events,
+                    send_to_viewers,
+                    relay_bulk_data,
+                    send_controller_responses,
+                )
+                    .chain()
+                    .after(authenticate_peers),
+            );
+    }
+}
+
+/// Authoritative state shared by all peers.
+#[derive(Resource, Default)]
+pub struct HubState {
+    /// `ApplyCommand`s by log index, with canonical entity ids. The history of an entity
+    /// spawned through the hub is dropped when it is despawned.
+    commands: BTreeMap<usize, WorldCommand>,
+    next_command: usize,
+    /// Canonical entity created by the `Spawn` at a command log index.
+    spawned: HashMap<usize, Entity>,
+    canonical_index: HashMap<Entity, usize>,
+    /// Latest telemetry bindings, replayed to late viewers.
+    telemetry_bindings: Option<Vec<TelemetryBinding>>,
+    /// Latest definition of every widget, replayed to late viewers, and its controller.
+    widgets: Vec<(WidgetCommand, Entity)>,
+    /// Controller waiting on each outstanding ticket.
+    open_tickets: HashMap<u64, Entity>,
+    next_ticket: u64,
+    next_join: u64,
+}
+
+impl HubState {
+    fn issue_ticket(&mut self) -> u64 {
+        self.next_ticket += 1;
+        self.next_ticket - 1
+    }
+
+    /// Number of commands in the authoritative log.
+    pub fn command_count(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Append a command to the log and return its index. Despawning an entity spawned
+    /// through the hub instead drops its spawn, every command targeting it and its
+    /// canonical id, so late viewers never replay it.
+    fn log_command(&mut self, command: &WorldCommand) -> Option<usize> {
+        if let WorldCommand::Despawn { entity } = command
+            && let Some(spawn) = self.canonical_index.remove(entity)
+        {
+            self.spawned.remove(&spawn);
+            self.commands
+                .retain(|index, logged| *index != spawn && command_entity(logged) != Some(*entity));
+            return None;
+        }
+        let index = self.next_command;
+        self.next_command += 1;
+        self.commands.insert(index, command.clone());
+        Some(index)
+    }
+
+    fn set_widget(&mut self, command: &WidgetCommand, controller: Entity) {
+        let id = command.id();
+        let existing = self
+            .widgets
+            .iter()
+            .position(|(widget, _)| widget.id() == id);
+        match (command, existing) {
+            (WidgetCommand::Remove { .. }, Some(index)) => {
+                self.widgets.remove(index);
+            }
+            (WidgetCommand::Remove { .. }, None) => {}
+            (command, Some(index)) => self.widgets[index] = (command.clone(), controller),
+            (command, None) => self.widgets.push((command.clone(), controller)),
+        }
+    }
+
+    fn widget_owner(&self, id: &str) -> Option<Entity> {
+        self.widgets
+            .iter()
+            .find(|(widget, _)| widget.id() == id)
+            .map(|(_, controller)| *controller)
+    }
+
+    /// Log index of the `Spawn` that created the entity a command targets.
+    fn spawn_index(&self, command: &WorldCommand) -> Option<usize> {
+        command_entity(command).and_then(|entity| self.canonical_index.get(&entity).copied())
+    }
+
+    fn canonical_to_local(&self, viewer: &HubViewer, entity: Entity) -> Option<Entity> {
+        match self.canonical_index.get(&entity) {
+            Some(index) => viewer.local_by_index.get(index).copied(),
+            // not created through the hub (e.g. part of the viewer's own scene)
+            None => Some(entity),
+        }
+    }
+
+    fn local_to_canonical(&self, viewer: &HubViewer) -> HashMap<Entity, Entity> {
+        viewer
+            .local_by_index
+            .iter()
+            .filter_map(|(index, local)| Some((*local, *self.spawned.get(index)?)))
+            .collect()
+    }
+}
+
+/// A request queued for one viewer, with canonical entity ids.
+#[derive(Clone, Debug)]
+struct Forwarded {
+    request: ProtoRequest,
+    /// Set when a controller is waiting for the answer.
+    ticket: Option<u64>,
+    /// Position of an `ApplyCommand` in the command log.
+    log_index: Option<usize>,
+    /// Log index of the `Spawn` of the entity an `ApplyCommand` targets, looked up when
+    /// queued since a `Despawn` forgets the canonical id before viewers receive it.
+    target: Option<usize>,
+}
+
+/// A connected viewer and the requests it still has to process.
+#[derive(Component, Debug)]
+pub struct HubViewer {
+    joined: u64,
+    queue: VecDeque<Forwarded>,
+    in_flight: Option<Forwarded>,
+    /// Viewer-local entity spawned for each command log index.
+    local_by_index: HashMap<usize, Entity>,
+}
+
+impl HubViewer {
+    /// Rewrite a command targeting the entity spawned at `target` to this viewer's id,
+    /// forgetting the id when the command despawns it.
+    fn localise(&mut self, command: &WorldCommand, target: Option<usize>) -> Option<WorldCommand> {
+        let Some(index) = target else {
+            // not created through the hub (e.g. part of the viewer's own scene)
+            return Some(command.clone());
+        };
+        let local = *self.local_by_index.get(&index)?;
+        if matches!(command, WorldCommand::Despawn { .. }) {
+            self.local_by_index.remove(&index);
+        }
+        localise(command, |_| Some(local))
+    }
+}
+
+/// A connected controller.
+#[derive(Component, Debug)]
+pub struct HubController;
+
+/// Event kinds a controller subscribed to.
+#[derive(Component, Debug, Default)]
+struct HubSubscription(HashSet<ViewerEventKind>);
+
+/// Responses owed to a controller link, keyed by ticket.
+#[derive(Component, Debug, Default)]
+struct ControllerResponses {
+    /// Tickets in the order the requests arrived.
+    order: VecDeque<u64>,
+    ready: HashMap<u64, ProtoResponse>,
+    /// Tickets of `Stats` requests, answered by the hub itself.
+    stats: Vec<u64>,
+}
+
+impl ControllerResponses {
+    fn fill(&mut self, ticket: u64, response: ProtoResponse) {
+        self.ready.insert(ticket, response);
+    }
+
+    /// Take the next response, if every earlier one has been sent.
+    fn pop_ready(&mut self) -> Option<ProtoResponse> {
+        let response = self.ready.remove(self.order.front()?)?;
+        self.order.pop_front();
+        Some(response)
+    }
+}
+
+fn register_peers(
+    mut commands: Commands,
+    mut state: ResMut<HubState>,
+    peers: Query<(Entity, &TransportPeer), (With<LinkOf>, Added<TransportPeer>)>,
+) {
+    for (entity, peer) in &peers {
+        if peer.role.is_none() {
+            continue;
+        }
+        match peer.endpoint {
+            TransportEndpoint::Viewer => {
+                let mut queue: VecDeque<Forwarded> = state
+                    .commands
+                    .iter()
+                    .map(|(index, command)| Forwarded {
+                        request: ProtoRequest::ApplyCommand(command.clone()),
+                        ticket: None,
+                        log_index: Some(*index),
+                        target: state.spawn_index(command),
+                    })
+                    .collect();
+                // events are filtered per controller by the hub
+                queue.push_front(Forwarded {
+                    request: ProtoRequest::Subscribe(ViewerEventKind::ALL.to_vec()),
+                    ticket: None,
+                    log_index: None,
+                    target: None,
+                });
+                if let Some(bindings) = &state.telemetry_bindings {
+                    queue.push_back(Forwarded {
+                        request: ProtoRequest::SetTelemetryBindings(bindings.clone()),
+                        ticket: None,
+                        log_index: None,
+                        target: None,
+                    });
+                }
+                queue.extend(state.widgets.iter().map(|(widget, _)| Forwarded {
+                    request: ProtoRequest::Widget(widget.clone()),
+                    ticket: None,
+                    log_index: None,
+                    target: None,
+                }));
+                info!(
+                    "Viewer {:?} joined the hub; replaying {} commands",
+                    entity,
+                    state.commands.len()
+                );
+                commands.entity(entity).insert(HubViewer {
+                    joined: state.next_join,
+                    queue,
+                    in_flight: None,
+                    local_by_index: HashMap::new(),
+                });
+                state.next_join += 1;
+            }
+            TransportEndpoint::Controller => {
+                info!("Controller {:?} joined the hub", entity);
+                commands.entity(entity).insert(HubController);
+            }
+            TransportEndpoint::Hub => {
+                warn!(
+                    "Hub {:?} connected to this hub; chaining hubs is not supported",
+                    entity
+                );
+            }
+        }
+    }
+}
+
+fn receive_controller_requests(
+    mut commands: Commands,
+    config: Res<TransportConfig>,
+    mut state: ResMut<HubState>,
+    mut controllers: Query<
+        (
+            Entity,
+            &mut MessageReceiver<ProtoRequest>,
+            &mut ControllerResponses,
+            &mut LinkStats,
+            Option<&TransportPeer>,
+        ),
+        (With<LinkOf>, Without<HubViewer>),
+    >,
+    mut viewers: Query<(Entity, &mut HubViewer)>,
+) {
+    let primary = primary_viewer(viewers.iter());
+    for (controller, mut receiver, mut responses, mut stats, peer) in &mut controllers {
+        let role = TransportPeer::role_of(&config, peer);
+        for request in receiver.receive() {
+            stats.received(MessageChannel::Requests);
+            let ticket = state.issue_ticket();
+            responses.order.push_back(ticket);
+            if !role.is_some_and(|role| role.allows(&request)) {
+                warn!(
+                    "Rejected request from {:?} with role {:?}",
+                    controller, role
+                );
+                responses.fill(
+                    ticket,
+                    ProtoResponse::Error {
+                        message: format!(
+                            "permission denied: requires the {:?} role",
+                            AccessRole::required_for(&request)
+                        ),
+                    },
+                );
+                continue;
+            }
+            match request {
+                ProtoRequest::Subscribe(kinds) => {
+                    commands
+                        .entity(controller)
+                        .insert(HubSubscription(kinds.into_iter().collect()));
+                    responses.fill(ticket, ProtoResponse::Ack);
+                    continue;
+                }
+                ProtoRequest::Stats => {
+                    responses.stats.push(ticket);
+                    continue;
+                }
+                _ => {}
+            }
+            let Some(primary) = primary else {
+                responses.fill(
+                    ticket,
+                    ProtoResponse::Error {
+                        message: "no viewer is connected to the hub".to_string(),
+                    },
+                );
+                continue;
+            };
+            state.open_tickets.insert(ticket, controller);
+
+            let target = match &request {
+                ProtoRequest::ApplyCommand(command) => state.spawn_index(command),
+                _ => None,
+            };
+            let log_index = match &request {
+                ProtoRequest::ApplyCommand(command) => state.log_command(command),
+                ProtoRequest::SetTelemetryBindings(bindings) => {
+                    state.telemetry_bindings = Some(bindings.clone());
+                    None
+                }
+                ProtoRequest::Widget(command) => {
+                    state.set_widget(command, controller);
+                    None
+                }
+                ProtoRequest::List
+                | ProtoRequest::Subscribe(_)
+                | ProtoRequest::Stats
+                | ProtoRequest::ExportGltf { .. }
+                | ProtoRequest::LoadUrdf { .. } => None,
+            };
+            // listing and exporting are answered by the primary alone; everything else
+            // changes every viewer
+            let broadcast = !matches!(
+                request,
+                ProtoRequest::List | ProtoRequest::ExportGltf { .. }
+            );
+            for (entity, mut viewer) in &mut viewers {
+                if broadcast || entity == primary {
+                    viewer.queue.push_back(Forwarded {
+                        request: request.clone(),
+                        ticket: Some(ticket),
+                        log_index,
+                        target,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Answer `Stats` with every link of the hub. Requests queued for a viewer but not
+/// yet sent count as pending on that viewer.
+fn answer_stats_requests(
+    links: Query<(PeerStatsData, Option<&HubViewer>)>,
+    mut controllers: Query<&mut ControllerResponses>,
+) {
+    if controllers
+        .iter()
+        .all(|responses| responses.stats.is_empty())
+    {
+        return;
+    }
+    let peers: Vec<_> = links
+        .iter()
+        .map(|(data, viewer)| {
+            let mut stats = peer_stats(data);
+            if let Some(viewer) = viewer {
+                stats.pending_requests += viewer.queue.len() as u64;
+            }
+            stats
+        })
+        .collect();
+    for mut responses in &mut controllers {
+        for ticket in std::mem::take(&mut responses.stats) {
+            responses.fill(
+                ticket,
+                ProtoResponse::Stats {
+                    peers: peers.clone(),
+                },
+            );
+        }
+    }
+}
+
+/// Fail the tickets the primary viewer will not answer after a viewer left, e.g. a
+/// `List` that only the previous primary had queued.
+fn fail_orphaned_tickets(
+    mut removed: RemovedComponents<HubViewer>,
+    mut state: ResMut<HubState>,
+    viewers: Query<(Entity, &HubViewer)>,
+    mut controllers: Query<&mut ControllerResponses, Without<HubViewer>>,
+) {
+    if removed.read().count() == 0 {
+        return;
+    }
+    let primary = primary_viewer(viewers.iter());
+    let answerable: HashSet<u64> = viewers
+        .iter()
+        .filter(|(entity, _)| Some(*entity) == primary)
+        .flat_map(|(_, viewer)| viewer.queue.iter().chain(&viewer.in_flight))
+        .filter_map(|forwarded| forwarded.ticket)
+        .collect();
+    let orphaned: Vec<u64> = state
+        .open_tickets
+        .keys()
+        .filter(|ticket| !answerable.contains(ticket))
+        .copied()
+        .collect();
+    for ticket in orphaned {
+        answer(
+            &mut state,
+            &mut controllers,
+            Some(ticket),
+            ProtoResponse::Error {
+                message: "the viewer answering this request disconnected".to_string(),
+            },
+        );
+    }
+}
+
+/// Send each controller the responses that are ready, in request order.
+fn send_controller_responses(
+    mut controllers: Query<
+        (
+            &mut ControllerResponses,
+            &mut MessageSender<ProtoResponse>,
+            &mut LinkStats,
+        ),
+        Without<HubViewer>,
+    >,
+) {
+    for (mut responses, mut sender, mut stats) in &mut controllers {
+        while let Some(response) = responses.pop_ready() {
+            if let ProtoResponse::Error { .. } = response {
+                stats.error();
+            }
+            sender.send::<StreamReliable>(response);
+            stats.sent(MessageChannel::Responses);
+        }
+    }
+}
+
+/// Remove the widgets of controllers that left from the hub and every viewer.
+fn remove_departed_widgets(
+    mut removed: RemovedComponents<HubController>,
+    mut state: ResMut<HubState>,
+    mut viewers: Query<&mut HubViewer>,
+) {
+    for controller in removed.read() {
+        let (departed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut state.widgets)
+            .into_iter()
+            .partition(|(_, owner)| *owner == controller);
+        state.widgets = kept;
+        for (widget, _) in departed {
+            let remove = ProtoRequest::Widget(WidgetCommand::Remove {
+                id: widget.id().to_string(),
+            });
+            for mut viewer in &mut viewers {
+                viewer.queue.push_back(Forwarded {
+                    request: remove.clone(),
+                    ticket: None,
+                    log_index: None,
+                    target: None,
+                });
+            }
+        }
+    }
+}
+
+/// The viewer that answers controllers: the one connected longest.
+fn primary_viewer<'a>(viewers: impl Iterator<Item = (Entity, &'a HubViewer)>) -> Option<Entity> {
+    viewers
+        .min_by_key(|(_, viewer)| viewer.joined)
+        .map(|(entity, _)| entity)
+}
+
+fn route_viewer_responses(
+    mut state: ResMut<HubState>,
+    mut viewers: Query<(
+        Entity,
+        &mut HubViewer,
+        &mut MessageReceiver<ProtoResponse>,
+        &mut LinkStats,
+    )>,
+    mut controllers: Query<&mut ControllerResponses, Without<HubViewer>>,
+) {
+    let primary = primary_viewer(
+        viewers
+            .iter()
+            .map(|(entity, viewer, _, _)| (entity, viewer)),
+    );
+    for (entity, mut viewer, mut receiver, mut viewer_stats) in &mut viewers {
+        for response in receiver.receive() {
+            viewer_stats.received(MessageChannel::Responses);
+            if let ProtoResponse::Error { .. } = response {
+                viewer_stats.error();
+            }
+            let Some(forwarded) = viewer.in_flight.take() else {
+                warn!("Dropping unsolicited viewer response: {:?}", response);
+                continue;
+            };
+            let response = canonical_response(&mut state, &mut viewer, &forwarded, response);
+
+            let is_primary = Some(entity) == primary;
+            if is_primary {
+                answer(&mut state, &mut controllers, forwarded.ticket, response);
+            }
+        }
+    }
+}
+
+fn route_viewer_events(
+    state: Res<HubState>,
+    mut viewers: Query<(
+        &HubViewer,
+        &mut MessageReceiver<ViewerEvent>,
+        &mut LinkStats,
+    )>,
+    mut controllers: Query<
+        (
+            Option<&HubSubscription>,
+            &mut MessageSender<ViewerEvent>,
+            &mut LinkStats,
+        ),
+        Without<HubViewer>,
+    >,
+) {
+    for (viewer, mut receiver, mut viewer_stats) in &mut viewers {
+        let mut events = receiver.receive().peekable();
+        if events.peek().is_none() {
+            continue;
+        }
+        let canonical = state.local_to_canonical(viewer);
+        for mut event in events {
+            viewer_stats.received(MessageChannel::Events);
+            event.map_entities(|local| canonical.get(&local).copied().unwrap_or(local));
+            let owner = event.widget_id().and_then(|id| state.widget_owner(id));
+            if let Some(owner) = owner {
+                if let Ok((_, mut sender, mut stats)) = controllers.get_mut(owner) {
+                    sender.send::<StreamReliable>(event);
+                    stats.sent(MessageChannel::Events);
+                }
+                continue;
+            }
+            for (subscription, mut sender, mut stats) in &mut controllers {
+                if subscription.is_some_and(|subscription| subscription.0.contains(&event.kind())) {
+                    sender.send::<StreamReliable>(event.clone());
+                    stats.sent(MessageChannel::Events);
+                }
+            }
+        }
+    }
+}
+
+/// Record spawned entities and rewrite viewer-local ids in a response to canonical ids.
+fn canonical_response(
+    state: &mut HubState,
+    viewer: &mut HubViewer,
+    forwarded: &Forwarded,
+    response: ProtoResponse,
+) -> ProtoResponse {
+    match (&forwarded.request, response) {
+        (
+            ProtoRequest::ApplyCommand(WorldCommand::Spawn { .. }),
+            ProtoResponse::CommandResponseEntity(local),
+        ) => {
+            let Some(index) = forwarded.log_index else {
+                return ProtoResponse::CommandResponseEntity(local);
+            };
+            viewer.local_by_index.insert(index, local);
+            // answered after the entity was despawned: kept only to translate the despawn
+            if !state.commands.contains_key(&index) {
+                return ProtoResponse::CommandResponseEntity(local);
+            }
+            let canonical = *state.spawned.entry(index).or_insert(local);
+            state.canonical_index.entry(canonical).or_insert(index);
+            ProtoResponse::CommandResponseEntity(canonical)
+        }
+        (ProtoRequest::ApplyCommand(command), ProtoResponse::CommandResponseEntity(local)) => {
+            ProtoResponse::CommandResponseEntity(command_entity(command).unwrap_or(local))
+        }
+        (ProtoRequest::List, ProtoResponse::Entities { entities }) => {
+            let canonical = state.local_to_canonical(viewer);
+            ProtoResponse::Entities {
+                entities: entities
+                    .into_iter()
+                    .map(|info| {
+                        let id = Entity::try_from_bits(info.id)
+                            .and_then(|local| canonical.get(&local))
+                            .map_or(info.id, |entity| entity.to_bits());
+                        EntityInfo { id, ..info }
+                    })
+                    .collect(),
+            }
+        }
+        (_, response) => response,
+    }
+}
+
+fn command_entity(command: &WorldCommand) -> Option<Entity> {
+    match command {
+        WorldCommand::Insert { entity, .. }
+        | WorldCommand::Update { entity, .. }
+        | WorldCommand::Remove { entity, .. }
+        | WorldCommand::Despawn { entity } => Some(*entity),
+        WorldCommand::Spawn { .. } | WorldCommand::Clear => None,
+    }
+}
+
+/// Rewrite canonical ids in a command to a viewer's local ids.
+fn localise(
+    command: &WorldCommand,
+    to_local: impl Fn(Entity) -> Option<Entity>,
+) -> Option<WorldCommand> {
+    let mut command = command.clone();
+    match &mut command {
+        WorldCommand::Insert { entity, .. }
+        | WorldCommand::Update { entity, .. }
+        | WorldCommand::Remove { entity, .. }
+        | WorldCommand::Despawn { entity } => *entity = to_local(*entity)?,
+        WorldCommand::Spawn { .. } | WorldCommand::Clear => {}
+    }
+    Some(command)
+}
+
+fn send_to_viewers(
+    mut state: ResMut<HubState>,
+    mut viewers: Query<(
+        Entity,
+        &mut HubViewer,
+        &mut MessageSender<ProtoRequest>,
+        &mut LinkStats,
+    )>,
+    mut controllers: Query<&mut ControllerResponses, Without<HubViewer>>,
+) {
+    let primary = primary_viewer(
+        viewers
+            .iter()
+            .map(|(entity, viewer, _, _)| (entity, viewer)),
+    );
+    for (entity, mut viewer, mut sender, mut stats) in &mut viewers {
+        while viewer.in_flight.is_none() {
+            let Some(forwarded) = viewer.queue.pop_front() else {
+                break;
+            };
+            let request = match &forwarded.request {
+                ProtoRequest::ApplyCommand(command) => {
+                    match viewer.localise(command, forwarded.target) {
+                        Some(command) => ProtoRequest::ApplyCommand(command),
+                        None => {
+                            warn!(
+                                "Viewer {:?} does not know the entity in {:?}; skipping",
+                                entity, command
+                            );
+                            if Some(entity) == primary {
+                                answer(
+                                    &mut state,
+                                    &mut controllers,
+                                    forwarded.ticket,
+                                    ProtoResponse::Error {
+                                        message: format!("unknown entity in {:?}", command),
+                                    },
+                                );
+                            }
+                            continue;
+                        }
+                    }
+                }
+                request => request.clone(),
+            };
+            sender.send::<StreamReliable>(request);
+            stats.sent(MessageChannel::Requests);
+            viewer.in_flight = Some(forwarded);
+        }
+    }
+}
+
+/// Relayed bulk transfers by controller link and transfer: the id the hub sends them
+/// under and the viewers that got the first chunk.
+#[derive(Default)]
+struct BulkRelay {
+    next_transfer: u64,
+    transfers: HashMap<(Entity, u64), (u64, Vec<Entity>)>,
+}
+
+/// Forward bulk chunks from controllers to every viewer, with the target entity in the
+/// viewer's ids. A viewer that has not spawned the target yet misses the transfer.
+fn relay_bulk_data(
+    config: Res<TransportConfig>,
+    state: Res<HubState>,
+    mut relay: Local<BulkRelay>,
+    mut controllers: Query<
+        (
+            Entity,
+            &mut MessageReceiver<StreamBytes>,
+            &mut LinkStats,
+            Option<&TransportPeer>,
+        ),
+        (With<HubController>, Without<HubViewer>),
+    >,
+    mut viewers: Query<
+        (
+            Entity,
+            &HubViewer,
+            &mut MessageSender<StreamBytes>,
+            &mut LinkStats,
+        ),
+        Without<HubController>,
+    >,
+) {
+    for (controller, mut receiver, mut stats, peer) in &mut controllers {
+        let allowed = TransportPeer::role_of(&config, peer)
+            .is_some_and(|role| role >= AccessRole::Controller);
+        for chunk in receiver.receive() {
+            stats.received(MessageChannel::Bulk);
+            if !allowed {
+                stats.error();
+                warn!(
+                    "Rejected bulk data from {:?}: requires the Controller role",
+                    controller
+                );
+                continue;
+            }
+            let key = (controller, chunk.transfer);
+            let last = chunk.index + 1 >= chunk.count;
+            if let Some(header) = &chunk.header {
+                let transfer = relay.next_transfer;
+                relay.next_transfer += 1;
+                let mut targets = Vec::new();
+                for (viewer_entity, viewer, mut sender, mut viewer_stats) in &mut viewers {
+                    let Some(local) = state.canonical_to_local(viewer, header.entity) else {
+                        warn!(
+                            "Viewer {:?} has not spawned {:?} yet; skipping its bulk data",
+                            viewer_entity, header.entity
+                        );
+                        continue;
+                    };
+                    let mut header = header.clone();
+                    header.entity = local;
+                    sender.send::<StreamReliable>(StreamBytes {
+                        transfer,
+                        header: Some(header),
+                        ..chunk.clone()
+                    });
+                    viewer_stats.sent(MessageChannel::Bulk);
+                    targets.push(viewer_entity);
+                }
+                relay.transfers.insert(key, (transfer, targets));
+            } else if let Some((transfer, targets)) = relay.transfers.get(&key) {
+                for target in targets {
+                    if let Ok((_, _, mut sender, mut viewer_stats)) = viewers.get_mut(*target) {
+                        sender.send::<StreamReliable>(StreamBytes {
+                            transfer: *transfer,
+                            ..chunk.clone()
+                        });
+                        viewer_stats.sent(MessageChannel::Bulk);
+                    }
+                }
+            } else {
+                stats.error();
+                warn!(
+                    "Dropping a chunk of unknown bulk transfer {} from {:?}",
+                    chunk.transfer, controller
+                );
+            }
+            if last {
+                relay.transfers.remove(&key);
+            }
+        }
+    }
+    relay
+        .transfers
+        .retain(|(controller, _), _| controllers.contains(*controller));
+}
+
+fn answer(
+    state: &mut HubState,
+    controllers: &mut Query<&mut ControllerResponses, Without<HubViewer>>,
+    ticket: Option<u64>,
+    response: ProtoResponse,
+) {
+    let Some(ticket) = ticket else {
+        return;
+    };
+    let Some(controller) = state.open_tickets.remove(&ticket) else {
+        return;
+    };
+    if let Ok(mut responses) = controllers.get_mut(controller) {
+        responses.fill(ticket, response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ControllerResponses, Forwarded, HubState, HubViewer, canonical_response, localise,
+    };
+    use bevy::prelude::Entity;
+    use dimensify_protocol::WidgetCommand;
+    use dimensify_protocol::WorldCommand;
+    use dimensify_transport::{ProtoRequest, ProtoResponse};
+    use std::collections::{HashMap, VecDeque};
+
+    fn viewer(joined: u64) -> HubViewer {
+        HubViewer {
+            joined,
+            queue: VecDeque::new(),
+            in_flight: None,
+            local_by_index: HashMap::new(),
+        }
+    }
+
+    fn spawn_at(index: usize) -> Forwarded {
+        Forwarded {
+            request: ProtoRequest::ApplyCommand(WorldCommand::Spawn {
+                components: Vec::new(),
+            }),
+            ticket: None,
+            log_index: Some(index),
+            target: None,
+        }
+    }
+
+    #[test]
+    fn late_viewer_ids_are_translated() {
+        let mut state = HubState::default();
+        let mut primary = viewer(0);
+        let mut late = viewer(1);
+        let canonical = Entity::from_raw_u32(10).unwrap();
+        let local = Entity::from_raw_u32(42).unwrap();
+        let spawn = WorldCommand::Spawn {
+            components: Vec::new(),
+        };
+        assert_eq!(state.log_command(&spawn), Some(0));
+
+        let response = canonical_response(
+            &mut state,
+            &mut primary,
+            &spawn_at(0),
+            ProtoResponse::CommandResponseEntity(canonical),
+        );
+        assert!(matches!(response, ProtoResponse::CommandResponseEntity(e) if e == canonical));
+
+        // the late viewer spawns the same log entry under a different id
+        let response = canonical_response(
+            &mut state,
+            &mut late,
+            &spawn_at(0),
+            ProtoResponse::CommandResponseEntity(local),
+        );
+        assert!(matches!(response, ProtoResponse::CommandResponseEntity(e) if e == canonical));
+
+        let despawn = WorldCommand::Despawn { entity: canonical };
+        let localised = localise(&despawn, |e| state.canonical_to_local(&late, e)).unwrap();
+        assert!(matches!(localised, WorldCommand::Despawn { entity } if entity == local));
+    }
+
+    #[test]
+    fn despawned_entities_are_forgotten() {
+        let mut state = HubState::default();
+        let mut primary = viewer(0);
+        let mut late = viewer(1);
+        let canonical = Entity::from_raw_u32(10).unwrap();
+        let local = Entity::from_raw_u32(42).unwrap();
+        let spawn = WorldCommand::Spawn {
+            components: Vec::new(),
+        };
+        assert_eq!(state.log_command(&spawn), Some(0));
+        canonical_response(
+            &mut state,
+            &mut primary,
+            &spawn_at(0),
+            ProtoResponse::CommandResponseEntity(canonical),
+        );
+
+        let despawn = WorldCommand::Despawn { entity: canonical };
+        let target = state.spawn_index(&despawn);
+        assert_eq!(target, Some(0));
+        assert_eq!(state.log_command(&despawn), None);
+        assert!(state.spawned.is_empty());
+        assert!(state.canonical_index.is_empty());
+
+        // the late viewer answers its spawn after the despawn was logged
+        let response = canonical_response(
+            &mut state,
+            &mut late,
+            &spawn_at(0),
+            ProtoResponse::CommandResponseEntity(local),
+        );
+        assert!(matches!(response, ProtoResponse::CommandResponseEntity(e) if e == local));
+        assert!(state.spawned.is_empty());
+
+        for (viewer, entity) in [(&mut primary, canonical), (&mut late, local)] {
+            let localised = viewer.localise(&despawn, target).unwrap();
+            assert!(matches!(localised, WorldCommand::Despawn { entity: e } if e == entity));
+            assert!(viewer.local_by_index.is_empty());
+        }
+    }
+
+    #[test]
+    fn controller_responses_keep_request_order() {
+        let mut responses = ControllerResponses::default();
+        responses.order.extend([3, 4]);
+        responses.fill(4, ProtoResponse::Ack);
+        assert!(responses.pop_ready().is_none());
+
+        responses.fill(
+            3,
+            ProtoResponse::Entities {
+                entities: Vec::new(),
+            },
+        );
+        assert!(matches!(
+            responses.pop_ready(),
+            Some(ProtoResponse::Entities { .. })
+        ));
+        assert!(matches!(responses.pop_ready(), Some(ProtoResponse::Ack)));
+        assert!(responses.pop_ready().is_none());
+    }
+
+    #[test]
+    fn widgets_keep_latest_definition() {
+        let mut state = HubState::default();
+        let controller = Entity::from_raw_u32(7).unwrap();
+        let slider = |value| WidgetCommand::Slider {
+            id: "gain".to_string(),
+            text: "Gain".to_string(),
+            value,
+            min: 0.0,
+            max: 1.0,
+        };
+        state.set_widget(&slider(0.2), controller);
+        state.set_widget(&slider(0.5), controller);
+        assert_eq!(state.widgets, vec![(slider(0.5), controller)]);
+        assert_eq!(state.widget_owner("gain"), Some(controller));
+
+        state.set_widget(
+            &WidgetCommand::Remove {
+                id: "gain".to_string(),
+            },
+            controller,
+        );
+        assert!(state.widgets.is_empty());
+    }
+
+    #[test]
+    fn despawned_entities_leave_the_command_log() {
+        let mut state = HubState::default();
+        let mut primary = viewer(0);
+        let kept = Entity::from_raw_u32(10).unwrap();
+        let gone = Entity::from_raw_u32(11).unwrap();
+        for (entity, index) in [(kept, 0), (gone, 1)] {
+            let spawn = WorldCommand::Spawn {
+                components: Vec::new(),
+            };
+            assert_eq!(state.log_command(&spawn), Some(index));
+            canonical_response(
+                &mut state,
+                &mut primary,
+                &spawn_at(index),
+                ProtoResponse::CommandResponseEntity(entity),
+            );
+        }
+        for entity in [kept, gone] {
+            state.log_command(&WorldCommand::Insert {
+                entity,
+                components: Vec::new(),
+            });
+        }
+        assert_eq!(state.command_count(), 4);
+
+        assert_eq!(
+            state.log_command(&WorldCommand::Despawn { entity: gone }),
+            None
+        );
+        assert_eq!(state.command_count(), 2);
+        assert!(state.commands.values().all(|command| match command {
+            WorldCommand::Insert { entity, .. } => *entity == kept,
+            command => matches!(command, WorldCommand::Spawn { .. }),
+        }));
+        // indices stay stable for viewers still replaying the log
+        assert_eq!(
+            state.log_command(&WorldCommand::Despawn { entity: kept }),
+            None
+        );
+        assert_eq!(state.log_command(&WorldCommand::Clear), Some(4));
+    }
+}
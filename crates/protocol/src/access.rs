@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ProtoRequest, WorldCommand};
+
+/// What a transport connection may do. Each role includes the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AccessRole {
+    /// Read-only: list entities, subscribe to events and query statistics.
+    Observer,
+    /// Change the scene, telemetry bindings and widgets.
+    Controller,
+    /// Also clear the scene, export it to files and load robots from files on the
+    /// viewer's machine.
+    Admin,
+}
+
+impl AccessRole {
+    /// Role a connection needs to send `request`.
+    pub fn required_for(request: &ProtoRequest) -> Self {
+        match request {
+            ProtoRequest::List | ProtoRequest::Subscribe(_) | ProtoRequest::Stats => Self::Observer,
+            ProtoRequest::ApplyCommand(WorldCommand::Clear)
+            | ProtoRequest::ExportGltf { .. }
+            | ProtoRequest::LoadUrdf { .. } => Self::Admin,
+            ProtoRequest::ApplyCommand(_)
+            | ProtoRequest::SetTelemetryBindings(_)
+            | ProtoRequest::Widget(_) => Self::Controller,
+        }
+    }
+
+    /// Whether this role may send `request`.
+    pub fn allows(self, request: &ProtoRequest) -> bool {
+        self >= Self::required_for(request)
+    }
+}
+
+impl TryFrom<&str> for AccessRole {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "observer" => Ok(Self::Observer),
+            "controller" => Ok(Self::Controller),
+            "admin" => Ok(Self::Admin),
+            _ => Err(format!("unknown access role '{}'", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AccessRole;
+    use crate::{ProtoRequest, WorldCommand};
+
+    #[test]
+    fn roles_include_the_ones_before_them() {
+        let load = ProtoRequest::LoadUrdf {
+            path: "robot.urdf".to_string(),
+            fixed_base: false,
+        };
+        let clear = ProtoRequest::ApplyCommand(WorldCommand::Clear);
+        let spawn = ProtoRequest::ApplyCommand(WorldCommand::Spawn {
+            components: Vec::new(),
+        });
+
+        assert!(AccessRole::Observer.allows(&ProtoRequest::List));
+        assert!(!AccessRole::Observer.allows(&spawn));
+        assert!(AccessRole::Controller.allows(&spawn));
+        assert!(!AccessRole::Controller.allows(&clear));
+        assert!(!AccessRole::Controller.allows(&load));
+        assert!(AccessRole::Admin.allows(&clear));
+        assert!(AccessRole::Admin.allows(&load));
+    }
+}
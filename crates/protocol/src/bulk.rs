@@ -0,0 +1,230 @@
+use bevy_ecs::entity::Entity;
+use serde::{Deserialize, Serialize};
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+use crate::{PodVec3, PodVec4, TransportError};
+
+/// Compression of the bytes of a bulk transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BulkCompression {
+    #[default]
+    None,
+    Zstd,
+    Lz4,
+}
+
+/// Kind of [`BulkData`], announced ahead of its bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BulkKind {
+    Mesh,
+    PointCloud,
+    Image,
+}
+
+/// Describes a bulk transfer; sent with its first chunk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulkHeader {
+    /// Entity the data is inserted on.
+    pub entity: Entity,
+    pub kind: BulkKind,
+    pub compression: BulkCompression,
+    /// Length of the encoded bytes before compression.
+    pub len: u64,
+}
+
+/// Geometry or pixels too large for a `WorldCommand`, sent as raw POD buffers
+/// instead of serde-encoded values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkData {
+    /// Triangle mesh. `normals` is empty or one per position; `indices` is empty
+    /// for a non-indexed mesh.
+    Mesh {
+        positions: Vec<PodVec3>,
+        normals: Vec<PodVec3>,
+        indices: Vec<u32>,
+    },
+    /// Points with no colors, or one RGBA color per point.
+    PointCloud {
+        positions: Vec<PodVec3>,
+        colors: Vec<PodVec4>,
+    },
+    /// RGBA8 pixels, row by row.
+    Image {
+        width: u32,
+        height: u32,
+        rgba8: Vec<u8>,
+    },
+}
+
+impl BulkData {
+    pub fn kind(&self) -> BulkKind {
+        match self {
+            Self::Mesh { .. } => BulkKind::Mesh,
+            Self::PointCloud { .. } => BulkKind::PointCloud,
+            Self::Image { .. } => BulkKind::Image,
+        }
+    }
+
+    /// Encode as a sequence of buffers, each prefixed with its element count.
+    /// Values are little-endian, as on every platform we run on.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match self {
+            Self::Mesh {
+                positions,
+                normals,
+                indices,
+            } => {
+                write_buffer(&mut bytes, positions);
+                write_buffer(&mut bytes, normals);
+                write_buffer(&mut bytes, indices);
+            }
+            Self::PointCloud { positions, colors } => {
+                write_buffer(&mut bytes, positions);
+                write_buffer(&mut bytes, colors);
+            }
+            Self::Image {
+                width,
+                height,
+                rgba8,
+            } => {
+                write_buffer(&mut bytes, &[*width, *height]);
+                write_buffer(&mut bytes, rgba8);
+            }
+        }
+        bytes
+    }
+
+    /// Decode and validate bytes produced by [`BulkData::encode`].
+    pub fn decode(kind: BulkKind, bytes: &[u8]) -> Result<Self, TransportError> {
+        let mut reader = Reader(bytes);
+        let data = match kind {
+            BulkKind::Mesh => Self::Mesh {
+                positions: reader.buffer()?,
+                normals: reader.buffer()?,
+                indices: reader.buffer()?,
+            },
+            BulkKind::PointCloud => Self::PointCloud {
+                positions: reader.buffer()?,
+                colors: reader.buffer()?,
+            },
+            BulkKind::Image => {
+                let size: Vec<u32> = reader.buffer()?;
+                let [width, height] = size[..] else {
+                    return Err(invalid("image size must be two values"));
+                };
+                Self::Image {
+                    width,
+                    height,
+                    rgba8: reader.buffer()?,
+                }
+            }
+        };
+        if !reader.0.is_empty() {
+            return Err(invalid("trailing bytes"));
+        }
+        data.validate()?;
+        Ok(data)
+    }
+
+    pub fn validate(&self) -> Result<(), TransportError> {
+        match self {
+            Self::Mesh {
+                positions,
+                normals,
+                indices,
+            } => {
+                if !normals.is_empty() && normals.len() != positions.len() {
+                    return Err(invalid("mesh needs one normal per position"));
+                }
+                let vertices = if indices.is_empty() {
+                    positions.len()
+                } else {
+                    indices.len()
+                };
+                if vertices % 3 != 0 {
+                    return Err(invalid("mesh vertices do not form triangles"));
+                }
+                if indices
+                    .iter()
+                    .any(|index| *index as usize >= positions.len())
+                {
+                    return Err(invalid("mesh index out of range"));
+                }
+            }
+            Self::PointCloud { positions, colors } => {
+                if !colors.is_empty() && colors.len() != positions.len() {
+                    return Err(invalid("point cloud needs one color per point"));
+                }
+            }
+            Self::Image {
+                width,
+                height,
+                rgba8,
+            } => {
+                if rgba8.len() as u64 != *width as u64 * *height as u64 * 4 {
+                    return Err(invalid("image is not width * height RGBA8 pixels"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid(message: &str) -> TransportError {
+    TransportError::InvalidBulk(message.to_string())
+}
+
+fn write_buffer<T: IntoBytes + Immutable>(bytes: &mut Vec<u8>, values: &[T]) {
+    bytes.extend_from_slice(&(values.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(values.as_bytes());
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], TransportError> {
+        if self.0.len() < len {
+            return Err(invalid("truncated buffer"));
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn buffer<T: FromBytes>(&mut self) -> Result<Vec<T>, TransportError> {
+        let count = u64::from_le_bytes(self.take(8)?.try_into().expect("eight bytes"));
+        let len = usize::try_from(count)
+            .ok()
+            .and_then(|count| count.checked_mul(size_of::<T>()))
+            .ok_or_else(|| invalid("buffer too large"))?;
+        // the bytes are not aligned for `T`, so read element by element
+        Ok(self
+            .take(len)?
+            .chunks_exact(size_of::<T>())
+            .map(|chunk| T::read_from_bytes(chunk).expect("chunk has the size of T"))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BulkData, BulkKind};
+
+    #[test]
+    fn mesh_round_trips_through_bytes() {
+        let mesh = BulkData::Mesh {
+            positions: vec![
+                [0.0, 0.0, 0.0].into(),
+                [1.0, 0.0, 0.0].into(),
+                [0.0, 1.0, 0.0].into(),
+            ],
+            normals: Vec::new(),
+            indices: vec![0, 1, 2],
+        };
+
+        let bytes = mesh.encode();
+        assert_eq!(BulkData::decode(BulkKind::Mesh, &bytes).unwrap(), mesh);
+        assert!(BulkData::decode(BulkKind::Mesh, &bytes[..bytes.len() - 1]).is_err());
+    }
+}
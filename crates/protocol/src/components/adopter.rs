@@ -50,6 +50,9 @@ pub enum ProtoComponent {
     },
     Mesh3d(Shape3d),
     MeshMaterial3d(Material),
+    /// Child entities spawned with these components; their transforms are relative to
+    /// this entity.
+    Children(Vec<Vec<ProtoComponent>>),
 }
 
 #[cfg(feature = "bevy")]
@@ -84,6 +87,7 @@ pub mod bevy_impls {
                 ProtoComponent::MeshMaterial3d(material) => {
                     InsertionResult::RequireResMaterial(material)
                 }
+                ProtoComponent::Children(children) => InsertionResult::SpawnChildren(children),
             }
         }
     }
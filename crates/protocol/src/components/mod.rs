@@ -5,7 +5,7 @@ mod custom;
 pub mod bevy_impls {
 
     pub use super::adopter::bevy_impls::*;
-    use crate::components::prelude::{Material, Shape3d};
+    use crate::components::prelude::{Material, ProtoComponent, Shape3d};
     use bevy::{asset::Assets, mesh::Mesh, pbr::StandardMaterial};
 
     /// A wrapper type to indicate the insertion result.
@@ -13,6 +13,7 @@ pub mod bevy_impls {
         Trivial(&'a mut bevy::prelude::EntityCommands<'b>),
         RequireResMesh(Shape3d),
         RequireResMaterial(Material),
+        SpawnChildren(Vec<Vec<ProtoComponent>>),
     }
 
     /// Insert a ProtoComponent into a Bevy entity.
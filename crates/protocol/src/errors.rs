@@ -10,6 +10,20 @@ pub enum TransportError {
     UdpFeatureRequired,
     #[error("invalid transport connection: {0}")]
     InvalidConnection(String),
+    #[error("invalid listen endpoint: {0}")]
+    InvalidListener(String),
+    #[error("invalid access token: {0}")]
+    InvalidAccessToken(String),
+    #[error("invalid bulk data: {0}")]
+    InvalidBulk(String),
+    #[error("{0} compression is not enabled in this build")]
+    CompressionUnavailable(String),
     #[error("invalid command: {0}")]
     InvalidCommand(String),
+    #[error("not connected: {0}")]
+    NotConnected(String),
+    #[error("no response within {0:?}")]
+    Timeout(std::time::Duration),
+    #[error("transport closed: {0}")]
+    Closed(String),
 }
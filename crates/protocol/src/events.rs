@@ -0,0 +1,181 @@
+use bevy_ecs::entity::Entity;
+use bevy_math::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::WidgetValue;
+
+/// Kinds of [`ViewerEvent`] a controller can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ViewerEventKind {
+    Picking,
+    Selection,
+    TransformEdit,
+    Widget,
+    Collision,
+}
+
+impl ViewerEventKind {
+    pub const ALL: [Self; 5] = [
+        Self::Picking,
+        Self::Selection,
+        Self::TransformEdit,
+        Self::Widget,
+        Self::Collision,
+    ];
+}
+
+/// Mouse button of a pick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PickButton {
+    Primary,
+    Secondary,
+    Middle,
+}
+
+/// Something that happened in a viewer, pushed to the controllers subscribed to its kind
+/// (see `ProtoRequest::Subscribe`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ViewerEvent {
+    /// An entity was clicked. `position` is the hit point in world space.
+    Picked {
+        entity: Entity,
+        position: Option<Vec3>,
+        button: PickButton,
+    },
+    /// The selected entity changed.
+    Selected { entity: Option<Entity> },
+    /// The transform of a protocol-spawned entity was changed in the viewer rather
+    /// than through a `WorldCommand`.
+    TransformEdited {
+        entity: Entity,
+        translation: Vec3,
+        rotation: Quat,
+        scale: Vec3,
+    },
+    /// A widget button was clicked.
+    WidgetClicked { id: String },
+    /// A widget checkbox was toggled.
+    WidgetToggled { id: String, checked: bool },
+    /// The value of a slider, number input, dropdown, text input or colour picker changed.
+    WidgetChanged { id: String, value: WidgetValue },
+    /// The set of entities a robot link collides with changed; empty once it is clear.
+    CollisionChanged {
+        entity: Entity,
+        colliding_with: Vec<Entity>,
+    },
+}
+
+impl ViewerEvent {
+    pub fn kind(&self) -> ViewerEventKind {
+        match self {
+            Self::Picked { .. } => ViewerEventKind::Picking,
+            Self::Selected { .. } => ViewerEventKind::Selection,
+            Self::TransformEdited { .. } => ViewerEventKind::TransformEdit,
+            Self::WidgetClicked { .. }
+            | Self::WidgetToggled { .. }
+            | Self::WidgetChanged { .. } => ViewerEventKind::Widget,
+            Self::CollisionChanged { .. } => ViewerEventKind::Collision,
+        }
+    }
+
+    /// Id of the widget a widget event is about.
+    pub fn widget_id(&self) -> Option<&str> {
+        match self {
+            Self::WidgetClicked { id }
+            | Self::WidgetToggled { id, .. }
+            | Self::WidgetChanged { id, .. } => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Rewrite every entity id in the event, e.g. from viewer-local to canonical ids.
+    pub fn map_entities(&mut self, mut f: impl FnMut(Entity) -> Entity) {
+        match self {
+            Self::Picked { entity, .. } | Self::TransformEdited { entity, .. } => {
+                *entity = f(*entity)
+            }
+            Self::Selected { entity } => {
+                if let Some(entity) = entity {
+                    *entity = f(*entity);
+                }
+            }
+            Self::CollisionChanged {
+                entity,
+                colliding_with,
+            } => {
+                *entity = f(*entity);
+                for other in colliding_with {
+                    *other = f(*other);
+                }
+            }
+            Self::WidgetClicked { .. }
+            | Self::WidgetToggled { .. }
+            | Self::WidgetChanged { .. } => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::entity::Entity;
+    use bevy_math::Vec3;
+
+    use super::{PickButton, ViewerEvent, ViewerEventKind};
+    use crate::WidgetValue;
+
+    fn entity(index: u32) -> Entity {
+        Entity::from_raw_u32(index).unwrap()
+    }
+
+    #[test]
+    fn widget_events_share_one_kind() {
+        let events = [
+            ViewerEvent::WidgetClicked { id: "go".into() },
+            ViewerEvent::WidgetToggled {
+                id: "grid".into(),
+                checked: true,
+            },
+            ViewerEvent::WidgetChanged {
+                id: "speed".into(),
+                value: WidgetValue::Number(0.5),
+            },
+        ];
+        for event in &events {
+            assert_eq!(event.kind(), ViewerEventKind::Widget);
+        }
+        assert_eq!(
+            ViewerEvent::Selected { entity: None }.kind(),
+            ViewerEventKind::Selection
+        );
+    }
+
+    #[test]
+    fn map_entities_rewrites_every_entity() {
+        let shift = |e: Entity| entity(e.index() + 10);
+
+        let mut picked = ViewerEvent::Picked {
+            entity: entity(1),
+            position: Some(Vec3::X),
+            button: PickButton::Primary,
+        };
+        picked.map_entities(shift);
+        assert!(matches!(picked, ViewerEvent::Picked { entity: e, .. } if e == entity(11)));
+
+        let mut collision = ViewerEvent::CollisionChanged {
+            entity: entity(1),
+            colliding_with: vec![entity(2), entity(3)],
+        };
+        collision.map_entities(shift);
+        assert_eq!(
+            collision,
+            ViewerEvent::CollisionChanged {
+                entity: entity(11),
+                colliding_with: vec![entity(12), entity(13)],
+            }
+        );
+
+        let mut cleared = ViewerEvent::Selected { entity: None };
+        cleared.map_entities(|_| panic!("nothing to map"));
+        assert_eq!(cleared, ViewerEvent::Selected { entity: None });
+    }
+}
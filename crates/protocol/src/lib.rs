@@ -1,11 +1,23 @@
+mod access;
+mod bulk;
 mod components;
 mod errors;
+mod events;
+mod primitives;
 mod requests;
+mod stats;
 mod telemetry;
+mod widgets;
 
+pub use access::AccessRole;
+pub use bulk::*;
 pub use errors::TransportError;
+pub use events::*;
+pub use primitives::{PodVec2, PodVec3, PodVec4};
 pub use requests::*;
+pub use stats::{ChannelStats, MessageChannel, PeerStats};
 pub use telemetry::*;
+pub use widgets::{WidgetCommand, WidgetTab, WidgetValue};
 
 pub mod bm3d {
     pub use bevy_math::primitives::{
@@ -43,7 +55,6 @@ pub mod prelude {
 
     // renamed
     pub use super::components::prelude::ProtoComponent as Component;
-    // pub use super::primitives::*;
     // pub use super::requests::*;
     // pub use super::telemetry::*;
 }
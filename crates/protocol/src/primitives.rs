@@ -1,15 +1,23 @@
 use serde::{Deserialize, Serialize};
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
-// #[cfg(feature = "zerocopy")]
 
-use bevy_math::Vec3;
+use bevy_math::{Vec2, Vec3, Vec4};
 
 /// Define a POD vector type with serialization/deserialization support.
 macro_rules! pod_vec {
-    ($name:ident, $len:expr) => {
+    ($name:ident, $len:expr, $vec:ty) => {
         #[repr(C)]
         #[derive(
-            Debug, Clone, Copy, Serialize, Deserialize, FromBytes, Immutable, IntoBytes, KnownLayout,
+            Debug,
+            Clone,
+            Copy,
+            PartialEq,
+            Serialize,
+            Deserialize,
+            FromBytes,
+            Immutable,
+            IntoBytes,
+            KnownLayout,
         )]
         #[serde(transparent)]
         pub struct $name(pub [f32; $len]);
@@ -25,11 +33,21 @@ macro_rules! pod_vec {
                 value.0
             }
         }
+
+        impl From<$vec> for $name {
+            fn from(value: $vec) -> Self {
+                Self(value.to_array())
+            }
+        }
+
+        impl From<$name> for $vec {
+            fn from(value: $name) -> Self {
+                <$vec>::from_array(value.0)
+            }
+        }
     };
 }
 
-#[cfg(feature = "bevy")]
-pub mod bevy_impls {
-    use super::*;
-    use bevy::prelude::{Quat as BevyQuat, Vec3 as BevyVec3};
-}
+pod_vec!(PodVec2, 2, Vec2);
+pod_vec!(PodVec3, 3, Vec3);
+pod_vec!(PodVec4, 4, Vec4);
@@ -1,4 +1,7 @@
-use crate::components::prelude::ProtoComponent;
+use crate::{
+    components::prelude::ProtoComponent, events::ViewerEventKind, stats::PeerStats,
+    telemetry::TelemetryBinding, widgets::WidgetCommand,
+};
 use bevy_ecs::entity::Entity;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +14,24 @@ pub enum ProtoRequest {
     ApplyCommand(WorldCommand),
     /// List entities and their component ids.
     List,
+    /// Replace the viewer's telemetry bindings.
+    SetTelemetryBindings(Vec<TelemetryBinding>),
+    /// Replace the sender's event subscriptions; an empty list unsubscribes.
+    /// Matching [`ViewerEvent`](crate::ViewerEvent)s are pushed to the sender as they happen.
+    Subscribe(Vec<ViewerEventKind>),
+    /// Traffic statistics of every connection of the receiving endpoint.
+    Stats,
+    /// Create, replace or remove a widget in the viewer's widget panel. Interactions
+    /// with it are pushed to the sender as [`ViewerEvent`](crate::ViewerEvent)s, without
+    /// a subscription, and it is removed when the sender disconnects.
+    Widget(WidgetCommand),
+    /// Write the visible scene as glTF 2.0 to `path`, relative to the viewer's export
+    /// directory; a `.glb` path gets binary glTF. With `joints`, robots keep their current joint values,
+    /// otherwise they are exported in their zero configuration.
+    ExportGltf { path: String, joints: bool },
+    /// Load the robot described by the URDF file at `path` on the viewer's machine. It is
+    /// answered once the load has started; the load shows its progress in the tasks tab.
+    LoadUrdf { path: String, fixed_base: bool },
 }
 
 #[cfg_attr(feature = "bevy", derive(Component))]
@@ -22,10 +43,28 @@ pub enum ProtoResponse {
     CommandResponseEntity(Entity),
     /// Full entity listing for `ProtoRequest::List`.
     Entities { entities: Vec<EntityInfo> },
+    /// Connection statistics for `ProtoRequest::Stats`.
+    Stats { peers: Vec<PeerStats> },
     /// Error response for malformed or failed requests.
     Error { message: String },
 }
 
+/// Scene replication from a viewer to the viewers mirroring it.
+///
+/// Entity ids are the source viewer's; mirrors keep their own mapping.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SceneSync {
+    /// Every protocol-spawned entity with its components. Replaces the mirrored scene.
+    Snapshot {
+        entities: Vec<(Entity, Vec<ProtoComponent>)>,
+    },
+    /// A command applied by the source viewer, with the entity it resolved to.
+    Command {
+        command: WorldCommand,
+        entity: Entity,
+    },
+}
+
 /// A wrapper type to re-pack a `usize` as a component id.
 ///
 /// Use this with `WorldCommand::Remove` after retrieving ids from `List`.
@@ -40,6 +79,13 @@ impl From<ComponentId> for bevy::ecs::component::ComponentId {
     }
 }
 
+#[cfg(feature = "bevy")]
+impl From<bevy::ecs::component::ComponentId> for ComponentId {
+    fn from(id: bevy::ecs::component::ComponentId) -> Self {
+        Self(id.index())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EntityInfo {
     /// Stable bits for the Bevy entity id.
@@ -0,0 +1,47 @@
+use bevy_ecs::entity::Entity;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+use crate::AccessRole;
+
+/// Kind of message counted in [`ChannelStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MessageChannel {
+    Hello,
+    Heartbeat,
+    Requests,
+    Responses,
+    Events,
+    SceneSync,
+    Bulk,
+}
+
+/// Message counts of one [`MessageChannel`] on a connection. Rates are averaged
+/// over the last second.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelStats {
+    pub channel: MessageChannel,
+    pub sent: u64,
+    pub received: u64,
+    pub sent_per_sec: f32,
+    pub received_per_sec: f32,
+}
+
+/// Traffic of one connection, as seen by the endpoint answering `ProtoRequest::Stats`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerStats {
+    /// The link entity on the answering endpoint.
+    pub entity: Entity,
+    pub addr: Option<SocketAddr>,
+    /// "Viewer", "Controller" or "Hub", once the peer announced itself.
+    pub endpoint: Option<String>,
+    /// `None` for the server this endpoint connected to, or a peer without a valid token.
+    pub role: Option<AccessRole>,
+    /// Round-trip time of the last heartbeat.
+    pub rtt_ms: Option<f32>,
+    /// Requests waiting for a response on this connection.
+    pub pending_requests: u64,
+    /// Error responses and undecodable data on this connection.
+    pub errors: u64,
+    pub channels: Vec<ChannelStats>,
+}
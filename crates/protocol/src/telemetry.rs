@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::prelude::{Vec2, Vec3, Vec4};
+use crate::prelude::{Quat, Vec2, Vec3, Vec4};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TelemetryEvent {
@@ -24,6 +24,7 @@ pub struct TelemetryTime {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
+#[rustfmt::skip] // keeps the short variants on one line
 pub enum TelemetryPayload {
     Scalar { value: f64 },
     Vec2 { value: Vec2 },
@@ -31,6 +32,57 @@ pub enum TelemetryPayload {
     Vec4 { value: Vec4 },
     Text { value: String },
     Blob { mime: Option<String>, data: Vec<u8> },
+    /// Full rigid transform of an entity.
+    Transform {
+        translation: Vec3,
+        rotation: Quat,
+        scale: Vec3,
+    },
+    /// Named joint positions (e.g. a robot's configuration).
+    JointState {
+        names: Vec<String>,
+        positions: Vec<f64>,
+    },
+    /// A point cloud with optional per-point RGBA colours.
+    Points3d {
+        positions: Vec<Vec3>,
+        /// Either empty, a single colour for all points, or one per point.
+        #[serde(default)]
+        colors: Vec<[u8; 4]>,
+        #[serde(default)]
+        radius: Option<f32>,
+    },
+    /// A connected polyline.
+    LineStrip3d {
+        points: Vec<Vec3>,
+        #[serde(default)]
+        color: Option<[u8; 4]>,
+    },
+    /// Raw or encoded image data.
+    Image {
+        /// Pixel width (ignored for encoded formats).
+        width: u32,
+        /// Pixel height (ignored for encoded formats).
+        height: u32,
+        format: TelemetryImageFormat,
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TelemetryImageFormat {
+    /// Tightly packed 8-bit RGB.
+    Rgb8,
+    /// Tightly packed 8-bit RGBA.
+    Rgba8,
+    /// Little-endian `f32` depth in metres.
+    DepthF32,
+    /// Little-endian `u16` depth in millimetres.
+    DepthU16,
+    /// PNG-encoded bytes.
+    Png,
+    /// JPEG-encoded bytes.
+    Jpeg,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,3 +92,38 @@ pub struct TelemetryMetadata {
     /// Optional long description.
     pub description: Option<String>,
 }
+
+/// Drives a target on a named viewer entity from the latest value of a telemetry path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryBinding {
+    /// Telemetry path to read.
+    pub path: String,
+    /// `Name` of the entity to drive.
+    pub entity: String,
+    pub target: TelemetryBindingTarget,
+}
+
+/// What a [`TelemetryBinding`] writes to, and the payload it expects.
+///
+/// Externally tagged, since bindings also travel over the (bincode) transport.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TelemetryBindingTarget {
+    /// Whole transform, from a `Transform` payload.
+    Transform,
+    /// Translation, from a `Vec3` payload.
+    Translation,
+    /// Rotation, from a `Vec4` (xyzw) payload.
+    Rotation,
+    /// Scale, from a `Vec3` payload.
+    Scale,
+    /// All joints of a robot, from a `JointState` payload.
+    JointState,
+    /// A single robot joint, from a `Scalar` payload.
+    Joint { name: String },
+    /// Base colour of the entity's material, from a `Vec4` (rgba) or `Vec3` (rgb) payload.
+    MaterialColor,
+    /// Visibility, from a `Scalar` payload (non-zero is visible).
+    Visibility,
+    /// Text of the entity's label. `Text` payloads are used as-is, others are formatted.
+    LabelText,
+}
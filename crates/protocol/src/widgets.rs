@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+/// A widget in the viewer's widget panel, sent with `ProtoRequest::Widget`.
+///
+/// Sending a widget with the `id` of an existing one replaces it, which is how a
+/// controller updates values, options or plot data. Groups, grids, tabs and panels
+/// list their children by id; widgets that are nobody's child are shown in the
+/// default "Widgets" window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WidgetCommand {
+    Label {
+        id: String,
+        text: String,
+    },
+    Button {
+        id: String,
+        text: String,
+    },
+    Checkbox {
+        id: String,
+        text: String,
+        checked: bool,
+    },
+    Slider {
+        id: String,
+        text: String,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+    NumberInput {
+        id: String,
+        text: String,
+        value: f64,
+    },
+    /// A combo box; `selected` indexes `options`.
+    Dropdown {
+        id: String,
+        text: String,
+        options: Vec<String>,
+        selected: usize,
+    },
+    TextInput {
+        id: String,
+        text: String,
+        value: String,
+    },
+    /// An RGBA colour in 0..1.
+    ColorPicker {
+        id: String,
+        text: String,
+        rgba: [f32; 4],
+    },
+    /// A line through `(x, y)` points.
+    Plot {
+        id: String,
+        text: String,
+        points: Vec<[f64; 2]>,
+    },
+    Separator {
+        id: String,
+    },
+    Horizontal {
+        id: String,
+        children: Vec<String>,
+    },
+    Vertical {
+        id: String,
+        children: Vec<String>,
+    },
+    /// A collapsible section titled `text`.
+    Collapsing {
+        id: String,
+        text: String,
+        open: bool,
+        children: Vec<String>,
+    },
+    /// Children laid out row by row, `columns` per row.
+    Grid {
+        id: String,
+        columns: usize,
+        children: Vec<String>,
+    },
+    Tabs {
+        id: String,
+        tabs: Vec<WidgetTab>,
+    },
+    /// A named panel, docked as a tab titled `id` that can be placed with the UI layout file.
+    Panel {
+        id: String,
+        children: Vec<String>,
+    },
+    /// Remove the widget with this id.
+    Remove { id: String },
+}
+
+/// One tab of a [`WidgetCommand::Tabs`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WidgetTab {
+    pub title: String,
+    pub children: Vec<String>,
+}
+
+impl WidgetCommand {
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Label { id, .. }
+            | Self::Button { id, .. }
+            | Self::Checkbox { id, .. }
+            | Self::Slider { id, .. }
+            | Self::NumberInput { id, .. }
+            | Self::Dropdown { id, .. }
+            | Self::TextInput { id, .. }
+            | Self::ColorPicker { id, .. }
+            | Self::Plot { id, .. }
+            | Self::Separator { id }
+            | Self::Horizontal { id, .. }
+            | Self::Vertical { id, .. }
+            | Self::Collapsing { id, .. }
+            | Self::Grid { id, .. }
+            | Self::Tabs { id, .. }
+            | Self::Panel { id, .. }
+            | Self::Remove { id } => id,
+        }
+    }
+}
+
+/// New value of an input widget, see `ViewerEvent::WidgetChanged`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WidgetValue {
+    /// Slider or number input.
+    Number(f64),
+    /// Text input, sent when it loses focus.
+    Text(String),
+    /// Index of the dropdown option.
+    Selected(usize),
+    Color([f32; 4]),
+}
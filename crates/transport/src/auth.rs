@@ -0,0 +1,67 @@
+//! Peer authentication on servers.
+//!
+//! Clients present [`TransportConfig::token`](crate::TransportConfig::token) in their
+//! [`TransportHello`]. The server resolves it to an [`AccessRole`] and records it on
+//! the link as a [`TransportPeer`]; request handlers check that role. The token is
+//! sent as is, so only WebTransport keeps it private on the wire.
+
+use bevy_ecs::prelude::*;
+use bevy_log::{info, warn};
+use dimensify_protocol::{AccessRole, MessageChannel};
+use lightyear::prelude::{LinkOf, MessageReceiver};
+
+use crate::{stats::LinkStats, web_transport::TransportHello};
+
+/// A client linked to this server, known once it sent its [`TransportHello`].
+#[derive(Component, Debug, Clone)]
+pub struct TransportPeer {
+    pub endpoint: crate::TransportEndpoint,
+    /// `None` if the peer may not do anything.
+    pub role: Option<AccessRole>,
+}
+
+impl TransportPeer {
+    /// Role of a link, which may not have sent its hello yet.
+    pub fn role_of(config: &crate::TransportConfig, peer: Option<&Self>) -> Option<AccessRole> {
+        match peer {
+            Some(peer) => peer.role,
+            None => config.role_for(None),
+        }
+    }
+}
+
+/// Turn the hello of each new link into a [`TransportPeer`]. Systems reading peers
+/// or their requests run after this one.
+pub fn authenticate_peers(
+    mut commands: Commands,
+    config: Res<crate::TransportConfig>,
+    mut links: Query<
+        (Entity, &mut MessageReceiver<TransportHello>, &mut LinkStats),
+        (With<LinkOf>, Without<TransportPeer>),
+    >,
+) {
+    for (entity, mut receiver, mut stats) in &mut links {
+        let Some(hello) = receiver
+            .receive()
+            .inspect(|_| stats.received(MessageChannel::Hello))
+            .last()
+        else {
+            continue;
+        };
+        let role = config.role_for(hello.token.as_deref());
+        match role {
+            Some(role) => info!(
+                "transport: {:?} peer {:?} joined as {:?}",
+                hello.endpoint, entity, role
+            ),
+            None => warn!(
+                "transport: {:?} peer {:?} has no valid token; rejecting its requests",
+                hello.endpoint, entity
+            ),
+        }
+        commands.entity(entity).insert(TransportPeer {
+            endpoint: hello.endpoint,
+            role,
+        });
+    }
+}
@@ -0,0 +1,304 @@
+//! Bulk transfers: [`BulkData`] encoded as POD bytes, optionally compressed, and
+//! split into [`StreamBytes`] chunks on the reliable channel, so that large meshes,
+//! point clouds and images stay under the message size limit.
+
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use dimensify_protocol::{BulkCompression, BulkData, BulkHeader, TransportError};
+
+use crate::web_transport::StreamBytes;
+
+/// Payload bytes per chunk.
+pub const BULK_CHUNK_SIZE: usize = 32 * 1024;
+
+/// Largest transfer accepted, before and after compression.
+pub const MAX_BULK_LEN: u64 = 256 << 20;
+
+/// Transfers one link may have in flight at once.
+pub const MAX_BULK_TRANSFERS: usize = 4;
+
+/// Bytes one link may have buffered in unfinished transfers.
+pub const MAX_BULK_BUFFERED: u64 = 256 << 20;
+
+/// Encode, compress and chunk `data` for `entity`. `transfer` must be unique per sender.
+pub fn bulk_chunks(
+    transfer: u64,
+    entity: Entity,
+    data: &BulkData,
+    compression: BulkCompression,
+) -> Result<Vec<StreamBytes>, TransportError> {
+    data.validate()?;
+    let encoded = data.encode();
+    if encoded.len() as u64 > MAX_BULK_LEN {
+        return Err(invalid("transfer too large"));
+    }
+    let header = BulkHeader {
+        entity,
+        kind: data.kind(),
+        compression,
+        len: encoded.len() as u64,
+    };
+    let bytes = compress(encoded, compression)?;
+    let count = bytes.len().div_ceil(BULK_CHUNK_SIZE).max(1);
+    let count = u32::try_from(count).map_err(|_| invalid("transfer too large"))?;
+    let mut chunks: Vec<StreamBytes> = bytes
+        .chunks(BULK_CHUNK_SIZE)
+        .enumerate()
+        .map(|(index, payload)| StreamBytes {
+            transfer,
+            header: None,
+            index: index as u32,
+            count,
+            payload: payload.to_vec(),
+        })
+        .collect();
+    if chunks.is_empty() {
+        chunks.push(StreamBytes {
+            transfer,
+            header: None,
+            index: 0,
+            count,
+            payload: Vec::new(),
+        });
+    }
+    chunks[0].header = Some(header);
+    Ok(chunks)
+}
+
+/// Reassembles the bulk transfers arriving on one link, within [`MAX_BULK_TRANSFERS`]
+/// and [`MAX_BULK_BUFFERED`].
+#[derive(Component, Default)]
+pub struct BulkAssembler {
+    transfers: HashMap<u64, PartialTransfer>,
+}
+
+struct PartialTransfer {
+    header: BulkHeader,
+    chunks: Vec<Vec<u8>>,
+    received: u64,
+}
+
+impl BulkAssembler {
+    /// Add a chunk, returning the transfer once its last chunk arrived.
+    pub fn push(
+        &mut self,
+        chunk: StreamBytes,
+    ) -> Option<Result<(BulkHeader, BulkData), TransportError>> {
+        if let Some(header) = chunk.header {
+            if header.len > MAX_BULK_LEN {
+                return Some(Err(invalid("transfer too large")));
+            }
+            self.transfers.remove(&chunk.transfer);
+            if self.transfers.len() >= MAX_BULK_TRANSFERS {
+                return Some(Err(invalid("too many transfers in flight")));
+            }
+            self.transfers.insert(
+                chunk.transfer,
+                PartialTransfer {
+                    header,
+                    chunks: Vec::new(),
+                    received: 0,
+                },
+            );
+        }
+        let Some(partial) = self.transfers.get_mut(&chunk.transfer) else {
+            // the start of this transfer was lost, e.g. to a reconnect
+            return Some(Err(invalid("chunk of an unknown transfer")));
+        };
+        // the reliable channel is ordered
+        if chunk.index as usize != partial.chunks.len() {
+            self.transfers.remove(&chunk.transfer);
+            return Some(Err(invalid("chunk out of order")));
+        }
+        partial.received += chunk.payload.len() as u64;
+        // uncompressed transfers cannot exceed their header, compressed ones the cap
+        let limit = match partial.header.compression {
+            BulkCompression::None => partial.header.len,
+            _ => MAX_BULK_LEN,
+        };
+        if partial.received > limit {
+            self.transfers.remove(&chunk.transfer);
+            return Some(Err(invalid("transfer larger than announced")));
+        }
+        partial.chunks.push(chunk.payload);
+        let complete = partial.chunks.len() >= chunk.count as usize;
+        if self.buffered() > MAX_BULK_BUFFERED {
+            self.transfers.remove(&chunk.transfer);
+            return Some(Err(invalid("too much bulk data in flight")));
+        }
+        if !complete {
+            return None;
+        }
+
+        let partial = self.transfers.remove(&chunk.transfer)?;
+        let bytes = partial.chunks.concat();
+        Some(
+            decompress(bytes, &partial.header)
+                .and_then(|bytes| BulkData::decode(partial.header.kind, &bytes))
+                .map(|data| (partial.header, data)),
+        )
+    }
+
+    /// Bytes held by unfinished transfers.
+    fn buffered(&self) -> u64 {
+        self.transfers
+            .values()
+            .map(|partial| partial.received)
+            .sum()
+    }
+}
+
+fn invalid(message: &str) -> TransportError {
+    TransportError::InvalidBulk(message.to_string())
+}
+
+fn compress(bytes: Vec<u8>, compression: BulkCompression) -> Result<Vec<u8>, TransportError> {
+    match compression {
+        BulkCompression::None => Ok(bytes),
+        #[cfg(feature = "zstd")]
+        BulkCompression::Zstd => zstd::bulk::compress(&bytes, 3)
+            .map_err(|err| TransportError::InvalidBulk(err.to_string())),
+        #[cfg(feature = "lz4")]
+        BulkCompression::Lz4 => {
+            use std::io::Write;
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+            encoder
+                .write_all(&bytes)
+                .map_err(|err| TransportError::InvalidBulk(err.to_string()))?;
+            encoder
+                .finish()
+                .map_err(|err| TransportError::InvalidBulk(err.to_string()))
+        }
+        #[allow(unreachable_patterns)]
+        other => Err(TransportError::CompressionUnavailable(format!(
+            "{:?}",
+            other
+        ))),
+    }
+}
+
+/// Decompress with a streaming decoder that stops one byte past the announced length, so
+/// memory grows with the output actually produced rather than with the header.
+fn decompress(bytes: Vec<u8>, header: &BulkHeader) -> Result<Vec<u8>, TransportError> {
+    let bytes = match header.compression {
+        BulkCompression::None => bytes,
+        #[cfg(feature = "zstd")]
+        BulkCompression::Zstd => read_bounded(
+            zstd::stream::read::Decoder::new(bytes.as_slice())
+                .map_err(|err| TransportError::InvalidBulk(err.to_string()))?,
+            header.len,
+        )?,
+        #[cfg(feature = "lz4")]
+        BulkCompression::Lz4 => read_bounded(
+            lz4_flex::frame::FrameDecoder::new(bytes.as_slice()),
+            header.len,
+        )?,
+        #[allow(unreachable_patterns)]
+        other => {
+            return Err(TransportError::CompressionUnavailable(format!(
+                "{:?}",
+                other
+            )));
+        }
+    };
+    if bytes.len() as u64 != header.len {
+        return Err(invalid("length does not match the header"));
+    }
+    Ok(bytes)
+}
+
+/// Read at most `len + 1` bytes, enough to tell an overlong stream from a matching one.
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+fn read_bounded(reader: impl std::io::Read, len: u64) -> Result<Vec<u8>, TransportError> {
+    use std::io::Read;
+
+    let mut bytes = Vec::new();
+    reader
+        .take(len.saturating_add(1))
+        .read_to_end(&mut bytes)
+        .map_err(|err| TransportError::InvalidBulk(err.to_string()))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BULK_CHUNK_SIZE, BulkAssembler, MAX_BULK_TRANSFERS, bulk_chunks};
+    use bevy_ecs::entity::Entity;
+    use dimensify_protocol::{BulkCompression, BulkData};
+
+    #[test]
+    fn chunks_reassemble_into_the_image() {
+        let image = BulkData::Image {
+            width: 128,
+            height: 128,
+            rgba8: (0..128 * 128 * 4).map(|i| i as u8).collect(),
+        };
+        let chunks = bulk_chunks(7, Entity::PLACEHOLDER, &image, BulkCompression::None).unwrap();
+        assert!(chunks.len() > 1);
+        assert!(
+            chunks
+                .iter()
+                .all(|chunk| chunk.payload.len() <= BULK_CHUNK_SIZE)
+        );
+
+        let mut assembler = BulkAssembler::default();
+        let mut done = None;
+        for chunk in chunks {
+            done = assembler.push(chunk);
+        }
+        let (header, data) = done.unwrap().unwrap();
+        assert_eq!(header.entity, Entity::PLACEHOLDER);
+        assert_eq!(data, image);
+    }
+
+    fn image(side: u32) -> BulkData {
+        BulkData::Image {
+            width: side,
+            height: side,
+            rgba8: vec![7; (side * side * 4) as usize],
+        }
+    }
+
+    #[test]
+    fn transfers_beyond_their_header_or_the_link_limit_are_refused() {
+        let mut chunks =
+            bulk_chunks(1, Entity::PLACEHOLDER, &image(8), BulkCompression::None).unwrap();
+        chunks[0].payload.push(0);
+        let mut assembler = BulkAssembler::default();
+        assert!(matches!(assembler.push(chunks.remove(0)), Some(Err(_))));
+
+        let mut assembler = BulkAssembler::default();
+        for transfer in 0..MAX_BULK_TRANSFERS as u64 {
+            let first = bulk_chunks(
+                transfer,
+                Entity::PLACEHOLDER,
+                &image(128),
+                BulkCompression::None,
+            )
+            .unwrap()
+            .remove(0);
+            assert!(assembler.push(first).is_none());
+        }
+        let extra = bulk_chunks(99, Entity::PLACEHOLDER, &image(128), BulkCompression::None)
+            .unwrap()
+            .remove(0);
+        assert!(matches!(assembler.push(extra), Some(Err(_))));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn compressed_transfers_cannot_outgrow_their_header() {
+        let mut chunks =
+            bulk_chunks(1, Entity::PLACEHOLDER, &image(64), BulkCompression::Zstd).unwrap();
+        if let Some(header) = &mut chunks[0].header {
+            header.len = 16;
+        }
+        let mut assembler = BulkAssembler::default();
+        let mut done = None;
+        for chunk in chunks {
+            done = assembler.push(chunk);
+        }
+        assert!(matches!(done, Some(Err(_))));
+    }
+}
@@ -0,0 +1,344 @@
+//! Connection liveness: heartbeats on every link, and reconnect with backoff for clients.
+//!
+//! Raw UDP links never notice a peer that went away, so both sides send a
+//! [`TransportHeartbeat`] every `heartbeat_interval_secs`. A client that hears
+//! nothing for `heartbeat_timeout_secs` disconnects and reconnects, backing off
+//! from 0.5s up to [`MAX_RECONNECT_BACKOFF_SECS`]. Heartbeats are answered with a
+//! pong, which gives each link's round-trip time (see [`LinkStats`]).
+
+use bevy_ecs::prelude::*;
+use bevy_internal::time::{Real, Time, Timer, TimerMode};
+use bevy_log::{info, warn};
+use dimensify_protocol::MessageChannel;
+use lightyear::prelude::{Client, Connect, Connected, Disconnect, MessageReceiver, MessageSender};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use crate::stats::LinkStats;
+use crate::web_transport::{HelloSent, StreamUnreliable};
+
+const MAX_RECONNECT_BACKOFF_SECS: f64 = 10.0;
+
+/// Sent periodically on every connection so clients notice a dead peer.
+///
+/// Both carry the `Time<Real>` seconds of the sender of the ping.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum TransportHeartbeat {
+    Ping(f64),
+    /// Answer to a ping, echoing its time.
+    Pong(f64),
+}
+
+/// State of the transport connection. Servers report `Connected` once listening.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Waiting for the first connection.
+    Connecting,
+    Connected,
+    /// The connection was lost; reconnect `attempt` is scheduled or running.
+    Reconnecting {
+        attempt: u32,
+        reason: String,
+    },
+    /// Shut down, or out of reconnect attempts.
+    Closed {
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connecting => write!(f, "connecting"),
+            Self::Connected => write!(f, "connected"),
+            Self::Reconnecting { attempt, reason } => {
+                write!(f, "reconnecting (attempt {}, {})", attempt, reason)
+            }
+            Self::Closed { reason } => write!(f, "closed ({})", reason),
+        }
+    }
+}
+
+/// Connection state, shared with threads outside the app (see `TransportController`).
+#[derive(Resource, Clone, Debug)]
+pub struct TransportStatus(Arc<Mutex<ConnectionState>>);
+
+impl Default for TransportStatus {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(ConnectionState::Connecting)))
+    }
+}
+
+impl TransportStatus {
+    pub fn get(&self) -> ConnectionState {
+        match self.0.lock() {
+            Ok(state) => state.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    pub(crate) fn set(&self, state: ConnectionState) {
+        let mut current = match self.0.lock() {
+            Ok(current) => current,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if *current != state {
+            info!("transport: {}", state);
+            *current = state;
+        }
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct HeartbeatTimer(Timer);
+
+impl HeartbeatTimer {
+    pub(crate) fn new(config: &crate::TransportConfig) -> Self {
+        Self(Timer::from_seconds(
+            config.heartbeat_interval_secs.max(0.01),
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+/// Reconnect bookkeeping for the client link. Times are `Time<Real>` seconds.
+#[derive(Resource, Default)]
+pub(crate) struct ClientLiveness {
+    connected: bool,
+    attempt: u32,
+    last_heard: f64,
+    /// When the next connect attempt starts.
+    retry_at: Option<f64>,
+    /// When the running connect attempt is given up.
+    deadline: Option<f64>,
+}
+
+impl ClientLiveness {
+    pub(crate) fn new(config: &crate::TransportConfig) -> Self {
+        let timeout = config.heartbeat_timeout_secs as f64;
+        Self {
+            deadline: (timeout > 0.0).then_some(timeout),
+            ..Default::default()
+        }
+    }
+
+    /// Note that the client is connected, which resets the backoff. Returns whether it
+    /// was not connected before.
+    fn connect(&mut self, now: f64) -> bool {
+        let newly = !self.connected;
+        if newly {
+            self.connected = true;
+            self.attempt = 0;
+            self.deadline = None;
+            self.last_heard = now;
+        }
+        newly
+    }
+
+    /// Note that the connection was lost and schedule the next attempt, unless the
+    /// attempts are used up.
+    fn lose(&mut self, config: &crate::TransportConfig, now: f64, reason: &str) -> ConnectionState {
+        self.connected = false;
+        self.deadline = None;
+        if config
+            .reconnect_attempts
+            .is_some_and(|max| self.attempt >= max)
+        {
+            return ConnectionState::Closed {
+                reason: format!("{} after {} reconnect attempts", reason, self.attempt),
+            };
+        }
+        self.attempt += 1;
+        self.retry_at = Some(now + reconnect_backoff(self.attempt));
+        ConnectionState::Reconnecting {
+            attempt: self.attempt,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Seconds to wait before reconnect `attempt`, doubling from 0.5s.
+fn reconnect_backoff(attempt: u32) -> f64 {
+    (0.5 * 2f64.powi(attempt.saturating_sub(1).min(31) as i32)).min(MAX_RECONNECT_BACKOFF_SECS)
+}
+
+pub(crate) fn send_heartbeats(
+    time: Res<Time<Real>>,
+    mut timer: ResMut<HeartbeatTimer>,
+    mut senders: Query<(&mut MessageSender<TransportHeartbeat>, &mut LinkStats), With<Connected>>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let now = time.elapsed_secs_f64();
+    for (mut sender, mut stats) in &mut senders {
+        sender.send::<StreamUnreliable>(TransportHeartbeat::Ping(now));
+        stats.sent(MessageChannel::Heartbeat);
+    }
+}
+
+pub(crate) fn receive_heartbeats(
+    time: Res<Time<Real>>,
+    mut liveness: Option<ResMut<ClientLiveness>>,
+    mut links: Query<(
+        &mut MessageReceiver<TransportHeartbeat>,
+        &mut MessageSender<TransportHeartbeat>,
+        &mut LinkStats,
+    )>,
+) {
+    let now = time.elapsed_secs_f64();
+    let mut heard = false;
+    // drain every link, servers included
+    for (mut receiver, mut sender, mut stats) in &mut links {
+        for heartbeat in receiver.receive() {
+            heard = true;
+            stats.received(MessageChannel::Heartbeat);
+            match heartbeat {
+                TransportHeartbeat::Ping(sent_at) => {
+                    sender.send::<StreamUnreliable>(TransportHeartbeat::Pong(sent_at));
+                    stats.sent(MessageChannel::Heartbeat);
+                }
+                TransportHeartbeat::Pong(sent_at) => stats.set_rtt(now - sent_at),
+            }
+        }
+    }
+    if let (true, Some(liveness)) = (heard, liveness.as_mut()) {
+        liveness.last_heard = time.elapsed_secs_f64();
+    }
+}
+
+pub(crate) fn track_client_connection(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    config: Res<crate::TransportConfig>,
+    status: Res<TransportStatus>,
+    mut liveness: ResMut<ClientLiveness>,
+    clients: Query<(Entity, Has<Connected>), With<Client>>,
+) {
+    let Ok((entity, connected)) = clients.single() else {
+        return;
+    };
+    let now = time.elapsed_secs_f64();
+    let timeout = config.heartbeat_timeout_secs as f64;
+
+    if connected {
+        if liveness.connect(now) {
+            status.set(ConnectionState::Connected);
+        } else if timeout > 0.0 && now - liveness.last_heard > timeout {
+            warn!(
+                "transport: no heartbeat for {:.1}s",
+                now - liveness.last_heard
+            );
+            commands.trigger(Disconnect { entity });
+            lose_connection(
+                &mut commands,
+                entity,
+                &config,
+                &status,
+                &mut liveness,
+                now,
+                "heartbeat timeout",
+            );
+        }
+        return;
+    }
+
+    if liveness.connected {
+        lose_connection(
+            &mut commands,
+            entity,
+            &config,
+            &status,
+            &mut liveness,
+            now,
+            "disconnected",
+        );
+        return;
+    }
+    if liveness.deadline.is_some_and(|deadline| now > deadline) {
+        commands.trigger(Disconnect { entity });
+        lose_connection(
+            &mut commands,
+            entity,
+            &config,
+            &status,
+            &mut liveness,
+            now,
+            "connect timed out",
+        );
+    }
+    if liveness.retry_at.is_some_and(|retry_at| now >= retry_at) {
+        liveness.retry_at = None;
+        liveness.deadline = (timeout > 0.0).then_some(now + timeout);
+        commands.trigger(Connect { entity });
+    }
+}
+
+fn lose_connection(
+    commands: &mut Commands,
+    entity: Entity,
+    config: &crate::TransportConfig,
+    status: &TransportStatus,
+    liveness: &mut ClientLiveness,
+    now: f64,
+    reason: &str,
+) {
+    // announce ourselves again on the next connection
+    commands.entity(entity).remove::<HelloSent>();
+    status.set(liveness.lose(config, now, reason));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClientLiveness, ConnectionState, reconnect_backoff};
+    use crate::TransportConfig;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let backoffs: Vec<f64> = (1..=7).map(reconnect_backoff).collect();
+        assert_eq!(backoffs, [0.5, 1.0, 2.0, 4.0, 8.0, 10.0, 10.0]);
+        assert_eq!(reconnect_backoff(u32::MAX), 10.0);
+    }
+
+    #[test]
+    fn connecting_resets_the_backoff() {
+        let config = TransportConfig::default();
+        let mut liveness = ClientLiveness::new(&config);
+        assert!(liveness.connect(0.0));
+        assert!(!liveness.connect(1.0));
+
+        for attempt in 1..=3 {
+            let state = liveness.lose(&config, 10.0, "disconnected");
+            assert!(
+                matches!(state, ConnectionState::Reconnecting { attempt: a, .. } if a == attempt)
+            );
+        }
+        assert_eq!(liveness.retry_at, Some(12.0));
+
+        assert!(liveness.connect(20.0));
+        liveness.lose(&config, 30.0, "heartbeat timeout");
+        assert_eq!(liveness.attempt, 1);
+        assert_eq!(liveness.retry_at, Some(30.5));
+    }
+
+    #[test]
+    fn reconnecting_stops_after_the_configured_attempts() {
+        let config = TransportConfig {
+            reconnect_attempts: Some(1),
+            ..Default::default()
+        };
+        let mut liveness = ClientLiveness::new(&config);
+        liveness.connect(0.0);
+
+        assert!(matches!(
+            liveness.lose(&config, 1.0, "disconnected"),
+            ConnectionState::Reconnecting { attempt: 1, .. }
+        ));
+        assert_eq!(
+            liveness.lose(&config, 2.0, "connect timed out"),
+            ConnectionState::Closed {
+                reason: "connect timed out after 1 reconnect attempts".to_string()
+            }
+        );
+    }
+}
@@ -3,15 +3,33 @@ use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
 use dimensify_protocol::TransportError;
-pub use dimensify_protocol::{EntityInfo, ProtoRequest, ProtoResponse};
+pub use dimensify_protocol::{AccessRole, EntityInfo, ProtoRequest, ProtoResponse};
 
-#[cfg(any(feature = "webtransport", feature = "websocket", feature = "udp"))]
+#[cfg(feature = "transport")]
+mod auth;
+#[cfg(feature = "transport")]
+mod bulk;
+#[cfg(feature = "transport")]
+mod connection;
+#[cfg(feature = "loopback")]
+mod loopback;
+#[cfg(feature = "transport")]
+mod stats;
+#[cfg(feature = "transport")]
 mod web_transport;
 
-#[cfg(any(feature = "webtransport", feature = "websocket", feature = "udp"))]
+#[cfg(feature = "transport")]
+pub use auth::{TransportPeer, authenticate_peers};
+#[cfg(feature = "transport")]
+pub use bulk::{BULK_CHUNK_SIZE, BulkAssembler, MAX_BULK_LEN, bulk_chunks};
+#[cfg(feature = "transport")]
+pub use connection::{ConnectionState, TransportHeartbeat, TransportStatus};
+#[cfg(feature = "transport")]
+pub use stats::{LinkStats, PeerStatsData, peer_stats};
+#[cfg(feature = "transport")]
 pub use web_transport::{
-    StreamBytes, StreamReliable, StreamUnreliable, TransportController, TransportPlugin,
-    TransportRuntimePlugin,
+    ResponseCallback, StreamBytes, StreamReliable, StreamUnreliable, TransportController,
+    TransportHello, TransportPlugin, TransportRuntimePlugin,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +40,9 @@ pub enum TransportMode {
     WebSocket,
     #[cfg(feature = "udp")]
     Udp,
+    /// In-process channels; `server_addr` only identifies the server.
+    #[cfg(feature = "loopback")]
+    Loopback,
 }
 
 impl Default for TransportMode {
@@ -32,6 +53,8 @@ impl Default for TransportMode {
         return Self::WebSocket;
         #[cfg(feature = "udp")]
         return Self::Udp;
+        #[cfg(feature = "loopback")]
+        return Self::Loopback;
     }
 }
 
@@ -45,6 +68,8 @@ impl TryFrom<String> for TransportMode {
             "websocket" => Ok(Self::WebSocket),
             #[cfg(feature = "udp")]
             "udp" => Ok(Self::Udp),
+            #[cfg(feature = "loopback")]
+            "loopback" => Ok(Self::Loopback),
             _ => Err(TransportError::InvalidConnection(value)),
         }
     }
@@ -67,10 +92,74 @@ pub enum TransportConnection {
 //     }
 // }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TransportEndpoint {
     Viewer,
     Controller,
+    /// Relay between controllers and viewers; sends and receives both requests and responses.
+    Hub,
+}
+
+/// An address a server listens on, with its own mode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransportListener {
+    pub mode: TransportMode,
+    pub addr: SocketAddr,
+    /// WebTransport only; falls back to the certificate of the [`TransportConfig`].
+    pub certificate_path: Option<String>,
+    pub certificate_key_path: Option<String>,
+}
+
+impl std::str::FromStr for TransportListener {
+    type Err = TransportError;
+
+    /// Parse `mode@host:port`, optionally followed by `;cert=path;key=path`.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || TransportError::InvalidListener(input.to_string());
+        let mut parts = input.trim().split(';');
+        let (mode, addr) = parts
+            .next()
+            .and_then(|endpoint| endpoint.split_once('@'))
+            .ok_or_else(invalid)?;
+        let mut listener = Self {
+            mode: mode.to_string().try_into().map_err(|_| invalid())?,
+            addr: addr.parse().map_err(|_| invalid())?,
+            certificate_path: None,
+            certificate_key_path: None,
+        };
+        for option in parts {
+            match option.split_once('=') {
+                Some(("cert", path)) => listener.certificate_path = Some(path.to_string()),
+                Some(("key", path)) => listener.certificate_key_path = Some(path.to_string()),
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(listener)
+    }
+}
+
+/// A token a server accepts, and the role it grants.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessToken {
+    pub role: AccessRole,
+    pub token: String,
+}
+
+impl std::str::FromStr for AccessToken {
+    type Err = TransportError;
+
+    /// Parse `role:token`.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (role, token) = input
+            .trim()
+            .split_once(':')
+            .filter(|(_, token)| !token.is_empty())
+            .ok_or_else(|| TransportError::InvalidAccessToken("expected role:token".to_string()))?;
+        Ok(Self {
+            role: AccessRole::try_from(role).map_err(TransportError::InvalidAccessToken)?,
+            token: token.to_string(),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Resource)]
@@ -84,6 +173,21 @@ pub struct TransportConfig {
     pub certificate_path: Option<String>,
     pub certificate_key_path: Option<String>,
     pub tick_hz: f32,
+    /// Endpoints a server listens on, all feeding the same requests. Empty listens on
+    /// `server_addr` with `mode` only.
+    pub listen: Vec<TransportListener>,
+    /// Seconds between heartbeats sent on every connection.
+    pub heartbeat_interval_secs: f32,
+    /// A client that hears nothing for this long treats the connection as lost. `0` disables it.
+    pub heartbeat_timeout_secs: f32,
+    /// How often a client reconnects after losing the connection. `None` retries forever.
+    pub reconnect_attempts: Option<u32>,
+    /// Token a client presents when connecting.
+    pub token: Option<String>,
+    /// Tokens a server accepts. Empty disables authentication: every peer is admin.
+    pub access_tokens: Vec<AccessToken>,
+    /// Role of peers without a valid token, when `access_tokens` is set. `None` rejects them.
+    pub anonymous_role: Option<AccessRole>,
 }
 
 impl Default for TransportConfig {
@@ -98,11 +202,48 @@ impl Default for TransportConfig {
             certificate_path: None,
             certificate_key_path: None,
             tick_hz: 60.0,
+            listen: Vec::new(),
+            heartbeat_interval_secs: 1.0,
+            heartbeat_timeout_secs: 5.0,
+            reconnect_attempts: None,
+            token: None,
+            access_tokens: Vec::new(),
+            anonymous_role: None,
         }
     }
 }
 
 impl TransportConfig {
+    /// Role of a peer that presented `token`.
+    pub fn role_for(&self, token: Option<&str>) -> Option<AccessRole> {
+        if self.access_tokens.is_empty() {
+            return Some(AccessRole::Admin);
+        }
+        token
+            .and_then(|token| {
+                // compare with every token, so the time taken tells nothing about them
+                self.access_tokens
+                    .iter()
+                    .filter(|access| constant_time_eq(access.token.as_bytes(), token.as_bytes()))
+                    .map(|access| access.role)
+                    .max()
+            })
+            .or(self.anonymous_role)
+    }
+
+    /// Endpoints a server listens on.
+    pub fn listeners(&self) -> Vec<TransportListener> {
+        if !self.listen.is_empty() {
+            return self.listen.clone();
+        }
+        vec![TransportListener {
+            mode: self.mode.clone(),
+            addr: self.server_addr,
+            certificate_path: None,
+            certificate_key_path: None,
+        }]
+    }
+
     #[cfg(not(target_family = "wasm"))]
     pub fn from_env() -> Self {
         use std::env;
@@ -153,6 +294,53 @@ impl TransportConfig {
             }
         }
 
+        if let Ok(value) = env::var("DIMENSIFY_TRANSPORT_LISTEN") {
+            for entry in value.split(',').filter(|entry| !entry.trim().is_empty()) {
+                match entry.parse() {
+                    Ok(listener) => config.listen.push(listener),
+                    Err(err) => bevy_log::warn!("DIMENSIFY_TRANSPORT_LISTEN: {}", err),
+                }
+            }
+        }
+
+        if let Ok(value) = env::var("DIMENSIFY_TRANSPORT_HEARTBEAT_SECS") {
+            if let Ok(secs) = value.parse() {
+                config.heartbeat_interval_secs = secs;
+            }
+        }
+
+        if let Ok(value) = env::var("DIMENSIFY_TRANSPORT_HEARTBEAT_TIMEOUT_SECS") {
+            if let Ok(secs) = value.parse() {
+                config.heartbeat_timeout_secs = secs;
+            }
+        }
+
+        if let Ok(value) = env::var("DIMENSIFY_TRANSPORT_RECONNECT_ATTEMPTS") {
+            if let Ok(attempts) = value.parse() {
+                config.reconnect_attempts = Some(attempts);
+            }
+        }
+
+        if let Ok(value) = env::var("DIMENSIFY_TRANSPORT_TOKEN") {
+            config.token = Some(value);
+        }
+
+        if let Ok(value) = env::var("DIMENSIFY_TRANSPORT_ACCESS_TOKENS") {
+            for entry in value.split(',').filter(|entry| !entry.trim().is_empty()) {
+                match entry.parse() {
+                    Ok(access) => config.access_tokens.push(access),
+                    Err(err) => bevy_log::warn!("DIMENSIFY_TRANSPORT_ACCESS_TOKENS: {}", err),
+                }
+            }
+        }
+
+        if let Ok(value) = env::var("DIMENSIFY_TRANSPORT_ANONYMOUS_ROLE") {
+            match AccessRole::try_from(value.as_str()) {
+                Ok(role) => config.anonymous_role = Some(role),
+                Err(err) => bevy_log::warn!("DIMENSIFY_TRANSPORT_ANONYMOUS_ROLE: {}", err),
+            }
+        }
+
         config
     }
 
@@ -172,10 +360,73 @@ fn parse_connection(input: &str) -> Option<TransportConnection> {
     }
 }
 
+/// Compare two byte strings in time depending only on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
 fn parse_endpoint(input: &str) -> Option<TransportEndpoint> {
     match input.to_ascii_lowercase().as_str() {
         "viewer" => Some(TransportEndpoint::Viewer),
         "controller" => Some(TransportEndpoint::Controller),
+        "hub" => Some(TransportEndpoint::Hub),
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use dimensify_protocol::AccessRole;
+
+    use super::{AccessToken, TransportConfig};
+
+    #[test]
+    fn parses_access_tokens() {
+        let token: AccessToken = " admin:lab-secret ".parse().expect("valid token");
+        assert_eq!(token.role, AccessRole::Admin);
+        assert_eq!(token.token, "lab-secret");
+
+        let token: AccessToken = "Observer:a:b".parse().expect("valid token");
+        assert_eq!(token.role, AccessRole::Observer);
+        assert_eq!(token.token, "a:b");
+
+        assert!("admin".parse::<AccessToken>().is_err());
+        assert!("admin:".parse::<AccessToken>().is_err());
+        assert!("root:secret".parse::<AccessToken>().is_err());
+    }
+
+    #[test]
+    fn peers_get_the_role_of_their_token() {
+        let mut config = TransportConfig::default();
+        assert_eq!(config.role_for(None), Some(AccessRole::Admin));
+
+        config.access_tokens = vec![
+            "admin:lab-secret".parse().unwrap(),
+            "observer:look".parse().unwrap(),
+        ];
+        assert_eq!(config.role_for(Some("lab-secret")), Some(AccessRole::Admin));
+        assert_eq!(config.role_for(Some("look")), Some(AccessRole::Observer));
+        assert_eq!(config.role_for(Some("lab-secre")), None);
+        assert_eq!(config.role_for(None), None);
+
+        config.anonymous_role = Some(AccessRole::Observer);
+        assert_eq!(config.role_for(Some("guess")), Some(AccessRole::Observer));
+        assert_eq!(config.role_for(None), Some(AccessRole::Observer));
+    }
+
+    #[cfg(feature = "udp")]
+    #[test]
+    fn parses_listener_with_certificate() {
+        use super::{TransportListener, TransportMode};
+
+        let listener: TransportListener = "udp@0.0.0.0:6210;cert=cert.pem;key=key.pem"
+            .parse()
+            .expect("valid listener");
+
+        assert!(matches!(listener.mode, TransportMode::Udp));
+        assert_eq!(listener.addr.port(), 6210);
+        assert_eq!(listener.certificate_path.as_deref(), Some("cert.pem"));
+        assert_eq!(listener.certificate_key_path.as_deref(), Some("key.pem"));
+        assert!("udp:0.0.0.0:6210".parse::<TransportListener>().is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! In-process transport: clients and servers in the same process, linked by
+//! crossbeam channels instead of sockets.
+//!
+//! A loopback server registers its listen address in a process-wide table. A
+//! loopback client connecting to that address creates a channel pair and hands one
+//! end to the server, which spawns a link for it. The address is only a key; no
+//! socket is opened. The address is free again once the listener is dropped.
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        LazyLock, Mutex, MutexGuard,
+        atomic::{AtomicU64, Ordering},
+        mpsc::{Receiver, Sender},
+    },
+};
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_internal::prelude::Name;
+use bevy_log::info;
+use lightyear::prelude::{Connect, Link, LinkOf, LinkStart};
+use lightyear_crossbeam::{CrossbeamIo, CrossbeamPlugin};
+
+/// Loopback servers by listen address, with the id of the listener.
+type Listeners = HashMap<SocketAddr, (u64, Sender<CrossbeamIo>)>;
+
+static LISTENERS: LazyLock<Mutex<Listeners>> = LazyLock::new(Default::default);
+static NEXT_LISTENER: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) struct LoopbackPlugin;
+
+impl Plugin for LoopbackPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<CrossbeamPlugin>() {
+            app.add_plugins(CrossbeamPlugin);
+        }
+        app.add_systems(Update, (accept_loopback_links, connect_loopback_clients));
+    }
+}
+
+/// A loopback server; receives the server end of each new client's channel.
+#[derive(Component)]
+pub(crate) struct LoopbackListener {
+    addr: SocketAddr,
+    id: u64,
+    links: Mutex<Receiver<CrossbeamIo>>,
+}
+
+impl LoopbackListener {
+    /// Register a server at `addr`, replacing any earlier one.
+    pub(crate) fn listen(addr: SocketAddr) -> Self {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let id = NEXT_LISTENER.fetch_add(1, Ordering::Relaxed);
+        listeners().insert(addr, (id, tx));
+        Self {
+            addr,
+            id,
+            links: Mutex::new(rx),
+        }
+    }
+}
+
+impl Drop for LoopbackListener {
+    fn drop(&mut self) {
+        let mut listeners = listeners();
+        // a later listener may have replaced this one
+        if listeners
+            .get(&self.addr)
+            .is_some_and(|(id, _)| *id == self.id)
+        {
+            listeners.remove(&self.addr);
+        }
+    }
+}
+
+fn listeners() -> MutexGuard<'static, Listeners> {
+    LISTENERS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A loopback client, linked once a server listens on `addr`.
+#[derive(Component)]
+pub(crate) struct LoopbackClient {
+    pub(crate) addr: SocketAddr,
+}
+
+fn connect(addr: SocketAddr) -> Option<CrossbeamIo> {
+    let listeners = listeners();
+    let (_, server) = listeners.get(&addr)?;
+    let (client_io, server_io) = CrossbeamIo::new_pair();
+    server.send(server_io).ok()?;
+    Some(client_io)
+}
+
+fn accept_loopback_links(mut commands: Commands, listeners: Query<(Entity, &LoopbackListener)>) {
+    for (server, listener) in &listeners {
+        let Ok(rx) = listener.links.lock() else {
+            continue;
+        };
+        for io in rx.try_iter() {
+            let entity = commands
+                .spawn((
+                    Name::from("LoopbackLink"),
+                    LinkOf { server },
+                    Link::new(None),
+                    io,
+                ))
+                .id();
+            commands.trigger(LinkStart { entity });
+        }
+    }
+}
+
+/// Link clients whose server has started. Retried every frame until it has.
+fn connect_loopback_clients(
+    mut commands: Commands,
+    clients: Query<(Entity, &LoopbackClient), Without<CrossbeamIo>>,
+) {
+    for (entity, client) in &clients {
+        if let Some(io) = connect(client.addr) {
+            info!("transport: loopback link to {}", client.addr);
+            commands.entity(entity).insert(io);
+            commands.trigger(Connect { entity });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::UdpSocket;
+
+    use super::{LoopbackListener, connect};
+
+    #[test]
+    fn listeners_free_their_address_when_dropped() {
+        let addr = UdpSocket::bind("127.0.0.1:0")
+            .and_then(|socket| socket.local_addr())
+            .unwrap();
+        let first = LoopbackListener::listen(addr);
+        let second = LoopbackListener::listen(addr);
+        assert!(connect(addr).is_some());
+
+        // the replaced listener leaves the address to its successor
+        drop(first);
+        assert!(connect(addr).is_some());
+        drop(second);
+        assert!(connect(addr).is_none());
+    }
+}
@@ -0,0 +1,169 @@
+//! Per-connection traffic counters, reported by `ProtoRequest::Stats` and the
+//! viewer's diagnostics tab.
+//!
+//! Every link carries a [`LinkStats`]. Systems that send or receive a message
+//! record it there; the transport counts its own hellos, heartbeats and bulk
+//! chunks, and measures the round-trip time from heartbeat pongs.
+
+use std::collections::BTreeMap;
+
+use bevy_ecs::prelude::*;
+use bevy_internal::time::{Real, Time, Timer, TimerMode};
+use dimensify_protocol::{ChannelStats, MessageChannel, PeerStats};
+use lightyear::prelude::PeerAddr;
+
+use crate::TransportPeer;
+
+/// Message counts and round-trip time of one link.
+#[derive(Component, Debug, Default)]
+pub struct LinkStats {
+    channels: BTreeMap<MessageChannel, ChannelCounter>,
+    errors: u64,
+    rtt_secs: Option<f64>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct ChannelCounter {
+    sent: u64,
+    received: u64,
+    /// Counts at the last rate update.
+    sent_mark: u64,
+    received_mark: u64,
+    sent_per_sec: f32,
+    received_per_sec: f32,
+}
+
+impl LinkStats {
+    pub fn sent(&mut self, channel: MessageChannel) {
+        self.channels.entry(channel).or_default().sent += 1;
+    }
+
+    pub fn received(&mut self, channel: MessageChannel) {
+        self.channels.entry(channel).or_default().received += 1;
+    }
+
+    /// Record an error response or data that could not be used.
+    pub fn error(&mut self) {
+        self.errors += 1;
+    }
+
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    pub fn rtt_secs(&self) -> Option<f64> {
+        self.rtt_secs
+    }
+
+    pub(crate) fn set_rtt(&mut self, secs: f64) {
+        self.rtt_secs = Some(secs);
+    }
+
+    /// Requests received but not answered, plus requests sent but not answered.
+    pub fn pending_requests(&self) -> u64 {
+        let count = |channel| self.channels.get(&channel).copied().unwrap_or_default();
+        let (requests, responses) = (
+            count(MessageChannel::Requests),
+            count(MessageChannel::Responses),
+        );
+        requests.received.saturating_sub(responses.sent)
+            + requests.sent.saturating_sub(responses.received)
+    }
+
+    pub fn channels(&self) -> Vec<ChannelStats> {
+        self.channels
+            .iter()
+            .map(|(channel, counter)| ChannelStats {
+                channel: *channel,
+                sent: counter.sent,
+                received: counter.received,
+                sent_per_sec: counter.sent_per_sec,
+                received_per_sec: counter.received_per_sec,
+            })
+            .collect()
+    }
+
+    fn update_rates(&mut self, elapsed_secs: f32) {
+        for counter in self.channels.values_mut() {
+            counter.sent_per_sec = (counter.sent - counter.sent_mark) as f32 / elapsed_secs;
+            counter.received_per_sec =
+                (counter.received - counter.received_mark) as f32 / elapsed_secs;
+            counter.sent_mark = counter.sent;
+            counter.received_mark = counter.received;
+        }
+    }
+}
+
+/// Query data for [`peer_stats`].
+pub type PeerStatsData = (
+    Entity,
+    &'static LinkStats,
+    Option<&'static PeerAddr>,
+    Option<&'static TransportPeer>,
+);
+
+/// Statistics of one link, e.g. `links.iter().map(peer_stats)` over a `Query<PeerStatsData>`.
+pub fn peer_stats(
+    (entity, stats, addr, peer): (
+        Entity,
+        &LinkStats,
+        Option<&PeerAddr>,
+        Option<&TransportPeer>,
+    ),
+) -> PeerStats {
+    PeerStats {
+        entity,
+        addr: addr.map(|addr| addr.0),
+        endpoint: peer.map(|peer| format!("{:?}", peer.endpoint)),
+        role: peer.and_then(|peer| peer.role),
+        rtt_ms: stats.rtt_secs.map(|secs| (secs * 1000.0) as f32),
+        pending_requests: stats.pending_requests(),
+        errors: stats.errors,
+        channels: stats.channels(),
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct StatsTimer(Timer);
+
+impl Default for StatsTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(1.0, TimerMode::Repeating))
+    }
+}
+
+pub(crate) fn update_link_rates(
+    time: Res<Time<Real>>,
+    mut timer: ResMut<StatsTimer>,
+    mut links: Query<&mut LinkStats>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let elapsed = timer.0.duration().as_secs_f32();
+    for mut stats in &mut links {
+        stats.update_rates(elapsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkStats;
+    use dimensify_protocol::MessageChannel;
+
+    #[test]
+    fn counts_pending_requests_in_both_directions() {
+        let mut stats = LinkStats::default();
+        for _ in 0..3 {
+            stats.received(MessageChannel::Requests);
+        }
+        stats.sent(MessageChannel::Responses);
+        stats.sent(MessageChannel::Requests);
+        assert_eq!(stats.pending_requests(), 3);
+
+        stats.update_rates(0.5);
+        let requests = &stats.channels()[0];
+        assert_eq!(requests.channel, MessageChannel::Requests);
+        assert_eq!(requests.received_per_sec, 6.0);
+    }
+}
@@ -5,17 +5,32 @@ use bevy_internal::{
     time::{Time, Timer, TimerMode},
 };
 use bevy_log::{LogPlugin, info};
-use dimensify_protocol::{ProtoRequest, ProtoResponse, TransportError};
+use dimensify_protocol::{
+    BulkCompression, BulkData, BulkHeader, MessageChannel, ProtoRequest, ProtoResponse, SceneSync,
+    TransportError, ViewerEvent,
+};
 use lightyear::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::VecDeque,
     sync::{
-        Mutex,
-        mpsc::{Receiver, Sender},
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{Receiver, RecvTimeoutError, Sender},
     },
-    time::Duration,
+    time::{Duration, Instant},
+};
+
+use crate::auth::authenticate_peers;
+use crate::bulk::{BulkAssembler, bulk_chunks};
+use crate::connection::{
+    ClientLiveness, ConnectionState, HeartbeatTimer, TransportHeartbeat, TransportStatus,
+    receive_heartbeats, send_heartbeats, track_client_connection,
 };
+use crate::stats::{LinkStats, StatsTimer, update_link_rates};
 
+#[cfg(feature = "loopback")]
+use crate::loopback::{LoopbackClient, LoopbackListener, LoopbackPlugin};
 #[cfg(all(feature = "webtransport", not(target_family = "wasm")))]
 use lightyear::prelude::Identity;
 #[cfg(feature = "udp")]
@@ -34,11 +49,27 @@ use lightyear::prelude::{
 #[cfg(feature = "udp")]
 use lightyear_udp::UdpPlugin;
 
+/// One chunk of a bulk transfer (see [`bulk_chunks`](crate::bulk_chunks)).
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct StreamBytes {
+    /// Transfer this chunk belongs to, unique per sender.
+    pub transfer: u64,
+    /// Sent with the first chunk.
+    pub header: Option<BulkHeader>,
+    pub index: u32,
+    pub count: u32,
     pub payload: Vec<u8>,
 }
 
+/// Sent once by every client after connecting, so that a hub can tell viewers and
+/// controllers apart. Viewer servers use it to find viewers that want to mirror them.
+/// Servers read it into a [`TransportPeer`](crate::TransportPeer).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TransportHello {
+    pub endpoint: crate::TransportEndpoint,
+    pub token: Option<String>,
+}
+
 pub struct StreamReliable;
 
 pub struct StreamUnreliable;
@@ -47,6 +78,10 @@ pub fn register_messages(app: &mut App) {
     app.register_message::<StreamBytes>();
     app.register_message::<ProtoRequest>();
     app.register_message::<ProtoResponse>();
+    app.register_message::<TransportHello>();
+    app.register_message::<SceneSync>();
+    app.register_message::<ViewerEvent>();
+    app.register_message::<TransportHeartbeat>();
 
     app.add_channel::<StreamReliable>(ChannelSettings {
         mode: ChannelMode::OrderedReliable(ReliableSettings::default()),
@@ -84,7 +119,18 @@ impl Default for TransportRuntimePlugin {
 impl Plugin for TransportRuntimePlugin {
     fn build(&self, app: &mut App) {
         register_messages(app);
-        app.insert_resource(self.config.clone());
+        app.insert_resource(self.config.clone())
+            .init_resource::<TransportStatus>()
+            .insert_resource(HeartbeatTimer::new(&self.config))
+            .init_resource::<StatsTimer>()
+            .add_systems(
+                Update,
+                (send_heartbeats, receive_heartbeats, update_link_rates),
+            );
+        #[cfg(feature = "loopback")]
+        if !app.is_plugin_added::<LoopbackPlugin>() {
+            app.add_plugins(LoopbackPlugin);
+        }
 
         match self.config.connection {
             crate::TransportConnection::Server => {
@@ -92,7 +138,10 @@ impl Plugin for TransportRuntimePlugin {
                     tick_duration: Duration::from_secs_f32(1.0 / self.config.tick_hz),
                 });
                 app.add_observer(insert_message_components_for_linkof);
-                app.add_systems(Update, ensure_message_components_for_linkof);
+                app.add_systems(
+                    Update,
+                    (ensure_message_components_for_linkof, authenticate_peers),
+                );
             }
             crate::TransportConnection::Client => {
                 app.add_plugins(ClientPlugins {
@@ -103,6 +152,14 @@ impl Plugin for TransportRuntimePlugin {
                 {
                     app.add_plugins(UdpPlugin);
                 }
+                app.insert_resource(ClientLiveness::new(&self.config))
+                    .add_systems(
+                        Update,
+                        (
+                            send_hello,
+                            track_client_connection.after(receive_heartbeats),
+                        ),
+                    );
             }
         }
 
@@ -114,79 +171,285 @@ impl Plugin for TransportRuntimePlugin {
     }
 }
 
+/// Poll interval of [`TransportController::send_and_wait`] while checking the connection.
+const WAIT_POLL: Duration = Duration::from_millis(50);
+
+/// Bulk chunks handed to the connection per frame, so requests are not starved.
+const BULK_CHUNKS_PER_FRAME: usize = 64;
+
+/// Called on the transport thread with the response to a request, or the error that
+/// ended the wait (see [`TransportController::submit`]).
+pub type ResponseCallback = Box<dyn FnOnce(Result<ProtoResponse, TransportError>) + Send + Sync>;
+
+/// Runs a transport client on its own thread and exchanges requests with it over channels.
+///
+/// Viewers and hubs answer the requests of a connection in order, so responses are
+/// matched to requests by position. Dropping the controller disconnects and stops the
+/// thread.
 pub struct TransportController {
-    request_tx: Sender<ProtoRequest>,
+    request_tx: Sender<(ProtoRequest, Option<ResponseCallback>)>,
+    bulk_tx: Sender<StreamBytes>,
     response_rx: Receiver<ProtoResponse>,
-    _handle: std::thread::JoinHandle<()>,
+    event_rx: Receiver<ViewerEvent>,
+    next_transfer: AtomicU64,
+    status: TransportStatus,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<std::thread::JoinHandle<()>>,
 }
 
 impl TransportController {
     pub fn start(config: crate::TransportConfig) -> Self {
         let (request_tx, request_rx) = std::sync::mpsc::channel();
+        let (bulk_tx, bulk_rx) = std::sync::mpsc::channel();
         let (response_tx, response_rx) = std::sync::mpsc::channel();
+        let (event_tx, event_rx) = std::sync::mpsc::channel();
+        let status = TransportStatus::default();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (thread_status, thread_shutdown) = (status.clone(), shutdown.clone());
         let handle = std::thread::spawn(move || {
             let mut app = App::new();
             app.add_plugins(MinimalPlugins);
             if transport_debug_enabled() {
                 app.add_plugins(LogPlugin::default());
             }
+            app.insert_resource(thread_status);
             app.add_plugins(TransportRuntimePlugin { config });
 
             app.insert_resource(TransportQueue {
                 request_rx: Mutex::new(request_rx),
+                bulk_rx: Mutex::new(bulk_rx),
                 response_tx,
+                event_tx,
                 pending: Vec::new(),
+                in_flight: VecDeque::new(),
+                pending_bulk: VecDeque::new(),
             });
 
-            app.add_systems(Update, send_requests);
-            app.add_systems(Update, collect_responses);
+            app.add_systems(Update, (send_requests, send_bulk.after(send_requests)));
+            app.add_systems(Update, (collect_responses, collect_events));
             // Ensure plugin finish hooks run (MessagePlugin/TransportPlugin build systems here).
             app.finish();
             app.cleanup();
 
-            loop {
+            while !thread_shutdown.load(Ordering::Relaxed) {
                 app.update();
                 std::thread::sleep(Duration::from_millis(16));
             }
+            disconnect_clients(&mut app);
+            if let Some(mut queue) = app.world_mut().get_resource_mut::<TransportQueue>() {
+                queue.fail_all(|| TransportError::Closed("shut down".to_string()));
+            }
         });
 
         Self {
             request_tx,
+            bulk_tx,
             response_rx,
-            _handle: handle,
+            event_rx,
+            next_transfer: AtomicU64::new(0),
+            status,
+            shutdown,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.status.get()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state() == ConnectionState::Connected
+    }
+
+    /// Block until connected, the connection is closed, or `timeout` passes.
+    pub fn wait_connected(&self, timeout: Duration) -> Result<(), TransportError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.state() {
+                ConnectionState::Connected => return Ok(()),
+                ConnectionState::Closed { reason } => return Err(TransportError::Closed(reason)),
+                state if Instant::now() >= deadline => {
+                    return Err(TransportError::NotConnected(state.to_string()));
+                }
+                _ => std::thread::sleep(WAIT_POLL),
+            }
+        }
+    }
+
+    /// Queue a request. Requests queued while reconnecting are sent once connected.
+    /// The response is returned by [`try_recv`](Self::try_recv).
+    pub fn send(&self, request: ProtoRequest) -> Result<(), TransportError> {
+        self.queue(request, None)
+    }
+
+    /// Queue a request and call `on_response` on the transport thread once it is answered.
+    ///
+    /// Any number of requests may be in flight. Requests that were sent when the
+    /// connection is lost fail with [`TransportError::NotConnected`]; queued ones are
+    /// sent after reconnecting. Everything fails with [`TransportError::Closed`] on close.
+    pub fn submit(
+        &self,
+        request: ProtoRequest,
+        on_response: ResponseCallback,
+    ) -> Result<(), TransportError> {
+        self.queue(request, Some(on_response))
+    }
+
+    fn queue(
+        &self,
+        request: ProtoRequest,
+        on_response: Option<ResponseCallback>,
+    ) -> Result<(), TransportError> {
+        if let ConnectionState::Closed { reason } = self.state() {
+            return Err(TransportError::Closed(reason));
         }
+        self.request_tx
+            .send((request, on_response))
+            .map_err(|_| TransportError::Closed("transport thread stopped".to_string()))
     }
 
-    pub fn send(&self, request: ProtoRequest) -> Result<(), String> {
-        self.request_tx.send(request).map_err(|err| err.to_string())
+    /// Send large data for an existing entity in chunks, without waiting for it to arrive.
+    /// The viewer logs data it cannot apply.
+    pub fn send_bulk(
+        &self,
+        entity: Entity,
+        data: &BulkData,
+        compression: BulkCompression,
+    ) -> Result<(), TransportError> {
+        if let ConnectionState::Closed { reason } = self.state() {
+            return Err(TransportError::Closed(reason));
+        }
+        let transfer = self.next_transfer.fetch_add(1, Ordering::Relaxed);
+        for chunk in bulk_chunks(transfer, entity, data, compression)? {
+            self.bulk_tx
+                .send(chunk)
+                .map_err(|_| TransportError::Closed("transport thread stopped".to_string()))?;
+        }
+        Ok(())
     }
 
+    /// Send a request and wait for its response.
+    ///
+    /// Fails early with [`TransportError::Closed`] if the connection closes while waiting,
+    /// and with [`TransportError::NotConnected`] rather than `Timeout` if the time ran out
+    /// while disconnected. A response arriving after the timeout is discarded.
     pub fn send_and_wait(
         &self,
         request: ProtoRequest,
         timeout: Duration,
-    ) -> Result<ProtoResponse, String> {
-        self.send(request)?;
-        self.response_rx
-            .recv_timeout(timeout)
-            .map_err(|err| err.to_string())
+    ) -> Result<ProtoResponse, TransportError> {
+        let (response_tx, response_rx) = std::sync::mpsc::channel();
+        self.submit(
+            request,
+            Box::new(move |response| {
+                let _ = response_tx.send(response);
+            }),
+        )?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(match self.state() {
+                    ConnectionState::Connected => TransportError::Timeout(timeout),
+                    state => TransportError::NotConnected(state.to_string()),
+                });
+            }
+            match response_rx.recv_timeout(remaining.min(WAIT_POLL)) {
+                Ok(response) => return response,
+                Err(RecvTimeoutError::Timeout) => {
+                    if let ConnectionState::Closed { reason } = self.state() {
+                        return Err(TransportError::Closed(reason));
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(TransportError::Closed(
+                        "transport thread stopped".to_string(),
+                    ));
+                }
+            }
+        }
     }
 
+    /// Next response to a request queued with [`send`](Self::send), if any.
     pub fn try_recv(&self) -> Option<ProtoResponse> {
         self.response_rx.try_recv().ok()
     }
+
+    /// Next pushed viewer event, if any (see `ProtoRequest::Subscribe`).
+    pub fn try_recv_event(&self) -> Option<ViewerEvent> {
+        self.event_rx.try_recv().ok()
+    }
+
+    /// Disconnect and stop the transport thread. Later requests fail with `Closed`.
+    pub fn close(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        self.status.set(ConnectionState::Closed {
+            reason: "shut down".to_string(),
+        });
+    }
+}
+
+impl Drop for TransportController {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Tell the server we are leaving, and give the disconnect a frame to go out.
+fn disconnect_clients(app: &mut App) {
+    let world = app.world_mut();
+    let clients: Vec<Entity> = world
+        .query_filtered::<Entity, With<Client>>()
+        .iter(world)
+        .collect();
+    for entity in clients {
+        world.trigger(Disconnect { entity });
+    }
+    app.update();
 }
 
 #[derive(Resource)]
 struct TransportQueue {
-    request_rx: Mutex<Receiver<ProtoRequest>>,
+    request_rx: Mutex<Receiver<(ProtoRequest, Option<ResponseCallback>)>>,
+    bulk_rx: Mutex<Receiver<StreamBytes>>,
     response_tx: Sender<ProtoResponse>,
-    pending: Vec<ProtoRequest>,
+    event_tx: Sender<ViewerEvent>,
+    pending: Vec<(ProtoRequest, Option<ResponseCallback>)>,
+    /// Sent requests in order; `None` answers go to `response_tx`.
+    in_flight: VecDeque<Option<ResponseCallback>>,
+    pending_bulk: VecDeque<StreamBytes>,
+}
+
+impl TransportQueue {
+    fn fail_in_flight(&mut self, error: impl Fn() -> TransportError) {
+        for on_response in self.in_flight.drain(..).flatten() {
+            on_response(Err(error()));
+        }
+    }
+
+    /// Fail everything sent or queued, including requests not picked up yet.
+    fn fail_all(&mut self, error: impl Fn() -> TransportError) {
+        self.fail_in_flight(&error);
+        let queued: Vec<_> = match self.request_rx.lock() {
+            Ok(rx) => rx.try_iter().collect(),
+            Err(_) => Vec::new(),
+        };
+        self.pending.extend(queued);
+        for (_, on_response) in self.pending.drain(..) {
+            if let Some(on_response) = on_response {
+                on_response(Err(error()));
+            }
+        }
+    }
 }
 
 fn send_requests(
     mut queue: ResMut<TransportQueue>,
-    mut senders: Query<&mut MessageSender<ProtoRequest>, With<Connected>>,
+    status: Res<TransportStatus>,
+    mut senders: Query<(&mut MessageSender<ProtoRequest>, &mut LinkStats), With<Connected>>,
 ) {
     let mut drained = Vec::new();
     if let Ok(rx) = queue.request_rx.lock() {
@@ -198,11 +461,25 @@ fn send_requests(
         queue.pending.extend(drained);
     }
 
+    // answers to requests sent on a lost connection never arrive
+    if senders.is_empty() && !queue.in_flight.is_empty() {
+        let state = status.get();
+        queue.fail_in_flight(|| TransportError::NotConnected(state.to_string()));
+    }
     if queue.pending.is_empty() {
         return;
     }
+    if let ConnectionState::Closed { reason } = status.get() {
+        bevy_log::warn!(
+            "transport: dropping {} queued requests ({})",
+            queue.pending.len(),
+            reason
+        );
+        queue.fail_all(|| TransportError::Closed(reason.clone()));
+        return;
+    }
 
-    let mut sender = match senders.iter_mut().next() {
+    let (mut sender, mut stats) = match senders.iter_mut().next() {
         Some(sender) => sender,
         None => {
             if transport_debug_enabled() {
@@ -212,18 +489,68 @@ fn send_requests(
         }
     };
 
-    for request in queue.pending.drain(..) {
+    let queue = &mut *queue;
+    for (request, on_response) in queue.pending.drain(..) {
         sender.send::<StreamReliable>(request);
+        stats.sent(MessageChannel::Requests);
+        queue.in_flight.push_back(on_response);
+    }
+}
+
+fn send_bulk(
+    mut queue: ResMut<TransportQueue>,
+    status: Res<TransportStatus>,
+    mut senders: Query<(&mut MessageSender<StreamBytes>, &mut LinkStats), With<Connected>>,
+) {
+    let queue = &mut *queue;
+    if let Ok(rx) = queue.bulk_rx.lock() {
+        queue.pending_bulk.extend(rx.try_iter());
+    }
+    if queue.pending_bulk.is_empty() {
+        return;
+    }
+    if let ConnectionState::Closed { .. } = status.get() {
+        queue.pending_bulk.clear();
+        return;
+    }
+    let Some((mut sender, mut stats)) = senders.iter_mut().next() else {
+        return;
+    };
+    let count = queue.pending_bulk.len().min(BULK_CHUNKS_PER_FRAME);
+    for chunk in queue.pending_bulk.drain(..count) {
+        sender.send::<StreamReliable>(chunk);
+        stats.sent(MessageChannel::Bulk);
     }
 }
 
 fn collect_responses(
-    queue: Res<TransportQueue>,
-    mut receivers: Query<&mut MessageReceiver<ProtoResponse>, With<Connected>>,
+    mut queue: ResMut<TransportQueue>,
+    mut receivers: Query<(&mut MessageReceiver<ProtoResponse>, &mut LinkStats), With<Connected>>,
 ) {
-    for mut receiver in &mut receivers {
+    for (mut receiver, mut stats) in &mut receivers {
         for response in receiver.receive() {
-            let _ = queue.response_tx.send(response);
+            stats.received(MessageChannel::Responses);
+            if let ProtoResponse::Error { .. } = response {
+                stats.error();
+            }
+            match queue.in_flight.pop_front().flatten() {
+                Some(on_response) => on_response(Ok(response)),
+                None => {
+                    let _ = queue.response_tx.send(response);
+                }
+            }
+        }
+    }
+}
+
+fn collect_events(
+    queue: Res<TransportQueue>,
+    mut receivers: Query<(&mut MessageReceiver<ViewerEvent>, &mut LinkStats), With<Connected>>,
+) {
+    for (mut receiver, mut stats) in &mut receivers {
+        for event in receiver.receive() {
+            stats.received(MessageChannel::Events);
+            let _ = queue.event_tx.send(event);
         }
     }
 }
@@ -231,12 +558,21 @@ fn collect_responses(
 fn setup_transport_endpoint(
     mut commands: Commands,
     config: Res<crate::TransportConfig>,
+    status: Res<TransportStatus>,
 ) -> Result<(), BevyError> {
-    let entity = match config.connection {
-        crate::TransportConnection::Server => spawn_server(&mut commands, config.as_ref())?,
-        crate::TransportConnection::Client => spawn_client(&mut commands, config.as_ref()),
-    };
-    commands.trigger(Connect { entity });
+    match config.connection {
+        crate::TransportConnection::Server => {
+            for listener in config.listeners() {
+                let entity = spawn_server(&mut commands, config.as_ref(), &listener)?;
+                commands.trigger(Connect { entity });
+            }
+            status.set(ConnectionState::Connected);
+        }
+        crate::TransportConnection::Client => {
+            let entity = spawn_client(&mut commands, config.as_ref());
+            commands.trigger(Connect { entity });
+        }
+    }
     Ok(())
 }
 
@@ -291,19 +627,24 @@ fn debug_transport_state(
 fn spawn_server(
     commands: &mut Commands,
     config: &crate::TransportConfig,
+    listener: &crate::TransportListener,
 ) -> Result<Entity, TransportError> {
+    info!(
+        "transport: listening on {} ({:?})",
+        listener.addr, listener.mode
+    );
     let mut entity = commands.spawn((
         Name::from("TransportServer"),
         Server::default(),
         Link::new(None),
-        LocalAddr(config.server_addr),
+        LocalAddr(listener.addr),
         RawServer,
     ));
 
-    match config.mode {
+    match listener.mode {
         #[cfg(feature = "webtransport")]
         crate::TransportMode::WebTransport => {
-            let certificate = load_certificate(config);
+            let certificate = load_certificate(config, listener);
             entity.insert(WebTransportServerIo { certificate });
         }
         #[cfg(feature = "websocket")]
@@ -315,13 +656,21 @@ fn spawn_server(
         crate::TransportMode::Udp => {
             entity.insert(ServerUdpIo::default());
         }
+        #[cfg(feature = "loopback")]
+        crate::TransportMode::Loopback => {
+            entity.insert(LoopbackListener::listen(listener.addr));
+        }
     }
 
     Ok(entity.id())
 }
 
 #[cfg(target_family = "wasm")]
-fn spawn_server(_commands: &mut Commands, _config: &crate::TransportConfig) -> Entity {
+fn spawn_server(
+    _commands: &mut Commands,
+    _config: &crate::TransportConfig,
+    _listener: &crate::TransportListener,
+) -> Entity {
     panic!("transport server is not supported on wasm");
 }
 
@@ -355,6 +704,12 @@ fn spawn_client(commands: &mut Commands, config: &crate::TransportConfig) -> Ent
             entity.insert(LocalAddr(local_addr));
             entity.insert(UdpIo::default());
         }
+        #[cfg(feature = "loopback")]
+        crate::TransportMode::Loopback => {
+            entity.insert(LoopbackClient {
+                addr: config.server_addr,
+            });
+        }
     }
 
     let endpoint = config.endpoint.clone();
@@ -372,25 +727,80 @@ fn insert_message_components_for_linkof(
 }
 
 fn insert_message_components(entity: &mut EntityCommands, endpoint: &crate::TransportEndpoint) {
-    entity.insert(MessageManager::default());
+    entity.insert((
+        MessageManager::default(),
+        MessageSender::<TransportHello>::default(),
+        MessageReceiver::<TransportHello>::default(),
+        MessageSender::<TransportHeartbeat>::default(),
+        MessageReceiver::<TransportHeartbeat>::default(),
+        LinkStats::default(),
+    ));
     match endpoint {
         crate::TransportEndpoint::Viewer => {
             entity.insert(MessageReceiver::<ProtoRequest>::default());
             entity.insert(MessageSender::<ProtoResponse>::default());
+            // viewer servers push scene state to viewer clients that mirror them
+            entity.insert(MessageSender::<SceneSync>::default());
+            entity.insert(MessageReceiver::<SceneSync>::default());
+            entity.insert(MessageSender::<ViewerEvent>::default());
+            entity.insert((
+                MessageReceiver::<StreamBytes>::default(),
+                BulkAssembler::default(),
+            ));
         }
         crate::TransportEndpoint::Controller => {
             entity.insert(MessageReceiver::<ProtoResponse>::default());
             entity.insert(MessageSender::<ProtoRequest>::default());
+            entity.insert(MessageReceiver::<ViewerEvent>::default());
+            entity.insert(MessageSender::<StreamBytes>::default());
+        }
+        crate::TransportEndpoint::Hub => {
+            entity.insert((
+                MessageReceiver::<ProtoRequest>::default(),
+                MessageSender::<ProtoResponse>::default(),
+                MessageReceiver::<ProtoResponse>::default(),
+                MessageSender::<ProtoRequest>::default(),
+                MessageReceiver::<ViewerEvent>::default(),
+                MessageSender::<ViewerEvent>::default(),
+                // relayed bulk transfers
+                MessageReceiver::<StreamBytes>::default(),
+                MessageSender::<StreamBytes>::default(),
+            ));
         }
     }
 }
 
+/// Marks a client that has announced its endpoint.
+#[derive(Component)]
+pub(crate) struct HelloSent;
+
+fn send_hello(
+    mut commands: Commands,
+    config: Res<crate::TransportConfig>,
+    mut clients: Query<
+        (Entity, &mut MessageSender<TransportHello>, &mut LinkStats),
+        (With<Client>, With<Connected>, Without<HelloSent>),
+    >,
+) {
+    for (entity, mut sender, mut stats) in &mut clients {
+        sender.send::<StreamReliable>(TransportHello {
+            endpoint: config.endpoint.clone(),
+            token: config.token.clone(),
+        });
+        stats.sent(MessageChannel::Hello);
+        commands.entity(entity).insert(HelloSent);
+    }
+}
+
 fn ensure_message_components_for_linkof(
     config: Res<crate::TransportConfig>,
     mut commands: Commands,
     query: Query<(Entity, &LinkOf), Without<MessageManager>>,
 ) {
-    if !matches!(config.endpoint, crate::TransportEndpoint::Viewer) {
+    if !matches!(
+        config.endpoint,
+        crate::TransportEndpoint::Viewer | crate::TransportEndpoint::Hub
+    ) {
         return;
     }
     for (entity, _link_of) in &query {
@@ -400,10 +810,19 @@ fn ensure_message_components_for_linkof(
 }
 
 #[cfg(all(feature = "webtransport", not(target_family = "wasm")))]
-fn load_certificate(config: &crate::TransportConfig) -> Identity {
-    if let (Some(cert_path), Some(key_path)) =
-        (&config.certificate_path, &config.certificate_key_path)
-    {
+fn load_certificate(
+    config: &crate::TransportConfig,
+    listener: &crate::TransportListener,
+) -> Identity {
+    let cert_path = listener
+        .certificate_path
+        .as_ref()
+        .or(config.certificate_path.as_ref());
+    let key_path = listener
+        .certificate_key_path
+        .as_ref()
+        .or(config.certificate_key_path.as_ref());
+    if let (Some(cert_path), Some(key_path)) = (cert_path, key_path) {
         return pollster::block_on(Identity::load_pemfiles(cert_path, key_path))
             .expect("failed to load transport certificate");
     }
@@ -412,6 +831,6 @@ fn load_certificate(config: &crate::TransportConfig) -> Identity {
 }
 
 #[cfg(any(not(feature = "webtransport"), target_family = "wasm"))]
-fn load_certificate(_config: &crate::TransportConfig) -> ! {
+fn load_certificate(_config: &crate::TransportConfig, _listener: &crate::TransportListener) -> ! {
     panic!("transport server certificates are not supported on wasm");
 }
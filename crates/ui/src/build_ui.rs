@@ -11,8 +11,12 @@ use bevy_egui::{
 use egui_tiles::{self, Tree};
 
 use crate::{
+    command_palette::{self, PaletteAction, PaletteActions},
+    console, diagnostics,
     layout::{self, DevUiLayoutSnapshot},
-    layout_kdl, layout_runtime, style, tabs,
+    layout_kdl, layout_runtime,
+    project::{self, ProjectDialogMode},
+    style, tabs,
 };
 use bevy_inspector_egui::DefaultInspectorConfigPlugin;
 
@@ -29,10 +33,18 @@ pub fn setup_ui(app: &mut App) {
     app.init_resource::<tabs::InspectorSelectionState>()
         .init_resource::<tabs::DockUiState>()
         .init_resource::<tabs::PanelRegistry>()
+        .init_resource::<tabs::DiagnosticsSections>()
+        .init_resource::<console::ConsoleLog>()
+        .init_resource::<console::ConsoleCommands>()
+        .init_resource::<project::ProjectDialog>()
+        .add_message::<project::ProjectRequest>()
+        .init_resource::<PaletteActions>()
+        .init_resource::<command_palette::CommandPalette>()
         .init_resource::<UiPanelVisibility>()
         .init_resource::<PanelLayoutDirty>()
         .init_resource::<crate::pane_widgets::PaneWidgetStates>()
         .init_state::<tabs::DevUiState>()
+        .add_plugins(diagnostics::plugin)
         .add_systems(PreStartup, no_egui_primary_context)
         .add_systems(Startup, (setup_panel_registry, setup_editor_layout).chain())
         .add_systems(PostStartup, setup_cameras_and_egui_ctx)
@@ -40,7 +52,56 @@ pub fn setup_ui(app: &mut App) {
             PostStartup,
             apply_dimensify_style.after(setup_cameras_and_egui_ctx),
         )
+        .add_systems(PreUpdate, console::drain_captured_logs)
         .add_systems(EguiPrimaryContextPass, update_ui);
+    register_palette_actions(&mut app.world_mut().resource_mut::<PaletteActions>());
+}
+
+/// Palette actions for the menus and panels of the viewer itself.
+fn register_palette_actions(actions: &mut PaletteActions) {
+    actions.register(PaletteAction::new("Save layout", |world| {
+        layout_kdl::save_current_layout(world)
+    }));
+    actions.register(PaletteAction::new(
+        "Reload layout",
+        layout_kdl::reload_layout,
+    ));
+    for (label, mode) in [
+        ("Open project", ProjectDialogMode::Open),
+        ("Save project", ProjectDialogMode::Save),
+        ("Export glTF", ProjectDialogMode::ExportGltf),
+    ] {
+        actions.register(PaletteAction::new(label, move |world| {
+            world.resource_mut::<project::ProjectDialog>().open(mode)
+        }));
+    }
+    actions.register(PaletteAction::new("Toggle left panel", |world| {
+        let mut panels = world.resource_mut::<UiPanelVisibility>();
+        panels.show_left = !panels.show_left;
+    }));
+    actions.register(PaletteAction::new("Toggle right panel", |world| {
+        let mut panels = world.resource_mut::<UiPanelVisibility>();
+        panels.show_right = !panels.show_right;
+    }));
+    actions.register(PaletteAction::new("Toggle bottom panel", |world| {
+        let mut panels = world.resource_mut::<UiPanelVisibility>();
+        panels.show_bottom = !panels.show_bottom;
+    }));
+    actions.register_provider(std::sync::Arc::new(|world| {
+        world
+            .resource::<tabs::PanelRegistry>()
+            .entries()
+            .map(|entry| {
+                let title = entry.title.clone();
+                PaletteAction::new(format!("Toggle panel: {}", title), move |world| {
+                    let mut registry = world.resource_mut::<tabs::PanelRegistry>();
+                    let enabled = registry.is_enabled(&title);
+                    registry.set_enabled(&title, !enabled);
+                    world.resource_mut::<PanelLayoutDirty>().0 = true;
+                })
+            })
+            .collect()
+    }));
 }
 
 /// Camera that renders the 3D/2D scene in world space.
@@ -328,6 +389,73 @@ fn setup_editor_layout(mut commands: Commands, registry: Res<tabs::PanelRegistry
     });
 }
 
+/// Register a panel after startup, e.g. one created by a remote controller, and rebuild
+/// the docked layouts. A panel the layout file does not place is appended to the tabs of
+/// its location.
+pub struct RegisterPanel(pub tabs::PanelEntry);
+
+impl Command for RegisterPanel {
+    fn apply(self, world: &mut World) {
+        let entry = self.0;
+        if let Some(mut snapshot) = world.get_resource_mut::<DevUiLayoutSnapshot>() {
+            let snapshot = &mut *snapshot;
+            let placed = [
+                &snapshot.left,
+                &snapshot.right,
+                &snapshot.bottom,
+                &snapshot.dock,
+            ]
+            .iter()
+            .any(|node| node.contains(&entry.title));
+            let node = match entry.location {
+                tabs::PanelLocation::Left => Some(&mut snapshot.left),
+                tabs::PanelLocation::Right => Some(&mut snapshot.right),
+                tabs::PanelLocation::Bottom => Some(&mut snapshot.bottom),
+                tabs::PanelLocation::Floating => None,
+            };
+            match node {
+                _ if placed => {}
+                Some(layout::LayoutNode::Tabs(titles)) => titles.push(entry.title.to_string()),
+                Some(_) => warn!(
+                    "Cannot place panel '{}': its side is not a tabs layout",
+                    entry.title
+                ),
+                None => {}
+            }
+        }
+        let Some(mut registry) = world.get_resource_mut::<tabs::PanelRegistry>() else {
+            return;
+        };
+        registry.register(entry);
+        world.resource_mut::<PanelLayoutDirty>().0 = true;
+    }
+}
+
+/// Remove a panel registered with [`RegisterPanel`], and its place in the layout, and
+/// rebuild the docked layouts.
+pub struct UnregisterPanel(pub String);
+
+impl Command for UnregisterPanel {
+    fn apply(self, world: &mut World) {
+        if let Some(mut snapshot) = world.get_resource_mut::<DevUiLayoutSnapshot>() {
+            let snapshot = &mut *snapshot;
+            for node in [
+                &mut snapshot.left,
+                &mut snapshot.right,
+                &mut snapshot.bottom,
+                &mut snapshot.dock,
+            ] {
+                node.remove(&self.0);
+            }
+        }
+        let Some(mut registry) = world.get_resource_mut::<tabs::PanelRegistry>() else {
+            return;
+        };
+        registry.unregister(&self.0);
+        world.resource_mut::<PanelLayoutDirty>().0 = true;
+    }
+}
+
 fn setup_panel_registry(mut registry: ResMut<tabs::PanelRegistry>) {
     tabs::register_default_panels(&mut registry);
 }
@@ -460,6 +588,8 @@ fn update_ui(world: &mut World) -> Result {
     // let mut save_layout = false;
     // let mut reload_layout = false;
     let mut layout_save_reload = (false, false);
+    let mut project_dialog = None;
+    let mut open_palette = false;
     // let mut panel_toggles_changed = false;
     let mut panel_toggles = Vec::new();
     let mut panel_enabled = std::collections::HashMap::new();
@@ -469,8 +599,8 @@ fn update_ui(world: &mut World) -> Result {
     {
         let registry = world.resource::<tabs::PanelRegistry>();
         for entry in registry.entries() {
-            let enabled = registry.is_enabled(entry.title);
-            let floating = registry.is_floating(entry.title);
+            let enabled = registry.is_enabled(&entry.title);
+            let floating = registry.is_floating(&entry.title);
             panel_enabled.insert(entry.title.to_string(), enabled);
             panel_floating.insert(entry.title.to_string(), floating);
             panel_toggles.push((entry.title.to_string(), entry.location, enabled, floating));
@@ -480,8 +610,22 @@ fn update_ui(world: &mut World) -> Result {
         }
     }
 
-    let (top_height, panel_toggles_changed) =
-        menu_bar(ctx, world, &mut layout_save_reload, &mut panel_toggles);
+    let (top_height, panel_toggles_changed) = menu_bar(
+        ctx,
+        world,
+        &mut layout_save_reload,
+        &mut project_dialog,
+        &mut open_palette,
+        &mut panel_toggles,
+    );
+    if let Some(mode) = project_dialog {
+        world.resource_mut::<project::ProjectDialog>().open(mode);
+    }
+    if open_palette {
+        command_palette::open_command_palette(world);
+    }
+    project::project_dialog_ui(ctx, world);
+    command_palette::command_palette_ui(ctx, world);
 
     let (show_left, show_right, show_bottom) = {
         let panels = world.resource::<UiPanelVisibility>();
@@ -674,6 +818,8 @@ fn menu_bar(
     ctx: &mut egui::Context,
     world: &mut World,
     layout_save_reload: &mut (bool, bool),
+    project_dialog: &mut Option<ProjectDialogMode>,
+    open_palette: &mut bool,
     panel_toggles: &mut Vec<(String, tabs::PanelLocation, bool, bool)>,
 ) -> (f32, bool) {
     let mut panel_toggles_changed = false;
@@ -709,8 +855,18 @@ fn menu_bar(
                     ui.separator();
                     ui.menu_button("File", |ui| {
                         if ui.button("Open Project…").clicked() {
+                            *project_dialog = Some(ProjectDialogMode::Open);
+                            ui.close();
+                        }
+                        if ui.button("Save Project…").clicked() {
+                            *project_dialog = Some(ProjectDialogMode::Save);
                             ui.close();
                         }
+                        if ui.button("Export glTF…").clicked() {
+                            *project_dialog = Some(ProjectDialogMode::ExportGltf);
+                            ui.close();
+                        }
+                        ui.separator();
                         if ui.button("Save Layout").clicked() {
                             layout_save_reload.0 = true;
                             ui.close();
@@ -752,7 +908,15 @@ fn menu_bar(
                         });
                     });
                     ui.menu_button("Tools", |ui| {
-                        ui.label("Simulation controls coming soon");
+                        if ui
+                            .add(egui::Button::new("Command Palette…").shortcut_text(
+                                ui.ctx().format_shortcut(&command_palette::SHORTCUT),
+                            ))
+                            .clicked()
+                        {
+                            *open_palette = true;
+                            ui.close();
+                        }
                     });
                 });
 
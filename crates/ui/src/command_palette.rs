@@ -0,0 +1,333 @@
+//! The command palette: a fuzzy-searched list of viewer actions, opened with
+//! Ctrl+Shift+P (Cmd+Shift+P on macOS) or from the Tools menu.
+//!
+//! Plugins add actions with [`RegisterPaletteAction::register_palette_action`], or a
+//! [`RegisterPaletteAction::register_palette_provider`] for actions that depend on the scene, such as
+//! one per named entity. Providers run each time the palette opens.
+
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Runs an action with the text typed for it, empty for actions without an argument.
+pub type PaletteHandler = Arc<dyn Fn(&str, &mut World) + Send + Sync>;
+
+/// Actions derived from the current state of the world.
+pub type PaletteProvider = Arc<dyn Fn(&mut World) -> Vec<PaletteAction> + Send + Sync>;
+
+/// Opens and closes the palette.
+pub const SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers {
+        shift: true,
+        ..egui::Modifiers::COMMAND
+    },
+    egui::Key::P,
+);
+
+/// Rows shown below the search field.
+const MAX_ROWS: usize = 12;
+
+#[derive(Clone)]
+pub struct PaletteAction {
+    pub label: String,
+    /// Hint for the text the action asks for before running, e.g. a path.
+    pub argument: Option<&'static str>,
+    pub handler: PaletteHandler,
+}
+
+impl PaletteAction {
+    pub fn new(label: impl Into<String>, run: impl Fn(&mut World) + Send + Sync + 'static) -> Self {
+        Self {
+            label: label.into(),
+            argument: None,
+            handler: Arc::new(move |_, world| run(world)),
+        }
+    }
+
+    /// An action that asks for a line of text first.
+    pub fn with_argument(
+        label: impl Into<String>,
+        hint: &'static str,
+        run: impl Fn(&str, &mut World) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            label: label.into(),
+            argument: Some(hint),
+            handler: Arc::new(run),
+        }
+    }
+}
+
+/// Actions offered by the palette, registered by plugins.
+#[derive(Resource, Default, Clone)]
+pub struct PaletteActions {
+    actions: Vec<PaletteAction>,
+    providers: Vec<PaletteProvider>,
+}
+
+impl PaletteActions {
+    /// Register an action if none with this label is known.
+    pub fn register(&mut self, action: PaletteAction) {
+        if self.actions.iter().any(|known| known.label == action.label) {
+            return;
+        }
+        self.actions.push(action);
+    }
+
+    pub fn register_provider(&mut self, provider: PaletteProvider) {
+        self.providers.push(provider);
+    }
+
+    /// Registered actions followed by those of the providers.
+    pub fn collect(&self, world: &mut World) -> Vec<PaletteAction> {
+        let mut actions = self.actions.clone();
+        for provider in &self.providers {
+            actions.extend(provider(world));
+        }
+        actions
+    }
+}
+
+/// Registers palette actions from plugin build functions.
+pub trait RegisterPaletteAction {
+    fn register_palette_action(&mut self, action: PaletteAction) -> &mut Self;
+    fn register_palette_provider(&mut self, provider: PaletteProvider) -> &mut Self;
+}
+
+impl RegisterPaletteAction for App {
+    fn register_palette_action(&mut self, action: PaletteAction) -> &mut Self {
+        self.init_resource::<PaletteActions>()
+            .world_mut()
+            .resource_mut::<PaletteActions>()
+            .register(action);
+        self
+    }
+
+    fn register_palette_provider(&mut self, provider: PaletteProvider) -> &mut Self {
+        self.init_resource::<PaletteActions>()
+            .world_mut()
+            .resource_mut::<PaletteActions>()
+            .register_provider(provider);
+        self
+    }
+}
+
+/// Score of `candidate` for `query`: every character of the query has to appear in
+/// order, ignoring case and spaces. Matches at word starts and runs of adjacent matches
+/// score higher, skipped characters lower. Each occurrence of the first character is
+/// tried as a start, so "lay" finds the word in "Reload layout".
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let candidate: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+    let Some(&first) = query.first() else {
+        return Some(0);
+    };
+    (0..candidate.len())
+        .filter(|&start| candidate[start] == first)
+        .filter_map(|start| score_from(&query, &candidate, start))
+        .max()
+}
+
+fn score_from(query: &[char], candidate: &[char], start: usize) -> Option<i32> {
+    let mut score = -(start.min(4) as i32);
+    let mut next = start;
+    let mut previous = None;
+    for &wanted in query {
+        let index = (next..candidate.len()).find(|&index| candidate[index] == wanted)?;
+        score += 1;
+        if index == 0 || !candidate[index - 1].is_alphanumeric() {
+            score += 8;
+        }
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 4;
+        }
+        if previous.is_some() {
+            score -= (index - next).min(4) as i32;
+        }
+        previous = Some(index);
+        next = index + 1;
+    }
+    Some(score)
+}
+
+/// Indices of the `actions` matching `query`, best first; ties keep their order.
+pub fn rank(query: &str, actions: &[PaletteAction]) -> Vec<usize> {
+    let mut ranked: Vec<(usize, i32)> = actions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, action)| Some((index, fuzzy_score(query, &action.label)?)))
+        .collect();
+    ranked.sort_by_key(|(_, score)| -score);
+    ranked.into_iter().map(|(index, _)| index).collect()
+}
+
+/// State of the palette while it is open.
+#[derive(Resource, Default)]
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    selected: usize,
+    /// Actions collected when the palette opened.
+    actions: Vec<PaletteAction>,
+    /// The action waiting for its argument.
+    prompt: Option<PaletteAction>,
+    argument: String,
+}
+
+impl CommandPalette {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+/// Open the palette with the actions registered right now.
+pub fn open_command_palette(world: &mut World) {
+    let actions = world
+        .get_resource::<PaletteActions>()
+        .cloned()
+        .unwrap_or_default()
+        .collect(world);
+    let mut palette = world.get_resource_or_init::<CommandPalette>();
+    *palette = CommandPalette {
+        open: true,
+        actions,
+        ..default()
+    };
+}
+
+pub(crate) fn command_palette_ui(ctx: &egui::Context, world: &mut World) {
+    if ctx.input_mut(|input| input.consume_shortcut(&SHORTCUT)) {
+        if world.resource::<CommandPalette>().open {
+            world.resource_mut::<CommandPalette>().open = false;
+        } else {
+            open_command_palette(world);
+        }
+    }
+
+    let mut run = None;
+    {
+        let mut palette = world.resource_mut::<CommandPalette>();
+        if !palette.open {
+            return;
+        }
+        let (up, down, enter, escape) = ctx.input_mut(|input| {
+            (
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+        if escape {
+            palette.open = false;
+            return;
+        }
+
+        egui::Window::new("Command palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .fixed_size(egui::vec2(420.0, 0.0))
+            .show(ctx, |ui| {
+                let palette = &mut *palette;
+                if let Some(action) = &palette.prompt {
+                    ui.label(action.label.as_str());
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut palette.argument)
+                            .hint_text(action.argument.unwrap_or_default())
+                            .desired_width(f32::INFINITY),
+                    );
+                    response.request_focus();
+                    if enter {
+                        run = Some((action.clone(), palette.argument.trim().to_string()));
+                    }
+                    return;
+                }
+
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut palette.query)
+                        .hint_text("Search actions")
+                        .desired_width(f32::INFINITY),
+                );
+                response.request_focus();
+                if response.changed() {
+                    palette.selected = 0;
+                }
+                let ranked = rank(&palette.query, &palette.actions);
+                if ranked.is_empty() {
+                    ui.weak("No matching action");
+                    return;
+                }
+                let rows = ranked.len().min(MAX_ROWS);
+                if up {
+                    palette.selected = palette.selected.checked_sub(1).unwrap_or(rows - 1);
+                }
+                if down {
+                    palette.selected = (palette.selected + 1) % rows;
+                }
+                palette.selected = palette.selected.min(rows - 1);
+                ui.separator();
+                let mut chosen = enter.then_some(palette.selected);
+                for (row, index) in ranked.iter().take(rows).enumerate() {
+                    let action = &palette.actions[*index];
+                    let label = match action.argument {
+                        Some(_) => format!("{}…", action.label),
+                        None => action.label.clone(),
+                    };
+                    let selected = row == palette.selected;
+                    if ui
+                        .add(
+                            egui::Button::selectable(selected, label)
+                                .wrap_mode(egui::TextWrapMode::Truncate),
+                        )
+                        .clicked()
+                    {
+                        chosen = Some(row);
+                    }
+                }
+                if ranked.len() > rows {
+                    ui.weak(format!("{} more", ranked.len() - rows));
+                }
+                if let Some(row) = chosen {
+                    let action = palette.actions[ranked[row]].clone();
+                    if action.argument.is_some() {
+                        palette.prompt = Some(action);
+                    } else {
+                        run = Some((action, String::new()));
+                    }
+                }
+            });
+        if run.is_some() {
+            palette.open = false;
+        }
+    }
+    if let Some((action, argument)) = run {
+        (action.handler)(&argument, world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PaletteAction, fuzzy_score, rank};
+
+    #[test]
+    fn palette_ranks_word_starts_first() {
+        assert!(fuzzy_score("rl", "Reload layout").is_some());
+        assert!(fuzzy_score("lr", "Reload layout").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+
+        let actions: Vec<PaletteAction> = ["Toggle panel: Console", "Reload layout", "Save layout"]
+            .into_iter()
+            .map(|label| PaletteAction::new(label, |_| {}))
+            .collect();
+        assert_eq!(rank("rel lay", &actions), [1]);
+        assert_eq!(rank("lay", &actions), [1, 2]);
+        assert_eq!(rank("tpc", &actions)[0], 0);
+    }
+}
@@ -0,0 +1,251 @@
+//! Log capture and command input behind the console tab.
+//!
+//! Set [`capture_log_layer`] as `LogPlugin::custom_layer` to record `bevy::log` output
+//! into [`ConsoleLog`]. Lines typed into the console run through [`run_console_command`].
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, mpsc},
+};
+
+use bevy::{
+    log::{
+        BoxedLayer, Level,
+        tracing::{
+            Event, Subscriber,
+            field::{Field, Visit},
+        },
+        tracing_subscriber::{Layer, layer::Context},
+    },
+    prelude::*,
+};
+
+/// Lines kept in the [`ConsoleLog`]; the oldest are dropped first.
+const CAPACITY: usize = 5000;
+
+/// Target of the lines the console writes itself.
+const CONSOLE_TARGET: &str = "console";
+
+#[derive(Debug, Clone)]
+pub struct ConsoleLine {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// Recent log output and console replies, oldest first.
+#[derive(Resource, Default)]
+pub struct ConsoleLog {
+    lines: VecDeque<ConsoleLine>,
+    /// Lines dropped from the front so far, so `dropped + index` numbers a line for good.
+    dropped: usize,
+    capturing: bool,
+}
+
+impl ConsoleLog {
+    pub fn push(&mut self, line: ConsoleLine) {
+        if self.lines.len() == CAPACITY {
+            self.lines.pop_front();
+            self.dropped += 1;
+        }
+        self.lines.push_back(line);
+    }
+
+    pub fn clear(&mut self) {
+        self.dropped += self.lines.len();
+        self.lines.clear();
+    }
+
+    /// Lines with their sequence numbers.
+    pub fn lines(&self) -> impl DoubleEndedIterator<Item = (usize, &ConsoleLine)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| (self.dropped + index, line))
+    }
+
+    /// Sequence number of the next line.
+    pub fn end(&self) -> usize {
+        self.dropped + self.lines.len()
+    }
+
+    /// Whether log output is captured, see [`capture_log_layer`].
+    pub fn is_capturing(&self) -> bool {
+        self.capturing
+    }
+
+    fn reply(&mut self, level: Level, message: &str) {
+        for line in message.lines() {
+            self.push(ConsoleLine {
+                level,
+                target: CONSOLE_TARGET.to_string(),
+                message: line.to_string(),
+            });
+        }
+    }
+}
+
+/// Handles a line typed into the console: `None` if the input is not for this handler,
+/// otherwise the reply or error to show.
+pub type ConsoleHandler =
+    Arc<dyn Fn(&str, &mut World) -> Option<Result<String, String>> + Send + Sync>;
+
+/// Commands accepted by the console besides `help` and `clear`, registered by plugins.
+#[derive(Resource, Default, Clone)]
+pub struct ConsoleCommands {
+    commands: Vec<(&'static str, ConsoleHandler)>,
+}
+
+impl ConsoleCommands {
+    /// Register a handler if none with this usage is known; `usage` is listed by `help`.
+    pub fn register(&mut self, usage: &'static str, handler: ConsoleHandler) {
+        if self.commands.iter().any(|(known, _)| *known == usage) {
+            return;
+        }
+        self.commands.push((usage, handler));
+    }
+
+    /// Usage lines of all handlers in registration order.
+    pub fn usages(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.commands.iter().map(|(usage, _)| *usage)
+    }
+
+    /// Run `input` through the handlers in registration order.
+    pub fn run(&self, input: &str, world: &mut World) -> Option<Result<String, String>> {
+        self.commands
+            .iter()
+            .find_map(|(_, handler)| handler(input, world))
+    }
+}
+
+/// Echo `input` into the console log and run it, writing the reply below it.
+pub fn run_console_command(world: &mut World, input: &str) {
+    let input = input.trim();
+    if input.is_empty() {
+        return;
+    }
+    world.init_resource::<ConsoleLog>();
+    world
+        .resource_mut::<ConsoleLog>()
+        .reply(Level::INFO, &format!("> {input}"));
+    let commands = world
+        .get_resource::<ConsoleCommands>()
+        .cloned()
+        .unwrap_or_default();
+    let reply = match input {
+        "clear" => {
+            world.resource_mut::<ConsoleLog>().clear();
+            return;
+        }
+        "help" => Ok(["help", "clear"]
+            .into_iter()
+            .chain(commands.usages())
+            .collect::<Vec<_>>()
+            .join("\n")),
+        input => commands
+            .run(input, world)
+            .unwrap_or_else(|| Err(format!("unknown command '{input}', try 'help'"))),
+    };
+    let mut log = world.resource_mut::<ConsoleLog>();
+    match reply {
+        Ok(reply) => log.reply(Level::INFO, &reply),
+        Err(err) => log.reply(Level::ERROR, &err),
+    }
+}
+
+/// Receiving end of [`CaptureLayer`].
+#[derive(Resource)]
+struct CapturedLogs(Mutex<mpsc::Receiver<ConsoleLine>>);
+
+/// A `LogPlugin::custom_layer` that sends log events to the [`ConsoleLog`].
+pub fn capture_log_layer(app: &mut App) -> Option<BoxedLayer> {
+    let (sender, receiver) = mpsc::channel();
+    app.insert_resource(CapturedLogs(Mutex::new(receiver)));
+    Some(Box::new(CaptureLayer { sender }))
+}
+
+pub(crate) fn drain_captured_logs(
+    captured: Option<Res<CapturedLogs>>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let Some(captured) = captured else {
+        return;
+    };
+    log.capturing = true;
+    let Ok(receiver) = captured.0.lock() else {
+        return;
+    };
+    for line in receiver.try_iter() {
+        log.push(line);
+    }
+}
+
+struct CaptureLayer {
+    sender: mpsc::Sender<ConsoleLine>,
+}
+
+impl<S: Subscriber> Layer<S> for CaptureLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+        let metadata = event.metadata();
+        // fails only once the app is gone
+        let _ = self.sender.send(ConsoleLine {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: message.0,
+        });
+    }
+}
+
+/// Formats the `message` field followed by the other fields as `name=value`.
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{value:?}"));
+        } else {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bevy::{log::Level, prelude::World};
+
+    use super::{ConsoleCommands, ConsoleLog, run_console_command};
+
+    fn messages(world: &World) -> Vec<String> {
+        world
+            .resource::<ConsoleLog>()
+            .lines()
+            .map(|(_, line)| line.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn console_runs_registered_commands() {
+        let mut world = World::new();
+        let mut commands = ConsoleCommands::default();
+        commands.register(
+            "ping",
+            Arc::new(|input, _world| (input == "ping").then(|| Ok("pong".to_string()))),
+        );
+        world.insert_resource(commands);
+
+        run_console_command(&mut world, "ping");
+        run_console_command(&mut world, "pong");
+        assert_eq!(messages(&world)[..3], ["> ping", "pong", "> pong"]);
+        let log = world.resource::<ConsoleLog>();
+        assert_eq!(log.lines().last().unwrap().1.level, Level::ERROR);
+
+        run_console_command(&mut world, "clear");
+        assert!(messages(&world).is_empty());
+        assert_eq!(world.resource::<ConsoleLog>().end(), 5);
+    }
+}
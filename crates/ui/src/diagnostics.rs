@@ -0,0 +1,193 @@
+//! Built-in sections of the diagnostics tab: frame timing, world size and asset memory.
+
+use std::sync::Arc;
+
+use bevy::{
+    diagnostic::{DiagnosticPath, DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    mesh::Indices,
+    prelude::*,
+};
+use bevy_egui::egui;
+
+use crate::{plot::line_plot, tabs::DiagnosticsSections};
+
+pub(crate) fn plugin(app: &mut App) {
+    if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+        app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+    }
+    app.init_resource::<DiagnosticsSections>();
+    let mut sections = app.world_mut().resource_mut::<DiagnosticsSections>();
+    sections.register("Frame", Arc::new(frame_section));
+    sections.register("World", Arc::new(world_section));
+    sections.register("Assets", Arc::new(assets_section));
+}
+
+fn frame_section(ui: &mut egui::Ui, world: &mut World) {
+    diagnostic_plot(ui, world, &FrameTimeDiagnosticsPlugin::FPS, "FPS");
+    diagnostic_plot(
+        ui,
+        world,
+        &FrameTimeDiagnosticsPlugin::FRAME_TIME,
+        "Frame time",
+    );
+}
+
+fn world_section(ui: &mut egui::Ui, world: &mut World) {
+    egui::Grid::new("diagnostics_world").show(ui, |ui| {
+        ui.label("Entities");
+        ui.label(world.entities().len().to_string());
+        ui.end_row();
+        ui.label("Archetypes");
+        ui.label(world.archetypes().len().to_string());
+        ui.end_row();
+        ui.label("Component types");
+        ui.label(world.components().len().to_string());
+        ui.end_row();
+    });
+}
+
+fn assets_section(ui: &mut egui::Ui, world: &mut World) {
+    let (meshes, mesh_memory) = asset_memory(world, mesh_bytes);
+    let (images, image_memory) = asset_memory(world, image_bytes);
+    let materials = world
+        .get_resource::<Assets<StandardMaterial>>()
+        .map_or(0, Assets::len);
+    egui::Grid::new("diagnostics_assets").show(ui, |ui| {
+        ui.label("Meshes");
+        ui.label(meshes.to_string());
+        ui.label(format_bytes(mesh_memory));
+        ui.end_row();
+        ui.label("Images");
+        ui.label(images.to_string());
+        ui.label(format_bytes(image_memory));
+        ui.end_row();
+        ui.label("Materials");
+        ui.label(materials.to_string());
+        ui.end_row();
+    });
+    ui.weak("Memory is estimated from buffer and texture sizes, without mipmaps.");
+}
+
+/// Latest value, average and history plot of a diagnostic, if it is recorded.
+pub fn diagnostic_plot(ui: &mut egui::Ui, world: &World, path: &DiagnosticPath, label: &str) {
+    let Some(diagnostic) = world
+        .get_resource::<DiagnosticsStore>()
+        .and_then(|store| store.get(path))
+    else {
+        ui.weak(format!("{label}: not recorded"));
+        return;
+    };
+    ui.label(format!(
+        "{label}: {:.1}{suffix} (average {:.1}{suffix})",
+        diagnostic.smoothed().unwrap_or_default(),
+        diagnostic.average().unwrap_or_default(),
+        suffix = diagnostic.suffix,
+    ));
+    let history: Vec<[f64; 2]> = diagnostic
+        .values()
+        .copied()
+        .filter(|value| value.is_finite())
+        .enumerate()
+        .map(|(index, value)| [index as f64, value])
+        .collect();
+    line_plot(ui, &history, 48.0);
+}
+
+/// Number of assets of a kind and their estimated size in bytes.
+fn asset_memory<A: Asset>(world: &World, bytes: fn(&A) -> usize) -> (usize, usize) {
+    world.get_resource::<Assets<A>>().map_or((0, 0), |assets| {
+        (
+            assets.len(),
+            assets.iter().map(|(_, asset)| bytes(asset)).sum(),
+        )
+    })
+}
+
+fn mesh_bytes(mesh: &Mesh) -> usize {
+    let indices = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.len() * 2,
+        Some(Indices::U32(indices)) => indices.len() * 4,
+        None => 0,
+    };
+    mesh.count_vertices() * mesh.get_vertex_size() as usize + indices
+}
+
+fn image_bytes(image: &Image) -> usize {
+    let descriptor = &image.texture_descriptor;
+    let (block_width, block_height) = descriptor.format.block_dimensions();
+    let block_size = descriptor.format.block_copy_size(None).unwrap_or(4) as usize;
+    let size = descriptor.size;
+    size.width.div_ceil(block_width) as usize
+        * size.height.div_ceil(block_height) as usize
+        * size.depth_or_array_layers as usize
+        * block_size
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::RenderAssetUsages,
+        mesh::{Indices, PrimitiveTopology},
+        prelude::*,
+        render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    use super::{format_bytes, image_bytes, mesh_bytes};
+
+    #[test]
+    fn bytes_are_shown_in_the_largest_fitting_unit() {
+        assert_eq!(format_bytes(0), "0.0 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+        assert_eq!(format_bytes(2048 * 1024 * 1024 * 1024), "2048.0 GiB");
+    }
+
+    #[test]
+    fn image_memory_follows_the_texture_format() {
+        let image = |format| {
+            Image::new_fill(
+                Extent3d {
+                    width: 4,
+                    height: 2,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &vec![0; format_size(format)],
+                format,
+                RenderAssetUsages::default(),
+            )
+        };
+        assert_eq!(image_bytes(&image(TextureFormat::Rgba8UnormSrgb)), 32);
+        assert_eq!(image_bytes(&image(TextureFormat::R32Float)), 32);
+        assert_eq!(image_bytes(&image(TextureFormat::R8Unorm)), 8);
+    }
+
+    fn format_size(format: TextureFormat) -> usize {
+        format.block_copy_size(None).unwrap() as usize
+    }
+
+    #[test]
+    fn mesh_memory_counts_vertices_and_indices() {
+        let mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0f32; 3]; 3])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0f32; 3]; 3]);
+        assert_eq!(mesh_bytes(&mesh), 3 * 24);
+
+        let indexed = mesh.with_inserted_indices(Indices::U16(vec![0, 1, 2]));
+        assert_eq!(mesh_bytes(&indexed), 3 * 24 + 3 * 2);
+    }
+}
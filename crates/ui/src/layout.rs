@@ -42,6 +42,28 @@ pub struct DevUiLayoutSnapshot {
     pub dock: LayoutNode,
 }
 
+impl LayoutNode {
+    /// Whether a panel with this title is placed anywhere in the node.
+    pub fn contains(&self, title: &str) -> bool {
+        match self {
+            LayoutNode::Tabs(titles) => titles.iter().any(|known| known == title),
+            LayoutNode::Split { children, .. } | LayoutNode::Grid(children) => {
+                children.iter().any(|child| child.contains(title))
+            }
+        }
+    }
+
+    /// Remove every tab with this title from the node.
+    pub fn remove(&mut self, title: &str) {
+        match self {
+            LayoutNode::Tabs(titles) => titles.retain(|known| known != title),
+            LayoutNode::Split { children, .. } | LayoutNode::Grid(children) => {
+                children.iter_mut().for_each(|child| child.remove(title))
+            }
+        }
+    }
+}
+
 impl DevUiLayout {
     /// Default layout used when the layout file is missing or invalid.
     pub fn default_layout() -> Self {
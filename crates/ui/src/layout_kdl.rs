@@ -99,6 +99,24 @@ pub fn save_current_layout(world: &World) {
     }
 }
 
+/// The current layout as KDL, or `None` before the docked UI is set up.
+pub fn current_layout_kdl(world: &World) -> Option<String> {
+    world
+        .contains_resource::<LeftPanelLayout>()
+        .then(|| layout_to_kdl(&snapshot_layout(world)))
+}
+
+/// Parse a KDL layout and apply it to the running UI.
+pub fn apply_layout_kdl(world: &mut World, src: &str) -> Result<(), String> {
+    if !world.contains_resource::<LeftPanelLayout>() {
+        return Err("the docked UI is not set up".to_string());
+    }
+    let layout = parse_layout(src)?;
+    let registry = world.resource::<PanelRegistry>().clone();
+    apply_layout(world, layout, &registry);
+    Ok(())
+}
+
 /// Convert a viewer tree into a serializable layout node.
 fn layout_from_viewer_tree(tree: &Option<egui_tiles::Tree<tabs::BoxedViewerTab>>) -> LayoutNode {
     let Some(tree) = tree.as_ref() else {
@@ -4,7 +4,13 @@ mod layout_runtime;
 mod pane_widgets;
 mod style;
 
+pub mod command_palette;
+pub mod console;
+pub mod diagnostics;
 pub mod layout;
+pub mod plot;
+pub mod project;
 pub mod tabs;
 
-pub use build_ui::{UiSpaceCamera, WorldSpaceCamera, setup_ui};
+pub use build_ui::{RegisterPanel, UiSpaceCamera, UnregisterPanel, WorldSpaceCamera, setup_ui};
+pub use layout_kdl::{apply_layout_kdl, current_layout_kdl};
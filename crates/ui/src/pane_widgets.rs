@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use bevy::prelude::*;
+use bevy::{log::Level, prelude::*};
 #[derive(Resource, Default)]
 pub struct PaneWidgetStates {
     pub states: HashMap<String, PaneWidgetState>,
@@ -12,6 +12,16 @@ pub struct PaneWidgetState {
     pub(crate) filter_include_children: bool,
     pub(crate) side_panels_show_hierarchy: bool,
     pub(crate) side_panels_show_inspector: bool,
+    /// Most verbose level shown in the console.
+    pub(crate) console_level: Level,
+    pub(crate) console_target: String,
+    pub(crate) console_search: String,
+    /// Sequence number of the first line hidden while the console is paused.
+    pub(crate) console_paused_at: Option<usize>,
+    pub(crate) console_input: String,
+    pub(crate) console_history: Vec<String>,
+    /// Position while browsing the history with the arrow keys.
+    pub(crate) console_history_pos: Option<usize>,
 }
 
 impl Default for PaneWidgetState {
@@ -21,6 +31,13 @@ impl Default for PaneWidgetState {
             filter_include_children: true,
             side_panels_show_hierarchy: true,
             side_panels_show_inspector: true,
+            console_level: Level::INFO,
+            console_target: String::new(),
+            console_search: String::new(),
+            console_paused_at: None,
+            console_input: String::new(),
+            console_history: Vec::new(),
+            console_history_pos: None,
         }
     }
 }
@@ -0,0 +1,52 @@
+//! Small plots drawn with the egui painter.
+
+use bevy_egui::egui;
+
+/// Draw `points` as a line scaled to fit the available width and `height`.
+pub fn line_plot(ui: &mut egui::Ui, points: &[[f64; 2]], height: f32) -> egui::Response {
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(ui.available_width(), height),
+        egui::Sense::hover(),
+    );
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    let Some(bounds) = points.iter().fold(None, |bounds, [x, y]| {
+        let [min_x, max_x, min_y, max_y] = bounds.unwrap_or([*x, *x, *y, *y]);
+        Some([min_x.min(*x), max_x.max(*x), min_y.min(*y), max_y.max(*y)])
+    }) else {
+        return response;
+    };
+    let [min_x, max_x, min_y, max_y] = bounds;
+    let scale = |value: f64, min: f64, max: f64| match max > min {
+        true => ((value - min) / (max - min)) as f32,
+        false => 0.5,
+    };
+    let line: Vec<egui::Pos2> = points
+        .iter()
+        .map(|[x, y]| {
+            egui::pos2(
+                rect.left() + rect.width() * scale(*x, min_x, max_x),
+                rect.bottom() - rect.height() * scale(*y, min_y, max_y),
+            )
+        })
+        .collect();
+    painter.add(egui::Shape::line(
+        line,
+        egui::Stroke::new(1.5, ui.visuals().selection.bg_fill),
+    ));
+    painter.text(
+        rect.left_top() + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        format!("{:.3}", max_y),
+        egui::FontId::monospace(10.0),
+        ui.visuals().weak_text_color(),
+    );
+    painter.text(
+        rect.left_bottom() + egui::vec2(4.0, -2.0),
+        egui::Align2::LEFT_BOTTOM,
+        format!("{:.3}", min_y),
+        egui::FontId::monospace(10.0),
+        ui.visuals().weak_text_color(),
+    );
+    response.on_hover_text(format!("x: {:.3} .. {:.3}", min_x, max_x))
+}
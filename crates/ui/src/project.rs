@@ -0,0 +1,132 @@
+//! The project dialog of the File menu.
+//!
+//! The dialog only asks for a path: it sends a [`ProjectRequest`] for the plugins that
+//! read and write project files and export the scene, which report back through
+//! [`ProjectDialog::report`].
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Open or save the project file at a path, or export the scene.
+#[derive(Message, Debug, Clone)]
+pub enum ProjectRequest {
+    Open(PathBuf),
+    Save(PathBuf),
+    /// Write the visible scene as glTF, `.glb` paths as binary glTF. `joints` keeps the
+    /// robots in their current joint configuration.
+    ExportGltf {
+        path: PathBuf,
+        joints: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectDialogMode {
+    Open,
+    Save,
+    ExportGltf,
+}
+
+/// State of the project dialog; the paths are remembered between uses.
+#[derive(Resource)]
+pub struct ProjectDialog {
+    pub mode: Option<ProjectDialogMode>,
+    pub path: String,
+    pub export_path: String,
+    /// Export robots in their current joint configuration.
+    pub export_joints: bool,
+    status: Option<Result<String, String>>,
+}
+
+impl Default for ProjectDialog {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            path: std::env::var("DIMENSIFY_PROJECT")
+                .unwrap_or_else(|_| "dimensify_project.json".to_string()),
+            export_path: "dimensify_scene.glb".to_string(),
+            export_joints: true,
+            status: None,
+        }
+    }
+}
+
+impl ProjectDialog {
+    pub fn open(&mut self, mode: ProjectDialogMode) {
+        self.mode = Some(mode);
+        self.status = None;
+    }
+
+    /// Show the outcome of a request.
+    pub fn report(&mut self, path: &std::path::Path, status: Result<String, String>) {
+        let path = path.display().to_string();
+        match self.mode {
+            Some(ProjectDialogMode::ExportGltf) => self.export_path = path,
+            _ => self.path = path,
+        }
+        self.status = Some(status);
+    }
+}
+
+pub(crate) fn project_dialog_ui(ctx: &egui::Context, world: &mut World) {
+    let mut request = None;
+    {
+        let mut dialog = world.resource_mut::<ProjectDialog>();
+        let Some(mode) = dialog.mode else {
+            return;
+        };
+        let (title, action) = match mode {
+            ProjectDialogMode::Open => ("Open project", "Open"),
+            ProjectDialogMode::Save => ("Save project", "Save"),
+            ProjectDialogMode::ExportGltf => ("Export glTF", "Export"),
+        };
+        let mut open = true;
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let dialog = &mut *dialog;
+                let path = match mode {
+                    ProjectDialogMode::ExportGltf => &mut dialog.export_path,
+                    _ => &mut dialog.path,
+                };
+                ui.horizontal(|ui| {
+                    ui.label("Path");
+                    ui.text_edit_singleline(path);
+                });
+                let path = PathBuf::from(path.trim());
+                if mode == ProjectDialogMode::ExportGltf {
+                    ui.checkbox(&mut dialog.export_joints, "Current joint values")
+                        .on_hover_text("Otherwise robots are exported in their zero configuration");
+                }
+                if ui.button(action).clicked() && !path.as_os_str().is_empty() {
+                    request = Some(match mode {
+                        ProjectDialogMode::Open => ProjectRequest::Open(path),
+                        ProjectDialogMode::Save => ProjectRequest::Save(path),
+                        ProjectDialogMode::ExportGltf => ProjectRequest::ExportGltf {
+                            path,
+                            joints: dialog.export_joints,
+                        },
+                    });
+                }
+                match &dialog.status {
+                    Some(Ok(message)) => {
+                        ui.label(message.as_str());
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, err.as_str());
+                    }
+                    None => {}
+                }
+            });
+        if !open {
+            dialog.mode = None;
+        }
+    }
+    if let Some(request) = request {
+        world.write_message(request);
+    }
+}
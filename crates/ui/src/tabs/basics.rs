@@ -1,4 +1,4 @@
-use bevy::prelude::*;
+use bevy::{log::Level, prelude::*};
 use bevy_egui::egui::{self, WidgetText};
 use bevy_inspector_egui::bevy_inspector::{
     Filter,
@@ -6,8 +6,13 @@ use bevy_inspector_egui::bevy_inspector::{
 };
 use egui_tiles::{self, Tiles, Tree};
 
-use super::{PanelEntry, PanelFactory, PanelLocation, PanelRegistry, ViewerTab};
-use crate::pane_widgets::{AssetViewKind, PaneWidgetStates};
+use super::{
+    DiagnosticsSections, PanelEntry, PanelFactory, PanelLocation, PanelRegistry, ViewerTab,
+};
+use crate::{
+    console::{ConsoleLine, ConsoleLog, run_console_command},
+    pane_widgets::{AssetViewKind, PaneWidgetState, PaneWidgetStates},
+};
 use bevy_inspector_egui::bevy_inspector;
 
 #[derive(Resource, Default)]
@@ -32,7 +37,6 @@ pub struct StateInspectorTab;
 pub struct SidePanelInspectorTab;
 pub struct ConsoleTab;
 pub struct DiagnosticsTab;
-pub struct TasksTab;
 
 pub fn register_default_panels(registry: &mut PanelRegistry) {
     let mut register = |title: &'static str,
@@ -40,7 +44,7 @@ pub fn register_default_panels(registry: &mut PanelRegistry) {
                         default_enabled: bool,
                         factory: PanelFactory| {
         registry.register(PanelEntry {
-            title,
+            title: title.into(),
             location,
             default_enabled,
             factory,
@@ -77,12 +81,6 @@ pub fn register_default_panels(registry: &mut PanelRegistry) {
         true,
         std::sync::Arc::new(|| Box::new(DiagnosticsTab)),
     );
-    register(
-        "Tasks",
-        PanelLocation::Bottom,
-        true,
-        std::sync::Arc::new(|| Box::new(TasksTab)),
-    );
 
     register(
         "Filter",
@@ -105,7 +103,7 @@ pub fn register_default_panels(registry: &mut PanelRegistry) {
 }
 
 impl ViewerTab for WorldInspectorTab {
-    fn title(&self) -> &'static str {
+    fn title(&self) -> &str {
         "World"
     }
 
@@ -118,7 +116,7 @@ impl ViewerTab for WorldInspectorTab {
 }
 
 impl ViewerTab for ResourceInspectorTab {
-    fn title(&self) -> &'static str {
+    fn title(&self) -> &str {
         "Resources"
     }
 
@@ -131,7 +129,7 @@ impl ViewerTab for ResourceInspectorTab {
 }
 
 impl ViewerTab for AssetsTab {
-    fn title(&self) -> &'static str {
+    fn title(&self) -> &str {
         "Assets"
     }
 
@@ -156,7 +154,7 @@ impl ViewerTab for AssetsTab {
 }
 
 impl ViewerTab for FilterInspectorTab {
-    fn title(&self) -> &'static str {
+    fn title(&self) -> &str {
         "Filter"
     }
 
@@ -181,7 +179,7 @@ impl ViewerTab for FilterInspectorTab {
 }
 
 impl ViewerTab for StateInspectorTab {
-    fn title(&self) -> &'static str {
+    fn title(&self) -> &str {
         "State"
     }
 
@@ -191,7 +189,7 @@ impl ViewerTab for StateInspectorTab {
 }
 
 impl ViewerTab for SidePanelInspectorTab {
-    fn title(&self) -> &'static str {
+    fn title(&self) -> &str {
         "Side Panels"
     }
 
@@ -321,31 +319,182 @@ impl Default for DockUiState {
 }
 
 impl ViewerTab for ConsoleTab {
-    fn title(&self) -> &'static str {
+    fn title(&self) -> &str {
         "Console"
     }
 
-    fn ui(&mut self, ui: &mut egui::Ui, _world: &mut World) {
-        ui.heading("Console");
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        world.init_resource::<ConsoleLog>();
+        let submitted = world.resource_scope(|world, mut states: Mut<PaneWidgetStates>| {
+            let state = states.states.entry("ui.console".to_string()).or_default();
+            let mut log = world.resource_mut::<ConsoleLog>();
+            console_log_ui(ui, state, &mut log);
+            console_input_ui(ui, state)
+        });
+        if let Some(input) = submitted {
+            run_console_command(world, &input);
+        }
     }
 }
 
-impl ViewerTab for DiagnosticsTab {
-    fn title(&self) -> &'static str {
-        "Diagnostics"
+/// Toolbar with the filters, and the captured lines that pass them.
+fn console_log_ui(ui: &mut egui::Ui, state: &mut PaneWidgetState, log: &mut ConsoleLog) {
+    let (mut copy, mut clear) = (false, false);
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("console_level")
+            .selected_text(state.console_level.as_str())
+            .show_ui(ui, |ui| {
+                for level in [
+                    Level::ERROR,
+                    Level::WARN,
+                    Level::INFO,
+                    Level::DEBUG,
+                    Level::TRACE,
+                ] {
+                    ui.selectable_value(&mut state.console_level, level, level.as_str());
+                }
+            });
+        ui.add(
+            egui::TextEdit::singleline(&mut state.console_target)
+                .hint_text("target")
+                .desired_width(120.0),
+        );
+        ui.add(
+            egui::TextEdit::singleline(&mut state.console_search)
+                .hint_text("search")
+                .desired_width(160.0),
+        );
+        let mut paused = state.console_paused_at.is_some();
+        if ui.toggle_value(&mut paused, "Pause").changed() {
+            state.console_paused_at = paused.then(|| log.end());
+        }
+        copy = ui.button("Copy").clicked();
+        clear = ui.button("Clear").clicked();
+    });
+    if clear {
+        log.clear();
     }
+    if !log.is_capturing() {
+        ui.weak(
+            "Log capture is off: set `LogPlugin::custom_layer` to \
+             `dimensify_ui::console::capture_log_layer`.",
+        );
+    }
+
+    let end = state.console_paused_at.unwrap_or(usize::MAX);
+    let target = state.console_target.to_lowercase();
+    let search = state.console_search.to_lowercase();
+    let visible: Vec<&ConsoleLine> = log
+        .lines()
+        .take_while(|(seq, _)| *seq < end)
+        .map(|(_, line)| line)
+        .filter(|line| {
+            line.level <= state.console_level
+                && line.target.to_lowercase().contains(&target)
+                && line.message.to_lowercase().contains(&search)
+        })
+        .collect();
+    if copy {
+        let text: Vec<String> = visible.iter().map(|line| console_line_text(line)).collect();
+        ui.ctx().copy_text(text.join("\n"));
+    }
+
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    let input_height = ui.spacing().interact_size.y + ui.spacing().item_spacing.y * 2.0;
+    egui::ScrollArea::vertical()
+        .id_salt("console_lines")
+        .auto_shrink(false)
+        .stick_to_bottom(true)
+        .max_height((ui.available_height() - input_height).max(row_height))
+        .show_rows(ui, row_height, visible.len(), |ui, rows| {
+            for line in &visible[rows] {
+                let color = match line.level {
+                    Level::ERROR => ui.visuals().error_fg_color,
+                    Level::WARN => ui.visuals().warn_fg_color,
+                    Level::INFO => ui.visuals().text_color(),
+                    _ => ui.visuals().weak_text_color(),
+                };
+                let text = egui::RichText::new(console_line_text(line))
+                    .monospace()
+                    .color(color);
+                ui.add(egui::Label::new(text).truncate())
+                    .on_hover_text(line.message.as_str());
+            }
+        });
+}
+
+fn console_line_text(line: &ConsoleLine) -> String {
+    format!(
+        "{:<5} {}: {}",
+        line.level.as_str(),
+        line.target,
+        line.message
+    )
+}
 
-    fn ui(&mut self, ui: &mut egui::Ui, _world: &mut World) {
-        ui.heading("Diagnostics");
+/// Command line with history on the arrow keys; returns the submitted input.
+fn console_input_ui(ui: &mut egui::Ui, state: &mut PaneWidgetState) -> Option<String> {
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut state.console_input)
+            .font(egui::TextStyle::Monospace)
+            .hint_text("command or JSON, 'help' lists commands")
+            .desired_width(f32::INFINITY),
+    );
+    let history = &state.console_history;
+    if response.has_focus() && !history.is_empty() {
+        let (up, down) = ui.input(|input| {
+            (
+                input.key_pressed(egui::Key::ArrowUp),
+                input.key_pressed(egui::Key::ArrowDown),
+            )
+        });
+        let pos = match (state.console_history_pos, up, down) {
+            (None, true, _) => Some(history.len() - 1),
+            (Some(pos), true, _) => Some(pos.saturating_sub(1)),
+            (Some(pos), false, true) if pos + 1 < history.len() => Some(pos + 1),
+            (Some(_), false, true) => None,
+            (pos, _, _) => pos,
+        };
+        if pos != state.console_history_pos {
+            state.console_input = pos.map(|pos| history[pos].clone()).unwrap_or_default();
+            state.console_history_pos = pos;
+        }
+    }
+    if !(response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter))) {
+        return None;
     }
+    response.request_focus();
+    state.console_history_pos = None;
+    let input = std::mem::take(&mut state.console_input);
+    if input.trim().is_empty() {
+        return None;
+    }
+    if state.console_history.last() != Some(&input) {
+        state.console_history.push(input.clone());
+    }
+    Some(input)
 }
 
-impl ViewerTab for TasksTab {
-    fn title(&self) -> &'static str {
-        "Tasks"
+impl ViewerTab for DiagnosticsTab {
+    fn title(&self) -> &str {
+        "Diagnostics"
     }
 
-    fn ui(&mut self, ui: &mut egui::Ui, _world: &mut World) {
-        ui.heading("Tasks");
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let sections = world
+            .get_resource::<DiagnosticsSections>()
+            .cloned()
+            .unwrap_or_default();
+        if sections.iter().next().is_none() {
+            ui.label("No diagnostics registered.");
+            return;
+        }
+        egui::ScrollArea::both().show(ui, |ui| {
+            for (title, section) in sections.iter() {
+                egui::CollapsingHeader::new(*title)
+                    .default_open(true)
+                    .show(ui, |ui| section(ui, world));
+            }
+        });
     }
 }
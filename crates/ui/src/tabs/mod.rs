@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
 
 use bevy::prelude::{Resource, World};
 use bevy_egui::egui;
@@ -7,7 +7,7 @@ mod basics;
 
 /// A pluggable panel rendered inside the UI tiles.
 pub trait ViewerTab: Send + Sync {
-    fn title(&self) -> &'static str;
+    fn title(&self) -> &str;
     fn ui(&mut self, ui: &mut egui::Ui, world: &mut World);
 }
 
@@ -24,10 +24,35 @@ pub enum PanelLocation {
     Floating,
 }
 
+/// Draws one section of the [`DiagnosticsTab`].
+pub type DiagnosticsSection = Arc<dyn Fn(&mut egui::Ui, &mut World) + Send + Sync>;
+
+/// Sections shown in the [`DiagnosticsTab`], registered by plugins with something to report.
+#[derive(Resource, Default, Clone)]
+pub struct DiagnosticsSections {
+    sections: Vec<(&'static str, DiagnosticsSection)>,
+}
+
+impl DiagnosticsSections {
+    /// Register a section if no section with this title is known.
+    pub fn register(&mut self, title: &'static str, section: DiagnosticsSection) {
+        if self.sections.iter().any(|(known, _)| *known == title) {
+            return;
+        }
+        self.sections.push((title, section));
+    }
+
+    /// Iterate all sections in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &(&'static str, DiagnosticsSection)> {
+        self.sections.iter()
+    }
+}
+
 /// Metadata and constructor for a single panel tab.
 #[derive(Clone)]
 pub struct PanelEntry {
-    pub title: &'static str,
+    /// Unique title, also used to place the panel in the layout file.
+    pub title: Cow<'static, str>,
     pub location: PanelLocation,
     pub default_enabled: bool,
     pub factory: PanelFactory,
@@ -61,6 +86,27 @@ impl PanelRegistry {
         self.entries.push(entry);
     }
 
+    /// Forget a panel, e.g. one that was registered at runtime and is gone again.
+    pub fn unregister(&mut self, title: &str) {
+        let Some(index) = self.by_title.remove(title) else {
+            return;
+        };
+        self.entries.remove(index);
+        self.enabled.remove(title);
+        self.floating.remove(title);
+        self.by_title = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (entry.title.to_string(), index))
+            .collect();
+    }
+
+    /// Whether a panel with this title is registered.
+    pub fn contains(&self, title: &str) -> bool {
+        self.by_title.contains_key(title)
+    }
+
     /// Iterate all registered panel definitions in insertion order.
     pub fn entries(&self) -> impl Iterator<Item = &PanelEntry> {
         self.entries.iter()
@@ -98,18 +144,18 @@ impl PanelRegistry {
     }
 
     /// First enabled, non-floating panel title, used for initial selection.
-    pub fn first_enabled_title(&self) -> Option<&'static str> {
+    pub fn first_enabled_title(&self) -> Option<&str> {
         self.entries
             .iter()
-            .find(|entry| self.is_enabled(entry.title) && !self.is_floating(entry.title))
-            .map(|entry| entry.title)
+            .find(|entry| self.is_enabled(&entry.title) && !self.is_floating(&entry.title))
+            .map(|entry| entry.title.as_ref())
     }
 }
 
 pub use basics::{
     AssetsTab, ConsoleTab, DevUiState, DiagnosticsTab, DockUiState, FilterInspectorTab,
     HierarchyTab, InspectorSelectionState, InspectorTab, ResourceInspectorTab,
-    SidePanelInspectorTab, StateInspectorTab, TasksTab, WorldInspectorTab, register_default_panels,
+    SidePanelInspectorTab, StateInspectorTab, WorldInspectorTab, register_default_panels,
 };
 
 pub(crate) use basics::DockPane;
@@ -0,0 +1,234 @@
+use pyo3::{exceptions::PyValueError, prelude::*};
+use std::sync::Mutex;
+
+use dimensify_protocol::{TransportError, WorldCommand};
+use dimensify_transport::{ProtoRequest, ProtoResponse, TransportController};
+
+use crate::{
+    client::{TransportClient, transport_error},
+    components::PyComponent,
+    metadata::{PyEntity, PyEntityInfo},
+};
+
+/// Converts a response into the value an awaitable resolves to.
+type Convert = fn(Python<'_>, ProtoResponse) -> PyResult<Py<PyAny>>;
+
+/// An asyncio flavour of `World`.
+///
+/// Requests return awaitables resolved by the transport thread, so any number of them
+/// can be in flight and `asyncio.gather` pipelines them over one connection. Methods
+/// must be called from a coroutine running on an event loop. There are no timeouts;
+/// use `asyncio.wait_for`. Requests made before the connection is up are sent once
+/// connected, and fail with `TransportConnectionError` if it is lost or closed first.
+#[pyclass]
+pub struct AsyncWorld {
+    controller: Mutex<Option<TransportController>>,
+}
+
+#[pymethods]
+impl AsyncWorld {
+    #[pyo3(signature = (server_addr=None, mode=None, client_addr=None, cert_digest=None, tick_hz=None, token=None))]
+    #[new]
+    pub fn new(
+        server_addr: Option<String>,
+        mode: Option<String>,
+        client_addr: Option<String>,
+        cert_digest: Option<String>,
+        tick_hz: Option<f32>,
+        token: Option<String>,
+    ) -> PyResult<Self> {
+        let client = TransportClient::new(
+            server_addr,
+            mode,
+            client_addr,
+            cert_digest,
+            tick_hz,
+            Some("client".to_string()),
+            Some("controller".to_string()),
+            None,
+            None,
+            token,
+        )?;
+        Ok(Self {
+            controller: Mutex::new(Some(client.into_controller())),
+        })
+    }
+
+    /// Spawn a new entity with the given components; resolves to the created entity.
+    #[pyo3(signature = (*components))]
+    pub fn spawn<'py>(
+        &self,
+        py: Python<'py>,
+        components: Vec<Py<PyComponent>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if components.is_empty() {
+            return Err(PyValueError::new_err(
+                "spawn() requires at least one component",
+            ));
+        }
+        let command = WorldCommand::Spawn {
+            components: components
+                .into_iter()
+                .map(|component| component.borrow(py).0.clone())
+                .collect(),
+        };
+        self.submit(
+            py,
+            ProtoRequest::ApplyCommand(command),
+            |py, response| match response {
+                ProtoResponse::CommandResponseEntity(entity) => Ok(PyEntity::from(entity)
+                    .into_pyobject(py)?
+                    .into_any()
+                    .unbind()),
+                other => Err(unexpected(other)),
+            },
+        )
+    }
+
+    /// Despawn an entity; resolves to `None`.
+    pub fn despawn<'py>(&self, py: Python<'py>, entity: PyEntity) -> PyResult<Bound<'py, PyAny>> {
+        let command = WorldCommand::Despawn { entity: entity.0 };
+        self.submit(
+            py,
+            ProtoRequest::ApplyCommand(command),
+            |py, response| match response {
+                ProtoResponse::CommandResponseEntity(_) | ProtoResponse::Ack => Ok(py.None()),
+                other => Err(unexpected(other)),
+            },
+        )
+    }
+
+    /// List all entities in the world; resolves to a list of `EntityInfo`.
+    pub fn list<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.submit(py, ProtoRequest::List, |py, response| match response {
+            ProtoResponse::Entities { entities } => Ok(entities
+                .into_iter()
+                .map(PyEntityInfo::from)
+                .collect::<Vec<_>>()
+                .into_pyobject(py)?
+                .into_any()
+                .unbind()),
+            other => Err(unexpected(other)),
+        })
+    }
+
+    /// Traffic statistics of the viewer's connections (see `TransportClient.stats`).
+    pub fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.submit(py, ProtoRequest::Stats, |py, response| match response {
+            ProtoResponse::Stats { peers } => {
+                let json = serde_json::to_string(&peers)
+                    .map_err(|err| PyValueError::new_err(err.to_string()))?;
+                Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+            }
+            other => Err(unexpected(other)),
+        })
+    }
+
+    /// Connection state of the underlying transport (see `TransportClient.connection_state`).
+    pub fn connection_state(&self) -> String {
+        match self.lock().as_ref() {
+            Some(controller) => controller.state().to_string(),
+            None => "closed (shut down)".to_string(),
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.lock()
+            .as_ref()
+            .is_some_and(TransportController::is_connected)
+    }
+
+    /// Disconnect from the viewer. Awaitables still pending raise
+    /// `TransportConnectionError`.
+    pub fn close(&self, py: Python<'_>) {
+        let controller = self.lock().take();
+        // the transport thread needs the GIL to resolve pending awaitables
+        py.detach(move || drop(controller));
+    }
+}
+
+impl AsyncWorld {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<TransportController>> {
+        self.controller
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Queue `request` and return an asyncio future resolved with its converted response.
+    fn submit<'py>(
+        &self,
+        py: Python<'py>,
+        request: ProtoRequest,
+        convert: Convert,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+        let future = event_loop.call_method0("create_future")?;
+        let (event_loop, resolved) = (event_loop.unbind(), future.clone().unbind());
+        let on_response = Box::new(move |response: Result<ProtoResponse, TransportError>| {
+            Python::attach(|py| {
+                let result = response
+                    .map_err(transport_error)
+                    .and_then(|response| convert(py, response));
+                let resolve = ResolveFuture {
+                    future: resolved,
+                    result: Some(result),
+                };
+                // fails only if the loop was closed in the meantime
+                let _ = event_loop
+                    .bind(py)
+                    .call_method1("call_soon_threadsafe", (resolve,));
+            });
+        });
+        match self.lock().as_ref() {
+            Some(controller) => controller
+                .submit(request, on_response)
+                .map_err(transport_error)?,
+            None => {
+                return Err(transport_error(TransportError::Closed(
+                    "shut down".to_string(),
+                )));
+            }
+        }
+        Ok(future)
+    }
+}
+
+impl Drop for AsyncWorld {
+    fn drop(&mut self) {
+        let controller = self.lock().take();
+        if controller.is_some() {
+            Python::attach(|py| py.detach(move || drop(controller)));
+        }
+    }
+}
+
+/// Sets the result of a future on its event loop's thread.
+#[pyclass]
+struct ResolveFuture {
+    future: Py<PyAny>,
+    result: Option<PyResult<Py<PyAny>>>,
+}
+
+#[pymethods]
+impl ResolveFuture {
+    fn __call__(&mut self, py: Python<'_>) -> PyResult<()> {
+        let future = self.future.bind(py);
+        // e.g. cancelled by `asyncio.wait_for`
+        if future.call_method0("done")?.is_truthy()? {
+            return Ok(());
+        }
+        match self.result.take() {
+            Some(Ok(value)) => future.call_method1("set_result", (value,))?,
+            Some(Err(err)) => future.call_method1("set_exception", (err.into_value(py),))?,
+            None => return Ok(()),
+        };
+        Ok(())
+    }
+}
+
+fn unexpected(response: ProtoResponse) -> PyErr {
+    match response {
+        ProtoResponse::Error { message } => PyValueError::new_err(message),
+        other => PyValueError::new_err(format!("unexpected response: {:?}", other)),
+    }
+}
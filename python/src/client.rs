@@ -1,4 +1,9 @@
-use pyo3::{exceptions::PyValueError, prelude::*};
+use pyo3::{
+    create_exception,
+    exceptions::{PyConnectionError, PyTimeoutError, PyValueError},
+    prelude::*,
+    types::PyList,
+};
 use std::time::Duration;
 
 use dimensify_transport::{
@@ -6,8 +11,26 @@ use dimensify_transport::{
     TransportEndpoint,
 };
 
+use dimensify_protocol::{
+    BulkCompression, BulkData, TelemetryBinding, TransportError, ViewerEvent, ViewerEventKind,
+    WidgetCommand, prelude::Entity,
+};
+
 use crate::metadata::{PyComponentInfo, PyEntityInfo};
 
+create_exception!(
+    dimensify,
+    TransportConnectionError,
+    PyConnectionError,
+    "The transport is not connected, or was closed."
+);
+create_exception!(
+    dimensify,
+    TransportTimeoutError,
+    PyTimeoutError,
+    "No response arrived in time although the transport is connected."
+);
+
 /// A client for the transport layer.
 #[pyclass(unsendable)]
 pub struct TransportClient {
@@ -16,7 +39,7 @@ pub struct TransportClient {
 
 #[pymethods]
 impl TransportClient {
-    #[pyo3(signature = (server_addr=None, mode=None, client_addr=None, cert_digest=None, tick_hz=None, connection=None, endpoint=None))]
+    #[pyo3(signature = (server_addr=None, mode=None, client_addr=None, cert_digest=None, tick_hz=None, connection=None, endpoint=None, heartbeat_timeout_secs=None, reconnect_attempts=None, token=None))]
     #[new]
     pub fn new(
         server_addr: Option<String>,
@@ -26,6 +49,9 @@ impl TransportClient {
         tick_hz: Option<f32>,
         connection: Option<String>,
         endpoint: Option<String>,
+        heartbeat_timeout_secs: Option<f32>,
+        reconnect_attempts: Option<u32>,
+        token: Option<String>,
     ) -> PyResult<Self> {
         let mut config = TransportConfig::default();
         config.connection =
@@ -61,6 +87,12 @@ impl TransportClient {
             config.tick_hz = hz;
         }
 
+        if let Some(secs) = heartbeat_timeout_secs {
+            config.heartbeat_timeout_secs = secs;
+        }
+        config.reconnect_attempts = reconnect_attempts;
+        config.token = token;
+
         Ok(Self {
             controller: TransportController::start(config),
         })
@@ -88,6 +120,157 @@ impl TransportClient {
             ))),
         }
     }
+
+    /// Replace the viewer's telemetry bindings.
+    ///
+    /// `bindings` is a JSON array of `{"path", "entity", "target"}` objects, e.g.
+    /// `{"path": "sim/q", "entity": "panda", "target": "JointState"}`.
+    #[pyo3(signature = (bindings, timeout_ms=None))]
+    pub fn set_telemetry_bindings(
+        &self,
+        bindings: String,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<()> {
+        let bindings: Vec<TelemetryBinding> = serde_json::from_str(&bindings)
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+        self.expect_ack(ProtoRequest::SetTelemetryBindings(bindings), timeout_ms)
+    }
+
+    /// Create or replace a widget in the viewer's widget panel.
+    ///
+    /// `widget` is a JSON object in the format of widget JSONL files, e.g.
+    /// `{"type": "Slider", "id": "gain", "text": "Gain", "value": 1.0, "min": 0.0, "max": 10.0}`.
+    /// Interactions with the widget are returned by `poll_events` without subscribing.
+    #[pyo3(signature = (widget, timeout_ms=None))]
+    pub fn set_widget(&self, widget: String, timeout_ms: Option<u64>) -> PyResult<()> {
+        self.expect_ack(ProtoRequest::Widget(parse_widget(&widget)?), timeout_ms)
+    }
+
+    /// Remove a widget created with `set_widget`.
+    #[pyo3(signature = (id, timeout_ms=None))]
+    pub fn remove_widget(&self, id: String, timeout_ms: Option<u64>) -> PyResult<()> {
+        self.expect_ack(
+            ProtoRequest::Widget(WidgetCommand::Remove { id }),
+            timeout_ms,
+        )
+    }
+
+    /// Write the viewer's visible scene as glTF 2.0 to `path` on the viewer's machine,
+    /// relative to its export directory (`DIMENSIFY_EXPORT_DIR`). Needs the admin role.
+    ///
+    /// A `.glb` path gets binary glTF. With `joints`, robots keep their current joint
+    /// values, otherwise they are exported in their zero configuration.
+    #[pyo3(signature = (path, joints=true, timeout_ms=None))]
+    pub fn export_gltf(&self, path: String, joints: bool, timeout_ms: Option<u64>) -> PyResult<()> {
+        self.expect_ack(ProtoRequest::ExportGltf { path, joints }, timeout_ms)
+    }
+
+    /// Load the robot described by the URDF file at `path` on the viewer's machine.
+    /// Needs the admin role and a viewer built with the `robot` feature.
+    #[pyo3(signature = (path, fixed_base=false, timeout_ms=None))]
+    pub fn load_urdf(
+        &self,
+        path: String,
+        fixed_base: bool,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<()> {
+        self.expect_ack(ProtoRequest::LoadUrdf { path, fixed_base }, timeout_ms)
+    }
+
+    /// Subscribe to viewer events, replacing any earlier subscription.
+    ///
+    /// `events` names the kinds to receive: "picking", "selection", "transform_edit",
+    /// "widget" and "collision". `None` subscribes to all, an empty list unsubscribes.
+    /// Received events are returned by `poll_events`.
+    #[pyo3(signature = (events=None, timeout_ms=None))]
+    pub fn subscribe(&self, events: Option<Vec<String>>, timeout_ms: Option<u64>) -> PyResult<()> {
+        let kinds = match events {
+            Some(events) => events
+                .iter()
+                .map(|name| {
+                    parse_event_kind(name).ok_or_else(|| {
+                        PyValueError::new_err(format!("unknown event kind '{}'", name))
+                    })
+                })
+                .collect::<PyResult<Vec<_>>>()?,
+            None => ViewerEventKind::ALL.to_vec(),
+        };
+        self.expect_ack(ProtoRequest::Subscribe(kinds), timeout_ms)
+    }
+
+    /// Traffic statistics of every connection of the viewer (or hub).
+    ///
+    /// Returns one dict per connection with `entity`, `addr`, `endpoint`, `role`,
+    /// `rtt_ms`, `pending_requests`, `errors` and `channels`, a list of
+    /// `{"channel", "sent", "received", "sent_per_sec", "received_per_sec"}` dicts.
+    #[pyo3(signature = (timeout_ms=None))]
+    pub fn stats<'py>(
+        &self,
+        py: Python<'py>,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let response = self.send_and_wait(ProtoRequest::Stats, timeout_ms)?;
+        match response {
+            ProtoResponse::Stats { peers } => {
+                let json = serde_json::to_string(&peers)
+                    .map_err(|err| PyValueError::new_err(err.to_string()))?;
+                py.import("json")?.call_method1("loads", (json,))
+            }
+            ProtoResponse::Error { message } => Err(PyValueError::new_err(message)),
+            other => Err(PyValueError::new_err(format!(
+                "unexpected response: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Connection state: "connecting", "connected", "reconnecting (...)" or "closed (...)".
+    pub fn connection_state(&self) -> String {
+        self.controller.state().to_string()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.controller.is_connected()
+    }
+
+    /// Block until connected. Raises `TransportConnectionError` on timeout or if closed.
+    #[pyo3(signature = (timeout_ms=None))]
+    pub fn wait_connected(&self, timeout_ms: Option<u64>) -> PyResult<()> {
+        let timeout = Duration::from_millis(timeout_ms.unwrap_or(5_000));
+        self.controller
+            .wait_connected(timeout)
+            .map_err(transport_error)
+    }
+
+    /// Disconnect and stop the transport thread. Later requests raise
+    /// `TransportConnectionError`.
+    pub fn close(&mut self) {
+        self.controller.close();
+    }
+
+    /// Take the viewer events received since the last call.
+    ///
+    /// Each event is a dict with a `type` key ("Picked", "Selected", "TransformEdited",
+    /// "WidgetClicked", "WidgetToggled", "WidgetChanged" or "CollisionChanged") and the event fields;
+    /// entities are their integer ids. If `callback` is given it is also called with
+    /// each event, in order.
+    #[pyo3(signature = (callback=None))]
+    pub fn poll_events<'py>(
+        &self,
+        py: Python<'py>,
+        callback: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let json = py.import("json")?;
+        let events = PyList::empty(py);
+        while let Some(event) = self.controller.try_recv_event() {
+            let event = json.call_method1("loads", (event_json(&event)?,))?;
+            if let Some(callback) = &callback {
+                callback.call1((event.clone(),))?;
+            }
+            events.append(event)?;
+        }
+        Ok(events)
+    }
 }
 
 impl TransportClient {
@@ -107,10 +290,34 @@ impl TransportClient {
         }
     }
 
+    pub(crate) fn into_controller(self) -> TransportController {
+        self.controller
+    }
+
     pub(crate) fn send(&self, request: ProtoRequest) -> PyResult<()> {
+        self.controller.send(request).map_err(transport_error)
+    }
+
+    pub(crate) fn send_bulk(
+        &self,
+        entity: Entity,
+        data: &BulkData,
+        compression: Option<&str>,
+    ) -> PyResult<()> {
+        let compression = match compression.map(str::to_ascii_lowercase).as_deref() {
+            None | Some("none") => BulkCompression::None,
+            Some("zstd") => BulkCompression::Zstd,
+            Some("lz4") => BulkCompression::Lz4,
+            Some(other) => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown compression '{}'",
+                    other
+                )));
+            }
+        };
         self.controller
-            .send(request)
-            .or_else(|err| Err(PyValueError::new_err(err.to_string())))
+            .send_bulk(entity, data, compression)
+            .map_err(transport_error)
     }
 
     pub(crate) fn send_and_wait(
@@ -121,10 +328,64 @@ impl TransportClient {
         let timeout = Duration::from_millis(timeout_ms.unwrap_or(1_000));
         self.controller
             .send_and_wait(request, timeout)
-            .or_else(|err| Err(PyValueError::new_err(err.to_string())))
+            .map_err(transport_error)
     }
 }
 
+pub(crate) fn transport_error(err: TransportError) -> PyErr {
+    match err {
+        TransportError::Timeout(_) => TransportTimeoutError::new_err(err.to_string()),
+        TransportError::NotConnected(_) | TransportError::Closed(_) => {
+            TransportConnectionError::new_err(err.to_string())
+        }
+        other => PyValueError::new_err(other.to_string()),
+    }
+}
+
+/// Read `{"type": "Slider", ...}`, the format of widget JSONL files.
+fn parse_widget(json: &str) -> PyResult<WidgetCommand> {
+    let mut fields: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(json).map_err(|err| PyValueError::new_err(err.to_string()))?;
+    let kind = match fields.remove("type") {
+        Some(serde_json::Value::String(kind)) => kind,
+        _ => return Err(PyValueError::new_err("a widget needs a \"type\" field")),
+    };
+    let mut tagged = serde_json::Map::new();
+    tagged.insert(kind, serde_json::Value::Object(fields));
+    serde_json::from_value(serde_json::Value::Object(tagged))
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+fn parse_event_kind(name: &str) -> Option<ViewerEventKind> {
+    match name.to_ascii_lowercase().as_str() {
+        "picking" => Some(ViewerEventKind::Picking),
+        "selection" => Some(ViewerEventKind::Selection),
+        "transform_edit" => Some(ViewerEventKind::TransformEdit),
+        "widget" => Some(ViewerEventKind::Widget),
+        "collision" => Some(ViewerEventKind::Collision),
+        _ => None,
+    }
+}
+
+/// Flatten `{"Picked": {...}}` into `{"type": "Picked", ...}`.
+fn event_json(event: &ViewerEvent) -> PyResult<String> {
+    let value =
+        serde_json::to_value(event).map_err(|err| PyValueError::new_err(err.to_string()))?;
+    let flattened = match value {
+        serde_json::Value::Object(map) if map.len() == 1 => {
+            let (kind, fields) = map.into_iter().next().expect("one entry");
+            let mut fields = match fields {
+                serde_json::Value::Object(fields) => fields,
+                _ => serde_json::Map::new(),
+            };
+            fields.insert("type".to_string(), serde_json::Value::String(kind));
+            serde_json::Value::Object(fields)
+        }
+        other => other,
+    };
+    Ok(flattened.to_string())
+}
+
 fn parse_connection(value: Option<&str>) -> Option<TransportConnection> {
     match value?.to_ascii_lowercase().as_str() {
         "server" => Some(TransportConnection::Server),
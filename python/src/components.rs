@@ -40,6 +40,7 @@ impl PyComponent {
                 translation, rotation, scale
             ),
             Component::MeshMaterial3d(value) => format!("MeshMaterial3d({:?})", value),
+            Component::Children(children) => format!("Children({} entities)", children.len()),
         };
         Ok(out)
     }
@@ -73,6 +74,18 @@ impl PyComponent {
     pub fn mesh_3d(shape: PyShape3d) -> Self {
         Self(Component::Mesh3d(shape.0))
     }
+
+    /// Create child entities, one per list of components, placed relative to the parent.
+    #[staticmethod]
+    #[pyo3(signature = (*children))]
+    pub fn children(children: Vec<Vec<PyComponent>>) -> Self {
+        Self(Component::Children(
+            children
+                .into_iter()
+                .map(|components| components.into_iter().map(|c| c.0).collect())
+                .collect(),
+        ))
+    }
 }
 
 impl fmt::Display for PyComponent {
@@ -1,5 +1,6 @@
 use pyo3::prelude::*;
 
+pub(crate) mod async_world;
 pub(crate) mod client;
 pub(crate) mod components;
 pub(crate) mod telemetry;
@@ -57,7 +58,7 @@ fn transport_features() -> Vec<String> {
 mod shapes;
 
 #[pymodule]
-fn dimensify(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
+fn dimensify(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<primitives::PyVec2>()?;
     m.add_class::<primitives::PyVec3>()?;
     m.add_class::<primitives::PyVec4>()?;
@@ -68,8 +69,17 @@ fn dimensify(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<metadata::DataSource>()?;
     m.add_class::<metadata::ViewerClient>()?;
     m.add_class::<client::TransportClient>()?;
+    m.add(
+        "TransportConnectionError",
+        py.get_type::<client::TransportConnectionError>(),
+    )?;
+    m.add(
+        "TransportTimeoutError",
+        py.get_type::<client::TransportTimeoutError>(),
+    )?;
     m.add_class::<metadata::PyEntityInfo>()?;
     m.add_class::<world::World>()?;
+    m.add_class::<async_world::AsyncWorld>()?;
     // m.add_class::<components::Name>()?;
     // m.add_class::<components::Transform3d>()?;
     // m.add_class::<components::Mesh3d>()?;
@@ -1,7 +1,10 @@
 use pyo3::{exceptions::PyValueError, prelude::*};
 use std::{fs::OpenOptions, io::Write};
 
-use dimensify_protocol::{TelemetryEvent, TelemetryMetadata, TelemetryPayload, TelemetryTime};
+use dimensify_protocol::{
+    TelemetryEvent, TelemetryImageFormat, TelemetryMetadata, TelemetryPayload, TelemetryTime,
+    prelude::{Quat, Vec3},
+};
 
 /// Append-only telemetry logger (JSONL).
 #[pyclass]
@@ -56,13 +59,189 @@ impl TelemetryClient {
             time,
             timeline,
             TelemetryPayload::Vec3 {
-                value: dimensify_protocol::prelude::Vec3::from([value.0, value.1, value.2]),
+                value: Vec3::from([value.0, value.1, value.2]),
             },
             unit,
             description,
         )
     }
 
+    /// Log a full transform; `rotation` is an `(x, y, z, w)` quaternion.
+    ///
+    /// Use `entity/<name>/transform` as the path to drive a named viewer entity.
+    #[pyo3(signature = (path, time, translation, rotation=(0.0, 0.0, 0.0, 1.0), scale=(1.0, 1.0, 1.0), timeline=None, description=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn log_transform(
+        &self,
+        path: String,
+        time: f64,
+        translation: (f32, f32, f32),
+        rotation: (f32, f32, f32, f32),
+        scale: (f32, f32, f32),
+        timeline: Option<String>,
+        description: Option<String>,
+    ) -> PyResult<()> {
+        self.write_event(
+            path,
+            time,
+            timeline,
+            TelemetryPayload::Transform {
+                translation: Vec3::from([translation.0, translation.1, translation.2]),
+                rotation: Quat::from_xyzw(rotation.0, rotation.1, rotation.2, rotation.3),
+                scale: Vec3::from([scale.0, scale.1, scale.2]),
+            },
+            None,
+            description,
+        )
+    }
+
+    /// Log named joint positions.
+    ///
+    /// Use `entity/<robot name>/joints` as the path to drive a loaded robot.
+    #[pyo3(signature = (path, time, names, positions, timeline=None, unit=None, description=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn log_joint_state(
+        &self,
+        path: String,
+        time: f64,
+        names: Vec<String>,
+        positions: Vec<f64>,
+        timeline: Option<String>,
+        unit: Option<String>,
+        description: Option<String>,
+    ) -> PyResult<()> {
+        if names.len() != positions.len() {
+            return Err(PyValueError::new_err(format!(
+                "got {} joint names but {} positions",
+                names.len(),
+                positions.len()
+            )));
+        }
+        self.write_event(
+            path,
+            time,
+            timeline,
+            TelemetryPayload::JointState { names, positions },
+            unit,
+            description,
+        )
+    }
+
+    /// Log a point cloud with optional RGBA colours (one for all points, or one per point).
+    #[pyo3(signature = (path, time, positions, colors=None, radius=None, timeline=None, description=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn log_points(
+        &self,
+        path: String,
+        time: f64,
+        positions: Vec<(f32, f32, f32)>,
+        colors: Option<Vec<(u8, u8, u8, u8)>>,
+        radius: Option<f32>,
+        timeline: Option<String>,
+        description: Option<String>,
+    ) -> PyResult<()> {
+        let colors: Vec<[u8; 4]> = colors
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(r, g, b, a)| [r, g, b, a])
+            .collect();
+        if colors.len() > 1 && colors.len() != positions.len() {
+            return Err(PyValueError::new_err(format!(
+                "got {} colors for {} points",
+                colors.len(),
+                positions.len()
+            )));
+        }
+        self.write_event(
+            path,
+            time,
+            timeline,
+            TelemetryPayload::Points3d {
+                positions: positions
+                    .into_iter()
+                    .map(|(x, y, z)| Vec3::from([x, y, z]))
+                    .collect(),
+                colors,
+                radius,
+            },
+            None,
+            description,
+        )
+    }
+
+    /// Log a connected line strip.
+    #[pyo3(signature = (path, time, points, color=None, timeline=None, description=None))]
+    pub fn log_line_strip(
+        &self,
+        path: String,
+        time: f64,
+        points: Vec<(f32, f32, f32)>,
+        color: Option<(u8, u8, u8, u8)>,
+        timeline: Option<String>,
+        description: Option<String>,
+    ) -> PyResult<()> {
+        self.write_event(
+            path,
+            time,
+            timeline,
+            TelemetryPayload::LineStrip3d {
+                points: points
+                    .into_iter()
+                    .map(|(x, y, z)| Vec3::from([x, y, z]))
+                    .collect(),
+                color: color.map(|(r, g, b, a)| [r, g, b, a]),
+            },
+            None,
+            description,
+        )
+    }
+
+    /// Log an image.
+    ///
+    /// `format` is one of `rgb8`, `rgba8`, `depth_f32`, `depth_u16`, `png` or `jpeg`.
+    /// Width and height are ignored for encoded formats.
+    #[pyo3(signature = (path, time, data, format, width=0, height=0, timeline=None, description=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn log_image(
+        &self,
+        path: String,
+        time: f64,
+        data: Vec<u8>,
+        format: &str,
+        width: u32,
+        height: u32,
+        timeline: Option<String>,
+        description: Option<String>,
+    ) -> PyResult<()> {
+        let format = match format {
+            "rgb8" => TelemetryImageFormat::Rgb8,
+            "rgba8" => TelemetryImageFormat::Rgba8,
+            "depth_f32" => TelemetryImageFormat::DepthF32,
+            "depth_u16" => TelemetryImageFormat::DepthU16,
+            "png" => TelemetryImageFormat::Png,
+            "jpeg" | "jpg" => TelemetryImageFormat::Jpeg,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "unsupported image format '{}'",
+                    other
+                )));
+            }
+        };
+        self.write_event(
+            path,
+            time,
+            timeline,
+            TelemetryPayload::Image {
+                width,
+                height,
+                format,
+                data,
+            },
+            None,
+            description,
+        )
+    }
+
     /// Log a text value to a telemetry path.
     #[pyo3(signature = (path, time, value, timeline=None, unit=None, description=None))]
     pub fn log_text(
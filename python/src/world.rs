@@ -11,7 +11,7 @@ use crate::{
     components::PyComponent,
     metadata::{PyEntity, PyEntityInfo},
 };
-use dimensify_protocol::WorldCommand;
+use dimensify_protocol::{BulkData, WorldCommand};
 
 /// A Bevy-like world API backed by the transport layer.
 #[pyclass(unsendable)]
@@ -23,7 +23,7 @@ pub struct World {
 
 #[pymethods]
 impl World {
-    #[pyo3(signature = (server_addr=None, mode=None, client_addr=None, cert_digest=None, tick_hz=None))]
+    #[pyo3(signature = (server_addr=None, mode=None, client_addr=None, cert_digest=None, tick_hz=None, token=None))]
     #[new]
     pub fn new(
         server_addr: Option<String>,
@@ -31,6 +31,7 @@ impl World {
         client_addr: Option<String>,
         cert_digest: Option<String>,
         tick_hz: Option<f32>,
+        token: Option<String>,
     ) -> PyResult<Self> {
         Ok(Self {
             client: TransportClient::new(
@@ -41,6 +42,9 @@ impl World {
                 tick_hz,
                 Some("client".to_string()),
                 Some("controller".to_string()),
+                None,
+                None,
+                token,
             )?,
             used_names: HashSet::new(),
             next_id: 0,
@@ -101,6 +105,139 @@ impl World {
         Ok(())
     }
 
+    /// Replace the mesh of an entity with a triangle mesh, sent as raw buffers.
+    ///
+    /// `positions` and `normals` are `(x, y, z)` triples; without `normals` they are
+    /// computed by the viewer. `compression` is `"zstd"`, `"lz4"` or `None`.
+    #[pyo3(signature = (entity, positions, indices=None, normals=None, compression=None))]
+    pub fn set_mesh(
+        &self,
+        entity: PyEntity,
+        positions: Vec<[f32; 3]>,
+        indices: Option<Vec<u32>>,
+        normals: Option<Vec<[f32; 3]>>,
+        compression: Option<String>,
+    ) -> PyResult<()> {
+        let data = BulkData::Mesh {
+            positions: positions.into_iter().map(Into::into).collect(),
+            normals: normals
+                .unwrap_or_default()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            indices: indices.unwrap_or_default(),
+        };
+        self.client
+            .send_bulk(entity.0, &data, compression.as_deref())
+    }
+
+    /// Replace the mesh of an entity with a point cloud; `colors` are RGBA in 0..1.
+    #[pyo3(signature = (entity, positions, colors=None, compression=None))]
+    pub fn set_point_cloud(
+        &self,
+        entity: PyEntity,
+        positions: Vec<[f32; 3]>,
+        colors: Option<Vec<[f32; 4]>>,
+        compression: Option<String>,
+    ) -> PyResult<()> {
+        let data = BulkData::PointCloud {
+            positions: positions.into_iter().map(Into::into).collect(),
+            colors: colors
+                .unwrap_or_default()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        };
+        self.client
+            .send_bulk(entity.0, &data, compression.as_deref())
+    }
+
+    /// Use an RGBA8 image (`width * height * 4` bytes) as the entity's texture.
+    #[pyo3(signature = (entity, width, height, rgba, compression=None))]
+    pub fn set_image(
+        &self,
+        entity: PyEntity,
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+        compression: Option<String>,
+    ) -> PyResult<()> {
+        let data = BulkData::Image {
+            width,
+            height,
+            rgba8: rgba,
+        };
+        self.client
+            .send_bulk(entity.0, &data, compression.as_deref())
+    }
+
+    /// Connection state of the underlying transport (see `TransportClient.connection_state`).
+    pub fn connection_state(&self) -> String {
+        self.client.connection_state()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.client.is_connected()
+    }
+
+    /// Block until connected. Raises `TransportConnectionError` on timeout or if closed.
+    #[pyo3(signature = (timeout_ms=None))]
+    pub fn wait_connected(&self, timeout_ms: Option<u64>) -> PyResult<()> {
+        self.client.wait_connected(timeout_ms)
+    }
+
+    /// Disconnect from the viewer.
+    pub fn close(&mut self) {
+        self.client.close();
+    }
+
+    /// Create or replace a widget in the viewer's widget panel.
+    ///
+    /// `widget` is a dict (or JSON string) such as
+    /// `{"type": "Button", "id": "reset", "text": "Reset"}`; see `TransportClient.set_widget`.
+    #[pyo3(signature = (widget, timeout_ms=None))]
+    pub fn set_widget(
+        &self,
+        py: Python<'_>,
+        widget: Bound<'_, PyAny>,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<()> {
+        let json = match widget.extract::<String>() {
+            Ok(json) => json,
+            Err(_) => py
+                .import("json")?
+                .call_method1("dumps", (widget,))?
+                .extract()?,
+        };
+        self.client.set_widget(json, timeout_ms)
+    }
+
+    /// Remove a widget created with `set_widget`.
+    #[pyo3(signature = (id, timeout_ms=None))]
+    pub fn remove_widget(&self, id: String, timeout_ms: Option<u64>) -> PyResult<()> {
+        self.client.remove_widget(id, timeout_ms)
+    }
+
+    /// Take the viewer events received since the last call (see `TransportClient.poll_events`).
+    #[pyo3(signature = (callback=None))]
+    pub fn poll_events<'py>(
+        &self,
+        py: Python<'py>,
+        callback: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, pyo3::types::PyList>> {
+        self.client.poll_events(py, callback)
+    }
+
+    /// Traffic statistics of the viewer's connections (see `TransportClient.stats`).
+    #[pyo3(signature = (timeout_ms=None))]
+    pub fn stats<'py>(
+        &self,
+        py: Python<'py>,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.client.stats(py, timeout_ms)
+    }
+
     /// List all entities in the world.
     #[pyo3(signature = (timeout_ms=None))]
     pub fn list(&self, timeout_ms: Option<u64>) -> PyResult<Vec<PyEntityInfo>> {